@@ -0,0 +1,37 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Integration with [async-std], enabled using the `async-std` feature.
+//!
+//! `Socket` can be converted into the types in `async_std::net` (and
+//! `async_std::os::unix::net` on Unix) using the [`TryFrom`] trait, which
+//! checks the socket's domain and type and sets it to nonblocking mode.
+//!
+//! [async-std]: https://docs.rs/async-std
+//! [`TryFrom`]: std::convert::TryFrom
+
+use std::net;
+#[cfg(unix)]
+use std::os::unix::net as unix_net;
+
+#[cfg(unix)]
+use async_std::os::unix::net as async_unix_net;
+
+try_from_socket! {
+    net::TcpStream => async_std::net::TcpStream = |s| Ok(s.into()),
+    net::TcpListener => async_std::net::TcpListener = |s| Ok(s.into()),
+    net::UdpSocket => async_std::net::UdpSocket = |s| Ok(s.into()),
+    #[cfg(unix)]
+    unix_net::UnixStream => async_unix_net::UnixStream = |s| Ok(s.into()),
+    #[cfg(unix)]
+    unix_net::UnixListener => async_unix_net::UnixListener = |s| Ok(s.into()),
+    #[cfg(unix)]
+    unix_net::UnixDatagram => async_unix_net::UnixDatagram = |s| Ok(s.into()),
+}
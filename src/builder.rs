@@ -0,0 +1,248 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::io;
+use std::time::Duration;
+
+use crate::sys::c_int;
+use crate::{Domain, Protocol, SockAddr, Socket, TcpKeepalive, Type};
+
+/// Builder for a [`Socket`], see [`Socket::builder`].
+///
+/// The builder collects socket options and applies them in the correct order
+/// when the socket is created. For example `SO_REUSEADDR` is set before the
+/// socket is bound and the socket is only made nonblocking after it's
+/// connected. Options that are not set keep the OS's default value.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use std::net::SocketAddr;
+///
+/// use socket2::{Domain, Socket, Type};
+///
+/// let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+/// let listener = Socket::builder(Domain::IPV4, Type::STREAM)
+///     .with_reuse_address(true)
+///     .with_nonblocking(true)
+///     .listen(&addr.into(), 128)?;
+/// # assert!(listener.reuse_address()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SocketBuilder {
+    domain: Domain,
+    type_: Type,
+    protocol: Option<Protocol>,
+    nonblocking: Option<bool>,
+    reuse_address: Option<bool>,
+    only_v6: Option<bool>,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+    keepalive: Option<TcpKeepalive>,
+    nodelay: Option<bool>,
+    linger: Option<Option<Duration>>,
+    read_timeout: Option<Option<Duration>>,
+    write_timeout: Option<Option<Duration>>,
+}
+
+impl SocketBuilder {
+    /// Returns a new builder for a socket with the given `domain` and
+    /// `type_`, without any options set.
+    pub const fn new(domain: Domain, type_: Type) -> SocketBuilder {
+        SocketBuilder {
+            domain,
+            type_,
+            protocol: None,
+            nonblocking: None,
+            reuse_address: None,
+            only_v6: None,
+            recv_buffer_size: None,
+            send_buffer_size: None,
+            keepalive: None,
+            nodelay: None,
+            linger: None,
+            read_timeout: None,
+            write_timeout: None,
+        }
+    }
+
+    /// Set the protocol of the socket, see [`Socket::new`].
+    pub const fn with_protocol(self, protocol: Protocol) -> Self {
+        Self {
+            protocol: Some(protocol),
+            ..self
+        }
+    }
+
+    /// Set the socket's nonblocking mode, see [`Socket::set_nonblocking`].
+    ///
+    /// This is applied last, i.e. after the socket is connected.
+    pub const fn with_nonblocking(self, nonblocking: bool) -> Self {
+        Self {
+            nonblocking: Some(nonblocking),
+            ..self
+        }
+    }
+
+    /// Set the `SO_REUSEADDR` option, see [`Socket::set_reuse_address`].
+    pub const fn with_reuse_address(self, reuse: bool) -> Self {
+        Self {
+            reuse_address: Some(reuse),
+            ..self
+        }
+    }
+
+    /// Set the `IPV6_V6ONLY` option, see [`Socket::set_only_v6`].
+    pub const fn with_only_v6(self, only_v6: bool) -> Self {
+        Self {
+            only_v6: Some(only_v6),
+            ..self
+        }
+    }
+
+    /// Set the `SO_RCVBUF` option, see [`Socket::set_recv_buffer_size`].
+    pub const fn with_recv_buffer_size(self, size: usize) -> Self {
+        Self {
+            recv_buffer_size: Some(size),
+            ..self
+        }
+    }
+
+    /// Set the `SO_SNDBUF` option, see [`Socket::set_send_buffer_size`].
+    pub const fn with_send_buffer_size(self, size: usize) -> Self {
+        Self {
+            send_buffer_size: Some(size),
+            ..self
+        }
+    }
+
+    /// Enable TCP keepalive using `params`, see
+    /// [`Socket::set_tcp_keepalive`].
+    pub const fn with_keepalive(self, params: TcpKeepalive) -> Self {
+        Self {
+            keepalive: Some(params),
+            ..self
+        }
+    }
+
+    /// Set the `TCP_NODELAY` option, see [`Socket::set_tcp_nodelay`].
+    pub const fn with_nodelay(self, nodelay: bool) -> Self {
+        Self {
+            nodelay: Some(nodelay),
+            ..self
+        }
+    }
+
+    /// Set the `SO_LINGER` option, see [`Socket::set_linger`].
+    pub const fn with_linger(self, linger: Option<Duration>) -> Self {
+        Self {
+            linger: Some(linger),
+            ..self
+        }
+    }
+
+    /// Set the `SO_RCVTIMEO` option, see [`Socket::set_read_timeout`].
+    pub const fn with_read_timeout(self, timeout: Option<Duration>) -> Self {
+        Self {
+            read_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set the `SO_SNDTIMEO` option, see [`Socket::set_write_timeout`].
+    pub const fn with_write_timeout(self, timeout: Option<Duration>) -> Self {
+        Self {
+            write_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Create the socket and apply all options, without binding or
+    /// connecting it.
+    pub fn build(self) -> io::Result<Socket> {
+        let socket = self.create()?;
+        self.finish(socket)
+    }
+
+    /// Create the socket, apply all options and bind it to `addr`.
+    pub fn bind(self, addr: &SockAddr) -> io::Result<Socket> {
+        let socket = self.create()?;
+        socket.bind(addr)?;
+        self.finish(socket)
+    }
+
+    /// Create the socket, apply all options, bind it to `addr` and start
+    /// listening for incoming connections.
+    pub fn listen(self, addr: &SockAddr, backlog: c_int) -> io::Result<Socket> {
+        let socket = self.create()?;
+        socket.bind(addr)?;
+        socket.listen(backlog)?;
+        self.finish(socket)
+    }
+
+    /// Create the socket, apply all options and connect it to `addr`.
+    ///
+    /// The socket is connected in blocking mode, even if
+    /// [`with_nonblocking`] is set.
+    ///
+    /// [`with_nonblocking`]: SocketBuilder::with_nonblocking
+    pub fn connect(self, addr: &SockAddr) -> io::Result<Socket> {
+        let socket = self.create()?;
+        socket.connect(addr)?;
+        self.finish(socket)
+    }
+
+    /// Create the socket and apply all options that must be set before the
+    /// socket is bound or connected.
+    fn create(&self) -> io::Result<Socket> {
+        let socket = Socket::new(self.domain, self.type_, self.protocol)?;
+        if let Some(only_v6) = self.only_v6 {
+            socket.set_only_v6(only_v6)?;
+        }
+        if let Some(reuse) = self.reuse_address {
+            socket.set_reuse_address(reuse)?;
+        }
+        // Buffer sizes must be set before connecting or listening for the TCP
+        // window scaling to take them into account.
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(ref params) = self.keepalive {
+            socket.set_tcp_keepalive(params)?;
+        }
+        if let Some(nodelay) = self.nodelay {
+            socket.set_tcp_nodelay(nodelay)?;
+        }
+        if let Some(linger) = self.linger {
+            socket.set_linger(linger)?;
+        }
+        if let Some(timeout) = self.read_timeout {
+            socket.set_read_timeout(timeout)?;
+        }
+        if let Some(timeout) = self.write_timeout {
+            socket.set_write_timeout(timeout)?;
+        }
+        Ok(socket)
+    }
+
+    /// Apply the options that must be set last.
+    fn finish(&self, socket: Socket) -> io::Result<Socket> {
+        if let Some(nonblocking) = self.nonblocking {
+            socket.set_nonblocking(nonblocking)?;
+        }
+        Ok(socket)
+    }
+}
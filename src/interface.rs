@@ -0,0 +1,58 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversion between network interface names and indices.
+//!
+//! Many socket options, e.g. [`Socket::join_multicast_v6`] and
+//! [`Socket::set_unicast_if_v6`], take the index of a network interface
+//! rather than its name. The functions in this module can be used to convert
+//! between the two.
+//!
+//! [`Socket::join_multicast_v6`]: crate::Socket::join_multicast_v6
+//! [`Socket::set_unicast_if_v6`]: crate::Socket::set_unicast_if_v6
+
+use std::ffi::CString;
+use std::io;
+
+use crate::sys;
+
+/// Returns the index of the network interface with the name `name`.
+///
+/// This function corresponds to `if_nametoindex(3)`.
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use socket2::interface;
+///
+/// # #[cfg(target_os = "linux")] {
+/// let index = interface::name_to_index("lo")?;
+/// assert_eq!(interface::index_to_name(index)?, "lo");
+/// # }
+/// # Ok(())
+/// # }
+/// ```
+pub fn name_to_index(name: &str) -> io::Result<u32> {
+    let name = CString::new(name).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "interface name contains a null byte",
+        )
+    })?;
+    sys::if_nametoindex(&name)
+}
+
+/// Returns the name of the network interface with the index `index`.
+///
+/// This function corresponds to `if_indextoname(3)`.
+pub fn index_to_name(index: u32) -> io::Result<String> {
+    sys::if_indextoname(index)
+}
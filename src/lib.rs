@@ -42,10 +42,46 @@
 // Disallow warnings in examples.
 #![doc(test(attr(deny(warnings))))]
 
+use std::fmt;
 use std::net::SocketAddr;
+use std::ops::{BitOr, BitOrAssign};
+use std::time::Duration;
 
+/// Implements `TryFrom<Socket>` for the types of an I/O library. The socket
+/// is set to nonblocking mode, converted into the standard library type `$std`
+/// (checking the domain and type) and then converted using `$convert`.
+#[cfg(any(feature = "async-std", feature = "mio", feature = "tokio"))]
+macro_rules! try_from_socket {
+    ($( $(#[$meta: meta])* $std: ty => $target: ty = $convert: expr ),* $(,)*) => {
+        $(
+            $(#[$meta])*
+            impl std::convert::TryFrom<crate::Socket> for $target {
+                type Error = std::io::Error;
+
+                /// Converts the socket, setting it to nonblocking mode. Returns
+                /// an error if the socket's domain or type don't match.
+                fn try_from(socket: crate::Socket) -> std::io::Result<$target> {
+                    socket.set_nonblocking(true)?;
+                    <$std as std::convert::TryFrom<crate::Socket>>::try_from(socket)
+                        .and_then($convert)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "async-std")]
+mod async_std_support;
+mod builder;
+#[cfg(not(target_os = "hermit"))]
+pub mod interface;
+#[cfg(feature = "mio")]
+mod mio_support;
 mod sockaddr;
 mod socket;
+mod sockref;
+#[cfg(feature = "tokio")]
+mod tokio_support;
 mod utils;
 
 #[cfg(unix)]
@@ -54,11 +90,53 @@ mod sys;
 #[cfg(windows)]
 #[path = "sys/windows.rs"]
 mod sys;
+#[cfg(target_os = "hermit")]
+#[path = "sys/hermit.rs"]
+mod sys;
 
-use sys::c_int;
+use sys::{c_int, c_short};
 
+pub use builder::SocketBuilder;
 pub use sockaddr::SockAddr;
 pub use socket::Socket;
+pub use sockref::SockRef;
+#[cfg(all(unix, not(target_os = "espidf")))]
+pub use sys::ControlMessage;
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub use sys::PktInfo;
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub use sys::PortRange;
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+pub use sys::TcpConnectionInfo;
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub use sys::UCred;
+#[cfg(unix)]
+pub use sys::{Cmsg, CmsgBuffer, CmsgIter, ControlMessageOwned};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub use sys::{ExtendedError, PmtudMode, TcpRepairQueue};
+#[cfg(target_os = "linux")]
+pub use sys::{TcpInfo, Timestamping, Timestamps, TxTimestamp, ZerocopyCompletion};
 
 /// Specification of the communication domain for a socket.
 ///
@@ -135,10 +213,19 @@ impl Type {
     pub const DGRAM: Type = Type(sys::SOCK_DGRAM);
 
     /// Type corresponding to `SOCK_SEQPACKET`.
+    ///
+    /// # Notes
+    ///
+    /// This is not available on ESP-IDF and Hermit.
+    #[cfg(not(any(target_os = "espidf", target_os = "hermit")))]
     pub const SEQPACKET: Type = Type(sys::SOCK_SEQPACKET);
 
     /// Type corresponding to `SOCK_RAW`.
-    #[cfg(not(target_os = "redox"))]
+    ///
+    /// # Notes
+    ///
+    /// This is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
     pub const RAW: Type = Type(sys::SOCK_RAW);
 }
 
@@ -163,9 +250,19 @@ pub struct Protocol(c_int);
 
 impl Protocol {
     /// Protocol corresponding to `ICMPv4`.
+    ///
+    /// # Notes
+    ///
+    /// This is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
     pub const ICMPV4: Protocol = Protocol(sys::IPPROTO_ICMP);
 
     /// Protocol corresponding to `ICMPv6`.
+    ///
+    /// # Notes
+    ///
+    /// This is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
     pub const ICMPV6: Protocol = Protocol(sys::IPPROTO_ICMPV6);
 
     /// Protocol corresponding to `TCP`.
@@ -186,3 +283,299 @@ impl From<Protocol> for c_int {
         p.0
     }
 }
+
+/// Configures a socket's TCP keepalive parameters.
+///
+/// See [`Socket::set_tcp_keepalive`].
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> std::io::Result<()> {
+/// use std::time::Duration;
+///
+/// use socket2::{Domain, Socket, TcpKeepalive, Type};
+///
+/// let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
+/// let keepalive = TcpKeepalive::new().with_time(Duration::from_secs(60));
+/// socket.set_tcp_keepalive(&keepalive)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TcpKeepalive {
+    #[cfg_attr(target_os = "hermit", allow(dead_code))]
+    time: Option<Duration>,
+    #[cfg_attr(
+        not(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "illumos",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "netbsd",
+            target_os = "redox",
+            target_os = "solaris",
+            windows
+        )),
+        allow(dead_code)
+    )]
+    interval: Option<Duration>,
+    #[cfg_attr(
+        not(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "illumos",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "netbsd",
+            target_os = "redox",
+            target_os = "solaris"
+        )),
+        allow(dead_code)
+    )]
+    retries: Option<u32>,
+}
+
+impl TcpKeepalive {
+    /// Returns a new, empty set of TCP keepalive parameters.
+    ///
+    /// Parameters that are not set keep the OS's default value.
+    pub const fn new() -> TcpKeepalive {
+        TcpKeepalive {
+            time: None,
+            interval: None,
+            retries: None,
+        }
+    }
+
+    /// Set the amount of time after which TCP keepalive probes will be sent
+    /// on idle connections.
+    ///
+    /// This will set `TCP_KEEPALIVE` on macOS and iOS, and `TCP_KEEPIDLE` on
+    /// all other Unix operating systems, except OpenBSD, on which this is
+    /// ignored as there is no corresponding option. On Hermit this is always
+    /// ignored.
+    ///
+    /// On Windows, this sets the value of the `tcp_keepalive` struct's
+    /// `keepalivetime` field.
+    ///
+    /// Some platforms specify this value in seconds, so sub-second
+    /// specifications may be omitted.
+    pub fn with_time(self, time: Duration) -> TcpKeepalive {
+        TcpKeepalive {
+            time: Some(time),
+            ..self
+        }
+    }
+
+    /// Set the value of the `TCP_KEEPINTVL` option. On Windows, this sets the
+    /// value of the `tcp_keepalive` struct's `keepaliveinterval` field.
+    ///
+    /// Sets the time interval between TCP keepalive probes.
+    ///
+    /// Some platforms specify this value in seconds, so sub-second
+    /// specifications may be omitted.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// illumos, iOS, Linux, macOS, NetBSD, Redox, Solaris and Windows.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "illumos",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "redox",
+        target_os = "solaris",
+        windows
+    ))]
+    pub fn with_interval(self, interval: Duration) -> TcpKeepalive {
+        TcpKeepalive {
+            interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Set the value of the `TCP_KEEPCNT` option.
+    ///
+    /// Set the maximum number of TCP keepalive probes that will be sent before
+    /// dropping a connection, if TCP keepalive is enabled on this socket.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// illumos, iOS, Linux, macOS, NetBSD, Redox and Solaris.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "illumos",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "redox",
+        target_os = "solaris"
+    ))]
+    pub fn with_retries(self, retries: u32) -> TcpKeepalive {
+        TcpKeepalive {
+            retries: Some(retries),
+            ..self
+        }
+    }
+}
+
+/// Flags for incoming messages.
+///
+/// Flags provide additional information about incoming messages, they are
+/// returned by the `recvmsg(2)` based receive methods, e.g.
+/// [`Socket::recv_vectored`].
+///
+/// # Notes
+///
+/// This type is not available on Hermit.
+#[cfg(not(target_os = "hermit"))]
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct RecvFlags(c_int);
+
+#[cfg(not(target_os = "hermit"))]
+impl RecvFlags {
+    /// Check if the message contains a truncated datagram, i.e. the datagram
+    /// was larger than the supplied buffer(s).
+    ///
+    /// This flag is only used for datagram-based sockets, not for
+    /// connection-oriented sockets.
+    ///
+    /// This corresponds to the `MSG_TRUNC` flag.
+    pub fn is_truncated(self) -> bool {
+        self.0 & sys::MSG_TRUNC != 0
+    }
+
+    /// Check if the control data (ancillary data) was truncated because the
+    /// supplied control buffer was too small.
+    ///
+    /// This corresponds to the `MSG_CTRUNC` flag.
+    pub fn is_control_truncated(self) -> bool {
+        self.0 & sys::MSG_CTRUNC != 0
+    }
+}
+
+#[cfg(not(target_os = "hermit"))]
+impl From<RecvFlags> for c_int {
+    fn from(f: RecvFlags) -> c_int {
+        f.0
+    }
+}
+
+#[cfg(not(target_os = "hermit"))]
+impl fmt::Debug for RecvFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut builder = f.debug_struct("RecvFlags");
+        builder.field("is_truncated", &self.is_truncated());
+        builder.field("is_control_truncated", &self.is_control_truncated());
+        #[cfg(unix)]
+        builder.field("is_end_of_record", &self.is_end_of_record());
+        builder.finish()
+    }
+}
+
+/// Readiness events to wait for, used in [`Socket::poll`].
+///
+/// Interests can be combined using the `|` operator.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct Interest(c_short);
+
+impl Interest {
+    /// Interest in the socket becoming readable, `POLLIN`.
+    pub const READABLE: Interest = Interest(sys::POLLIN);
+    /// Interest in the socket becoming writable, `POLLOUT`.
+    pub const WRITABLE: Interest = Interest(sys::POLLOUT);
+
+    /// Returns `true` if the interest includes [`Interest::READABLE`].
+    pub fn is_readable(self) -> bool {
+        self.0 & sys::POLLIN != 0
+    }
+
+    /// Returns `true` if the interest includes [`Interest::WRITABLE`].
+    pub fn is_writable(self) -> bool {
+        self.0 & sys::POLLOUT != 0
+    }
+}
+
+impl BitOr for Interest {
+    type Output = Interest;
+
+    fn bitor(self, rhs: Interest) -> Interest {
+        Interest(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Interest {
+    fn bitor_assign(&mut self, rhs: Interest) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl fmt::Debug for Interest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut builder = f.debug_struct("Interest");
+        builder.field("is_readable", &self.is_readable());
+        builder.field("is_writable", &self.is_writable());
+        builder.finish()
+    }
+}
+
+/// Readiness of a socket, as returned by [`Socket::poll`].
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct Readiness(c_short);
+
+impl Readiness {
+    /// Check if the socket is readable.
+    ///
+    /// This corresponds to the `POLLIN` event.
+    pub fn is_readable(self) -> bool {
+        self.0 & sys::POLLIN != 0
+    }
+
+    /// Check if the socket is writable.
+    ///
+    /// This corresponds to the `POLLOUT` event.
+    pub fn is_writable(self) -> bool {
+        self.0 & sys::POLLOUT != 0
+    }
+
+    /// Check if an error is pending on the socket, which can be retrieved
+    /// using [`Socket::take_error`].
+    ///
+    /// This corresponds to the `POLLERR` event.
+    pub fn is_error(self) -> bool {
+        self.0 & sys::POLLERR != 0
+    }
+
+    /// Check if the connection was closed (hang up).
+    ///
+    /// This corresponds to the `POLLHUP` event.
+    pub fn is_hup(self) -> bool {
+        self.0 & sys::POLLHUP != 0
+    }
+}
+
+impl fmt::Debug for Readiness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut builder = f.debug_struct("Readiness");
+        builder.field("is_readable", &self.is_readable());
+        builder.field("is_writable", &self.is_writable());
+        builder.field("is_error", &self.is_error());
+        builder.field("is_hup", &self.is_hup());
+        builder.finish()
+    }
+}
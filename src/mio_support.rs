@@ -0,0 +1,75 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Integration with [mio], enabled using the `mio` feature.
+//!
+//! On Unix [`Socket`] implements mio's [`Source`] trait, so it can be
+//! registered with a [`Registry`] directly. On all platforms `Socket` can be
+//! converted into the types in [`mio::net`] using the [`TryFrom`] trait,
+//! which checks the socket's domain and type and sets it to nonblocking
+//! mode.
+//!
+//! [mio]: https://docs.rs/mio
+//! [`Source`]: mio::event::Source
+//! [`Registry`]: mio::Registry
+
+use std::io;
+use std::net;
+#[cfg(unix)]
+use std::os::unix::net as unix_net;
+
+#[cfg(unix)]
+use mio::event::Source;
+#[cfg(unix)]
+use mio::unix::SourceFd;
+#[cfg(unix)]
+use mio::{Interest, Registry, Token};
+
+use crate::Socket;
+
+/// The socket must be in nonblocking mode, see [`Socket::set_nonblocking`].
+#[cfg(unix)]
+impl Source for Socket {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.inner).register(registry, token, interests)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interests: Interest,
+    ) -> io::Result<()> {
+        SourceFd(&self.inner).reregister(registry, token, interests)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        SourceFd(&self.inner).deregister(registry)
+    }
+}
+
+try_from_socket! {
+    net::TcpStream => mio::net::TcpStream = |s| Ok(mio::net::TcpStream::from_std(s)),
+    net::TcpListener => mio::net::TcpListener = |s| Ok(mio::net::TcpListener::from_std(s)),
+    net::UdpSocket => mio::net::UdpSocket = |s| Ok(mio::net::UdpSocket::from_std(s)),
+    #[cfg(unix)]
+    unix_net::UnixStream => mio::net::UnixStream = |s| Ok(mio::net::UnixStream::from_std(s)),
+    #[cfg(unix)]
+    unix_net::UnixListener => mio::net::UnixListener =
+        |s| Ok(mio::net::UnixListener::from_std(s)),
+    #[cfg(unix)]
+    unix_net::UnixDatagram => mio::net::UnixDatagram =
+        |s| Ok(mio::net::UnixDatagram::from_std(s)),
+}
@@ -1,7 +1,7 @@
 use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::{fmt, mem};
 
-#[cfg(any(unix, target_os = "redox"))]
+#[cfg(any(unix, target_os = "hermit"))]
 use libc::{
     sa_family_t, sockaddr_in, sockaddr_in6, sockaddr_storage, socklen_t, AF_INET, AF_INET6,
 };
@@ -33,6 +33,21 @@ impl fmt::Debug for SockAddr {
         } else if let Some(addr) = self.as_inet6() {
             builder.field("inet6", &addr);
         }
+        #[cfg(unix)]
+        {
+            if let Some(path) = self.as_pathname() {
+                builder.field("pathname", &path);
+            }
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            if let Some(name) = self.as_abstract_name() {
+                builder.field("abstract_name", &name);
+            }
+        }
+        #[cfg(target_os = "linux")]
+        if let (Some(protocol), Some(ifindex)) = (self.packet_protocol(), self.packet_ifindex()) {
+            builder.field("protocol", &protocol);
+            builder.field("ifindex", &ifindex);
+        }
         builder.finish()
     }
 }
@@ -43,69 +58,6 @@ impl SockAddr {
         SockAddr { storage: addr, len }
     }
 
-    /// Constructs a `SockAddr` with the family `AF_UNIX` and the provided path.
-    ///
-    /// This function is only available on Unix when the `unix` feature is
-    /// enabled.
-    ///
-    /// # Failure
-    ///
-    /// Returns an error if the path is longer than `SUN_LEN`.
-    #[cfg(all(unix, feature = "unix"))]
-    pub fn unix<P>(path: P) -> ::std::io::Result<SockAddr>
-    where
-        P: AsRef<::std::path::Path>,
-    {
-        use libc::{c_char, sockaddr_un, AF_UNIX};
-        use std::cmp::Ordering;
-        use std::io;
-        use std::os::unix::ffi::OsStrExt;
-
-        unsafe {
-            let mut addr = mem::zeroed::<sockaddr_un>();
-            addr.sun_family = AF_UNIX as sa_family_t;
-
-            let bytes = path.as_ref().as_os_str().as_bytes();
-
-            match (bytes.get(0), bytes.len().cmp(&addr.sun_path.len())) {
-                // Abstract paths don't need a null terminator
-                (Some(&0), Ordering::Greater) => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        "path must be no longer than SUN_LEN",
-                    ));
-                }
-                (Some(&0), _) => {}
-                (_, Ordering::Greater) | (_, Ordering::Equal) => {
-                    return Err(io::Error::new(
-                        io::ErrorKind::InvalidInput,
-                        "path must be shorter than SUN_LEN",
-                    ));
-                }
-                _ => {}
-            }
-
-            for (dst, src) in addr.sun_path.iter_mut().zip(bytes) {
-                *dst = *src as c_char;
-            }
-            // null byte for pathname is already there since we zeroed up front
-
-            let base = &addr as *const _ as usize;
-            let path = &addr.sun_path as *const _ as usize;
-            let sun_path_offset = path - base;
-
-            let mut len = sun_path_offset + bytes.len();
-            match bytes.get(0) {
-                Some(&0) | None => {}
-                Some(_) => len += 1,
-            }
-            Ok(SockAddr::from_raw_parts(
-                &addr as *const _ as *const _,
-                len as socklen_t,
-            ))
-        }
-    }
-
     unsafe fn as_<T>(&self, family: sa_family_t) -> Option<T> {
         if self.storage.ss_family != family {
             return None;
@@ -126,6 +78,34 @@ impl SockAddr {
         unsafe { self.as_(AF_INET6 as sa_family_t) }
     }
 
+    /// Returns this address as a `SocketAddrV4` if it is in the `AF_INET`
+    /// family, otherwise returns `None`.
+    ///
+    /// This is the same as [`SockAddr::as_inet`].
+    pub fn as_socket_ipv4(&self) -> Option<SocketAddrV4> {
+        self.as_inet()
+    }
+
+    /// Returns this address as a `SocketAddrV6` if it is in the `AF_INET6`
+    /// family, otherwise returns `None`.
+    ///
+    /// This is the same as [`SockAddr::as_inet6`].
+    pub fn as_socket_ipv6(&self) -> Option<SocketAddrV6> {
+        self.as_inet6()
+    }
+
+    /// Returns `true` if this address is in the `AF_INET` (IPv4) family,
+    /// `false` otherwise.
+    pub fn is_ipv4(&self) -> bool {
+        self.storage.ss_family == AF_INET as sa_family_t
+    }
+
+    /// Returns `true` if this address is in the `AF_INET6` (IPv6) family,
+    /// `false` otherwise.
+    pub fn is_ipv6(&self) -> bool {
+        self.storage.ss_family == AF_INET6 as sa_family_t
+    }
+
     /// Returns this address as a `SocketAddr` if it is in the `AF_INET`
     /// or `AF_INET6` family, otherwise returns `None`.
     pub fn as_std(&self) -> Option<SocketAddr> {
@@ -138,7 +118,17 @@ impl SockAddr {
         }
     }
 
-    /// Returns this address's family.
+    /// Returns this address's family, e.g. `AF_INET`, `AF_INET6` or `AF_UNIX`.
+    ///
+    /// This is useful to determine what kind of address was returned by
+    /// [`Socket::accept`], [`Socket::local_addr`] or [`Socket::recv_from`].
+    /// To compare it against the [`Domain`] constants convert it first, e.g.
+    /// `Domain::from(addr.family() as c_int)`.
+    ///
+    /// [`Socket::accept`]: crate::Socket::accept
+    /// [`Socket::local_addr`]: crate::Socket::local_addr
+    /// [`Socket::recv_from`]: crate::Socket::recv_from
+    /// [`Domain`]: crate::Domain
     pub fn family(&self) -> sa_family_t {
         self.storage.ss_family
     }
@@ -203,7 +193,11 @@ mod test {
     fn inet() {
         let raw = "127.0.0.1:80".parse::<SocketAddrV4>().unwrap();
         let addr = SockAddr::from(raw);
+        assert_eq!(addr.family(), AF_INET as sa_family_t);
+        assert!(addr.is_ipv4());
+        assert!(!addr.is_ipv6());
         assert!(addr.as_inet6().is_none());
+        assert_eq!(addr.as_socket_ipv4(), Some(raw));
         let addr = addr.as_inet().unwrap();
         assert_eq!(raw, addr);
     }
@@ -214,8 +208,19 @@ mod test {
             .parse::<SocketAddrV6>()
             .unwrap();
         let addr = SockAddr::from(raw);
+        assert_eq!(addr.family(), AF_INET6 as sa_family_t);
+        assert!(addr.is_ipv6());
+        assert!(!addr.is_ipv4());
         assert!(addr.as_inet().is_none());
+        assert_eq!(addr.as_socket_ipv6(), Some(raw));
         let addr = addr.as_inet6().unwrap();
         assert_eq!(raw, addr);
     }
+
+    #[test]
+    fn inet6_flowinfo() {
+        let raw = SocketAddrV6::new(std::net::Ipv6Addr::LOCALHOST, 80, 0x12345, 0);
+        let addr = SockAddr::from(raw).as_std().unwrap();
+        assert_eq!(SocketAddr::V6(raw), addr);
+    }
 }
@@ -8,13 +8,23 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use std::convert::TryFrom;
+#[cfg(not(target_os = "hermit"))]
+use std::io::IoSliceMut;
+#[cfg(not(target_os = "hermit"))]
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::net::{Shutdown, TcpListener, TcpStream, UdpSocket};
+#[cfg(target_os = "hermit")]
+use std::os::hermit::io::{FromRawFd, IntoRawFd};
 #[cfg(unix)]
 use std::os::unix::io::{FromRawFd, IntoRawFd};
+use std::time::Duration;
 use std::{fmt, io};
 
 use crate::sys::{self, c_int};
-use crate::{Domain, Protocol, SockAddr, Type};
+#[cfg(not(target_os = "hermit"))]
+use crate::RecvFlags;
+use crate::{Domain, Interest, Protocol, Readiness, SockAddr, SocketBuilder, TcpKeepalive, Type};
 
 /// An owned system socket.
 ///
@@ -26,14 +36,18 @@ use crate::{Domain, Protocol, SockAddr, Type};
 ///
 /// # Notes
 ///
-/// This type can be converted to and from all network types provided by the
-/// standard library using the [`From`] and [`Into`] traits. Is up to the user
-/// to ensure the socket is setup correctly for a given type!
+/// This type can be converted from all network types provided by the standard
+/// library using the [`From`] trait. Converting it into one of those types is
+/// done using the [`TryFrom`] trait, which checks the domain and type of the
+/// socket. It is still up to the user to ensure the socket is setup correctly
+/// for a given type, e.g. that a socket converted into a `TcpListener` is
+/// listening.
 ///
 /// # Examples
 ///
 /// ```
 /// # fn main() -> std::io::Result<()> {
+/// use std::convert::TryFrom;
 /// use std::net::{SocketAddr, TcpListener};
 /// use socket2::{Socket, Domain, Type};
 ///
@@ -49,7 +63,7 @@ use crate::{Domain, Protocol, SockAddr, Type};
 ///
 /// // Finally convert it to `TcpListener` from the standard library. Now it can
 /// // be used like any other `TcpListener`.
-/// let listener: TcpListener = socket.into();
+/// let listener = TcpListener::try_from(socket)?;
 /// # drop(listener);
 /// # Ok(())
 /// # }
@@ -61,10 +75,36 @@ pub struct Socket {
 }
 
 impl Socket {
-    /// Creates a new socket ready to be configured.
+    /// Creates a new socket and sets common flags.
+    ///
+    /// This function corresponds to `socket(2)` on Unix and `WSASocketW` on
+    /// Windows.
     ///
-    /// This function corresponds to `socket(2)`.
+    /// On Unix-like systems, the close-on-exec flag is set on the new socket.
+    /// Additionally, on Apple platforms `SO_NOSIGPIPE` is set. On Windows, the
+    /// socket is made non-inheritable.
+    ///
+    /// [`Socket::new_raw`] can be used if you don't want these flags to be
+    /// set.
     pub fn new(domain: Domain, type_: Type, protocol: Option<Protocol>) -> io::Result<Socket> {
+        let type_ = set_common_type(type_);
+        Socket::new_raw(domain, type_, protocol).and_then(set_common_flags)
+    }
+
+    /// Returns a builder to create a new socket with the given `domain` and
+    /// `type_`, applying the socket options in the correct order.
+    ///
+    /// See [`SocketBuilder`] for an example.
+    pub const fn builder(domain: Domain, type_: Type) -> SocketBuilder {
+        SocketBuilder::new(domain, type_)
+    }
+
+    /// Creates a new socket ready to be configured.
+    ///
+    /// This function corresponds to `socket(2)` on Unix and `WSASocketW` on
+    /// Windows and simply creates a new socket, no other configuration is
+    /// done.
+    pub fn new_raw(domain: Domain, type_: Type, protocol: Option<Protocol>) -> io::Result<Socket> {
         sys::socket(domain.0, type_.0, protocol.map(|p| p.0).unwrap_or(0))
     }
 
@@ -75,6 +115,71 @@ impl Socket {
         sys::connect(self.inner, addr.as_ptr(), addr.len())
     }
 
+    /// Initiate a connection on this socket to the specified address, only
+    /// waiting for a certain period of time for the connection to be
+    /// established.
+    ///
+    /// Unlike many other methods on `Socket`, this does *not* correspond to a
+    /// single C function. It sets the socket to non-blocking mode, calls
+    /// `connect(2)`, waits for the connection to be established using
+    /// `poll(2)` (`WSAPoll` on Windows) and sets the socket back to blocking
+    /// mode. Any error that occurred while connecting is returned, see
+    /// [`take_error`].
+    ///
+    /// If the connection isn't established within `timeout` an error of the
+    /// kind [`io::ErrorKind::TimedOut`] is returned. A `timeout` of zero is
+    /// an [`io::ErrorKind::InvalidInput`] error.
+    ///
+    /// # Notes
+    ///
+    /// The socket is always left in blocking mode, even if it was in
+    /// non-blocking mode before calling this function.
+    ///
+    /// [`take_error`]: Socket::take_error
+    pub fn connect_timeout(&self, addr: &SockAddr, timeout: Duration) -> io::Result<()> {
+        self.set_nonblocking(true)?;
+        let res = self.connect(addr);
+        self.set_nonblocking(false)?;
+
+        match res {
+            Ok(()) => return Ok(()),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {}
+            #[cfg(unix)]
+            Err(ref err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
+            Err(err) => return Err(err),
+        }
+
+        sys::poll_connect(self, timeout)
+    }
+
+    /// Dissolves the association of a connected socket, e.g. returning a
+    /// connected UDP socket to the unconnected state.
+    ///
+    /// On Unix this connects the socket to an address with the family
+    /// `AF_UNSPEC`, on Windows it connects to the unspecified address with
+    /// port zero. Note that for TCP sockets on Linux this aborts the
+    /// connection.
+    pub fn disconnect(&self) -> io::Result<()> {
+        sys::disconnect(self.inner)
+    }
+
+    /// Waits for the socket to become ready for the events in `interest`,
+    /// e.g. for a nonblocking connect to complete.
+    ///
+    /// If `timeout` is `None` this blocks until the socket is ready. If the
+    /// socket doesn't become ready within `timeout` an error of the kind
+    /// [`io::ErrorKind::TimedOut`] is returned, a `timeout` of zero checks
+    /// the readiness without blocking.
+    ///
+    /// Note that [`Readiness::is_error`] and [`Readiness::is_hup`] can be set
+    /// even if not included in `interest`.
+    ///
+    /// This function corresponds to `poll(2)` on Unix and `WSAPoll` on
+    /// Windows.
+    pub fn poll(&self, interest: Interest, timeout: Option<Duration>) -> io::Result<Readiness> {
+        sys::poll(self.inner, interest.0, timeout).map(Readiness)
+    }
+
     /// Binds this socket to the specified address.
     ///
     /// This function directly corresponds to the `bind(2)` function.
@@ -106,18 +211,107 @@ impl Socket {
 
     /// Accept a new incoming connection from this listener.
     ///
-    /// This function directly corresponds to the `accept(2)` function.
+    /// This function uses `accept4(2)` on platforms that support it and
+    /// `accept(2)` on platforms that do not.
+    ///
+    /// This function sets the same flags as is done for [`Socket::new`],
+    /// [`Socket::accept_raw`] can be used if you don't want to set those
+    /// flags.
     pub fn accept(&self) -> io::Result<(Socket, SockAddr)> {
+        self.accept_with(false)
+    }
+
+    /// Accept a new incoming connection from this listener, optionally
+    /// putting the accepted socket in non-blocking mode.
+    ///
+    /// This function uses `accept4(2)` with `SOCK_NONBLOCK` on platforms that
+    /// support it. On other platforms it falls back to `accept(2)` followed by
+    /// [`set_nonblocking`], so the accepted socket is in the requested mode
+    /// on all platforms (some platforms, e.g. macOS, would otherwise inherit
+    /// the mode of the listener).
+    ///
+    /// Like [`accept`] this sets the same flags as is done for
+    /// [`Socket::new`].
+    ///
+    /// [`set_nonblocking`]: Socket::set_nonblocking
+    /// [`accept`]: Socket::accept
+    pub fn accept_with(&self, nonblocking: bool) -> io::Result<(Socket, SockAddr)> {
+        // Use `accept4` on platforms that support it.
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "illumos",
+            target_os = "linux",
+            target_os = "openbsd",
+            target_os = "solaris"
+        ))]
+        {
+            let flags = if nonblocking {
+                libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK
+            } else {
+                libc::SOCK_CLOEXEC
+            };
+            self.accept4(flags)
+        }
+
+        // Fall back to `accept` on platforms that do not support `accept4`.
+        #[cfg(not(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "illumos",
+            target_os = "linux",
+            target_os = "openbsd",
+            target_os = "solaris"
+        )))]
+        {
+            let (socket, addr) = self.accept_raw()?;
+            // NetBSD has `SOCK_CLOEXEC`, so `set_common_flags` doesn't set
+            // the close-on-exec flag for us.
+            #[cfg(target_os = "netbsd")]
+            socket.set_cloexec(true)?;
+            let socket = set_common_flags(socket)?;
+            socket.set_nonblocking(nonblocking)?;
+            Ok((socket, addr))
+        }
+    }
+
+    /// Accept a new incoming connection from this listener.
+    ///
+    /// This function directly corresponds to the `accept(2)` function and
+    /// doesn't set any flags on the returned socket, unlike [`accept`].
+    ///
+    /// [`accept`]: Socket::accept
+    pub fn accept_raw(&self) -> io::Result<(Socket, SockAddr)> {
         sys::accept(self.inner)
     }
 
+    /// Creates a new independently owned handle to the underlying socket.
+    ///
+    /// The returned `Socket` is a reference to the same socket that this
+    /// object references. Both handles will read and write the same stream of
+    /// data, and options set on one socket will be propagated to the other.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this uses `F_DUPFD_CLOEXEC` and thus sets the `FD_CLOEXEC` flag
+    /// on the returned socket.
+    ///
+    /// On Windows this uses `WSADuplicateSocketW` and `WSASocketW` with
+    /// `WSA_FLAG_NO_HANDLE_INHERIT`, so the returned socket isn't inherited by
+    /// child processes.
+    pub fn try_clone(&self) -> io::Result<Socket> {
+        sys::try_clone(self.inner)
+    }
+
     /// Get the value of the `SO_ERROR` option on this socket.
     ///
     /// This will retrieve the stored error in the underlying socket, clearing
     /// the field in the process. This can be useful for checking errors between
     /// calls.
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
-        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_ERROR)
+        self.getsockopt::<c_int>(sys::SOL_SOCKET, sys::SO_ERROR)
             .map(|errno| {
                 if errno == 0 {
                     None
@@ -134,6 +328,253 @@ impl Socket {
     pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
         sys::shutdown(self.inner, how)
     }
+
+    /// Moves this socket into or out of non-blocking mode.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this corresponds to calling `fcntl` returning the current
+    /// flags, with `O_NONBLOCK` set or unset, to `fcntl` (using `F_SETFL`).
+    ///
+    /// On Windows this corresponds to calling `ioctlsocket` with `FIONBIO`.
+    pub fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        sys::set_nonblocking(self.inner, nonblocking)
+    }
+
+    /// Returns the number of bytes that can be read from the socket without
+    /// blocking.
+    ///
+    /// For datagram sockets this is usually the size of the next datagram,
+    /// but some platforms (e.g. Windows) return the total size of all queued
+    /// datagrams.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this corresponds to calling `ioctl` with `FIONREAD`.
+    ///
+    /// On Windows this corresponds to calling `ioctlsocket` with `FIONREAD`.
+    ///
+    /// This function is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
+    pub fn bytes_available(&self) -> io::Result<usize> {
+        sys::bytes_available(self.inner)
+    }
+
+    /// Sends data on the socket to a connected peer.
+    ///
+    /// This is typically used on TCP sockets or datagram sockets which have
+    /// been connected.
+    ///
+    /// On success returns the number of bytes that were sent.
+    ///
+    /// On platforms that support it this passes `MSG_NOSIGNAL`, so sending on
+    /// a socket whose peer has closed the connection returns an `EPIPE` error
+    /// rather than raising `SIGPIPE`. On other platforms see
+    /// `Socket::set_nosigpipe`.
+    pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.send_with_flags(buf, sys::MSG_NOSIGNAL)
+    }
+
+    /// Identical to [`send`] but allows for specification of arbitrary flags
+    /// to the underlying `send` call.
+    ///
+    /// [`send`]: Socket::send
+    pub fn send_with_flags(&self, buf: &[u8], flags: c_int) -> io::Result<usize> {
+        sys::send(self.inner, buf, flags)
+    }
+
+    /// Sends data on the socket to the given address. On success, returns the
+    /// number of bytes written.
+    ///
+    /// This is typically used on UDP or datagram-oriented sockets.
+    ///
+    /// Like [`send`] this passes `MSG_NOSIGNAL` on platforms that support it.
+    ///
+    /// [`send`]: Socket::send
+    pub fn send_to(&self, buf: &[u8], addr: &SockAddr) -> io::Result<usize> {
+        self.send_to_with_flags(buf, addr, sys::MSG_NOSIGNAL)
+    }
+
+    /// Identical to [`send_to`] but allows for specification of arbitrary
+    /// flags to the underlying `sendto` call.
+    ///
+    /// [`send_to`]: Socket::send_to
+    pub fn send_to_with_flags(
+        &self,
+        buf: &[u8],
+        addr: &SockAddr,
+        flags: c_int,
+    ) -> io::Result<usize> {
+        sys::send_to(self.inner, buf, addr, flags)
+    }
+
+    /// Receives data on the socket from the remote address to which it is
+    /// connected.
+    ///
+    /// The [`connect`] method will connect this socket to a remote address.
+    /// This method might fail if the socket is not connected.
+    ///
+    /// [`connect`]: Socket::connect
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.recv_with_flags(buf, 0)
+    }
+
+    /// Identical to [`recv`] but allows for specification of arbitrary flags
+    /// to the underlying `recv` call.
+    ///
+    /// [`recv`]: Socket::recv
+    pub fn recv_with_flags(&self, buf: &mut [u8], flags: c_int) -> io::Result<usize> {
+        sys::recv(self.inner, buf, flags)
+    }
+
+    /// Receives exactly enough data on the socket to fill `buf`.
+    ///
+    /// This passes the `MSG_WAITALL` flag to the underlying `recv` call, which
+    /// causes the call to block until the entire buffer is filled. The call
+    /// can still return less data, e.g. if a signal is caught or the
+    /// connection is closed, in which case this function continues receiving
+    /// data until `buf` is filled, much like [`Read::read_exact`].
+    ///
+    /// # Errors
+    ///
+    /// If the connection is closed before `buf` is filled an error of the
+    /// kind [`io::ErrorKind::UnexpectedEof`] is returned. The contents of
+    /// `buf` are unspecified in this case.
+    ///
+    /// # Notes
+    ///
+    /// This is only useful for blocking sockets, on a non-blocking socket
+    /// `MSG_WAITALL` is ignored and this function will return an error of the
+    /// kind [`io::ErrorKind::WouldBlock`] once no more data is available.
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`Read::read_exact`]: std::io::Read::read_exact
+    #[cfg(not(target_os = "hermit"))]
+    pub fn recv_exact(&self, mut buf: &mut [u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.recv_with_flags(buf, sys::MSG_WAITALL) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "failed to fill whole buffer",
+                    ))
+                }
+                Ok(n) => buf = &mut buf[n..],
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Receives data on the socket from the remote address to which it is
+    /// connected. Unlike [`recv`] this allows passing multiple buffers.
+    ///
+    /// The [`connect`] method will connect this socket to a remote address.
+    /// This method might fail if the socket is not connected.
+    ///
+    /// In addition to the number of bytes read, this function returns the
+    /// flags for the received message. See [`RecvFlags`] for more information
+    /// about the returned flags, e.g. whether or not the message was
+    /// truncated.
+    ///
+    /// This function directly corresponds to the `recvmsg(2)` function.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`recv`]: Socket::recv
+    /// [`connect`]: Socket::connect
+    #[cfg(not(target_os = "hermit"))]
+    pub fn recv_vectored(&self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<(usize, RecvFlags)> {
+        self.recv_vectored_with_flags(bufs, 0)
+    }
+
+    /// Identical to [`recv_vectored`] but allows for specification of
+    /// arbitrary flags to the underlying `recvmsg` call.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`recv_vectored`]: Socket::recv_vectored
+    #[cfg(not(target_os = "hermit"))]
+    pub fn recv_vectored_with_flags(
+        &self,
+        bufs: &mut [IoSliceMut<'_>],
+        flags: c_int,
+    ) -> io::Result<(usize, RecvFlags)> {
+        sys::recv_vectored(self.inner, bufs, flags)
+    }
+
+    /// Receives data from the socket. On success, returns the number of bytes
+    /// read and the address from whence the data came.
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SockAddr)> {
+        self.recv_from_with_flags(buf, 0)
+    }
+
+    /// Identical to [`recv_from`] but allows for specification of arbitrary
+    /// flags to the underlying `recvfrom` call.
+    ///
+    /// [`recv_from`]: Socket::recv_from
+    pub fn recv_from_with_flags(
+        &self,
+        buf: &mut [u8],
+        flags: c_int,
+    ) -> io::Result<(usize, SockAddr)> {
+        sys::recv_from(self.inner, buf, flags)
+    }
+
+    /// Returns the address of the sender of the next datagram queued on the
+    /// socket, without removing the datagram from the queue.
+    ///
+    /// This performs a zero-length `recvfrom` call using the `MSG_PEEK` flag,
+    /// allowing the sender of a datagram to be inspected before allocating a
+    /// buffer to receive it. Like [`recv_from`] this blocks until a datagram
+    /// is available, unless the socket is in non-blocking mode.
+    ///
+    /// [`recv_from`]: Socket::recv_from
+    pub fn peek_sender(&self) -> io::Result<SockAddr> {
+        sys::peek_sender(self.inner)
+    }
+
+    /// Receives data from the socket. Returns the amount of bytes read, the
+    /// [`RecvFlags`] and the address from whence the data came. Unlike
+    /// [`recv_from`] this allows passing multiple buffers.
+    ///
+    /// This function directly corresponds to the `recvmsg(2)` function.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`recv_from`]: Socket::recv_from
+    #[cfg(not(target_os = "hermit"))]
+    pub fn recv_from_vectored(
+        &self,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> io::Result<(usize, RecvFlags, SockAddr)> {
+        self.recv_from_vectored_with_flags(bufs, 0)
+    }
+
+    /// Identical to [`recv_from_vectored`] but allows for specification of
+    /// arbitrary flags to the underlying `recvmsg` call.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`recv_from_vectored`]: Socket::recv_from_vectored
+    #[cfg(not(target_os = "hermit"))]
+    pub fn recv_from_vectored_with_flags(
+        &self,
+        bufs: &mut [IoSliceMut<'_>],
+        flags: c_int,
+    ) -> io::Result<(usize, RecvFlags, SockAddr)> {
+        sys::recv_from_vectored(self.inner, bufs, flags)
+    }
 }
 
 impl Socket {
@@ -168,6 +609,10 @@ impl Socket {
     /// different command have different options the user must defined the
     /// correct type `T`!
     ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
     /// # Examples
     ///
     /// The following example retrieves and sets the file descriptor flags.
@@ -177,7 +622,8 @@ impl Socket {
     /// use socket2::{Domain, Socket, Type};
     ///
     /// # fn main() -> io::Result<()> {
-    /// let socket = Socket::new(Domain::IPV4, Type::STREAM, None)?;
+    /// // Using `new_raw` so the close-on-exec flag isn't set.
+    /// let socket = Socket::new_raw(Domain::IPV4, Type::STREAM, None)?;
     ///
     /// // Retrieve the flags, using nothing `()` as argument.
     /// let flags = socket.fcntl(libc::F_GETFD, ())?;
@@ -193,20 +639,1021 @@ impl Socket {
     /// # Ok(())
     /// # }
     /// ```
+    #[cfg(not(target_os = "hermit"))]
     pub fn fcntl<T>(&self, cmd: c_int, arg: T) -> io::Result<c_int> {
         sys::fcntl(self.inner, cmd, arg)
     }
 }
 
+/// Set `SOCK_CLOEXEC` on the `type_` on platforms that support it.
+pub(crate) fn set_common_type(type_: Type) -> Type {
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "illumos",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "redox",
+        target_os = "solaris"
+    ))]
+    let type_ = type_.cloexec();
+    type_
+}
+
+/// Set the flags on `socket` that can't be set atomically when creating the
+/// socket, see [`Socket::new`].
+pub(crate) fn set_common_flags(socket: Socket) -> io::Result<Socket> {
+    // On platforms that don't have `SOCK_CLOEXEC` use `FD_CLOEXEC`. ESP-IDF
+    // doesn't support `exec` at all (and lwIP's `fcntl` doesn't support
+    // `F_SETFD`).
+    #[cfg(all(
+        unix,
+        not(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "espidf",
+            target_os = "freebsd",
+            target_os = "illumos",
+            target_os = "linux",
+            target_os = "netbsd",
+            target_os = "openbsd",
+            target_os = "redox",
+            target_os = "solaris"
+        ))
+    ))]
+    socket.set_cloexec(true)?;
+
+    // On Apple platforms set `SO_NOSIGPIPE`.
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    socket.set_nosigpipe(true)?;
+
+    #[cfg(windows)]
+    socket.set_no_inherit(true)?;
+
+    Ok(socket)
+}
+
+/// Socket options get/set using `SOL_SOCKET`.
+///
+/// Additional documentation can be found in documentation of the OS.
+/// * Linux: <https://man7.org/linux/man-pages/man7/socket.7.html>
+/// * Windows: <https://docs.microsoft.com/en-us/windows/win32/winsock/sol-socket-socket-options>
+impl Socket {
+    /// Get the value of the `SO_REUSEADDR` option on this socket.
+    ///
+    /// For more information about this option, see [`set_reuse_address`].
+    ///
+    /// [`set_reuse_address`]: Socket::set_reuse_address
+    pub fn reuse_address(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(sys::SOL_SOCKET, sys::SO_REUSEADDR)
+            .map(|reuse| reuse != 0)
+    }
+
+    /// Set value for the `SO_REUSEADDR` option on this socket.
+    ///
+    /// This indicates that further calls to `bind` may allow reuse of local
+    /// addresses. For IPv4 sockets this means that a socket may bind even when
+    /// there's a socket already listening on this port.
+    pub fn set_reuse_address(&self, reuse: bool) -> io::Result<()> {
+        self.setsockopt(sys::SOL_SOCKET, sys::SO_REUSEADDR, &(reuse as c_int))
+    }
+
+    /// Get the value of the `SO_TYPE` option on this socket.
+    ///
+    /// Returns the type of the socket, e.g. [`Type::STREAM`]. This is useful
+    /// to validate the type of a socket created from a raw file descriptor or
+    /// socket handle.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
+    pub fn r#type(&self) -> io::Result<Type> {
+        self.getsockopt::<c_int>(sys::SOL_SOCKET, sys::SO_TYPE)
+            .map(Type::from)
+    }
+
+    /// Get the value of the `SO_KEEPALIVE` option on this socket.
+    ///
+    /// For more information about this option, see [`set_keepalive`].
+    ///
+    /// [`set_keepalive`]: Socket::set_keepalive
+    pub fn keepalive(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(sys::SOL_SOCKET, sys::SO_KEEPALIVE)
+            .map(|keepalive| keepalive != 0)
+    }
+
+    /// Set value for the `SO_KEEPALIVE` option on this socket.
+    ///
+    /// Enable sending of keep-alive messages on connection-oriented sockets.
+    /// To configure the keepalive parameters see [`set_tcp_keepalive`].
+    ///
+    /// [`set_tcp_keepalive`]: Socket::set_tcp_keepalive
+    pub fn set_keepalive(&self, keepalive: bool) -> io::Result<()> {
+        self.setsockopt(sys::SOL_SOCKET, sys::SO_KEEPALIVE, &(keepalive as c_int))
+    }
+
+    /// Get the value of the `SO_LINGER` option on this socket.
+    ///
+    /// For more information about this option, see [`set_linger`].
+    ///
+    /// [`set_linger`]: Socket::set_linger
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        self.getsockopt::<sys::linger>(sys::SOL_SOCKET, sys::SO_LINGER)
+            .map(from_linger)
+    }
+
+    /// Set value for the `SO_LINGER` option on this socket.
+    ///
+    /// If `linger` is not `None`, a close(2) or shutdown(2) will not return
+    /// until all queued messages for the socket have been successfully sent or
+    /// the linger timeout has been reached. Otherwise, the call returns
+    /// immediately and the closing is done in the background. When the socket
+    /// is closed as part of exit(2), it always lingers in the background.
+    ///
+    /// Setting a linger timeout of zero causes the connection to be aborted
+    /// (reset) when the socket is closed, rather than being closed
+    /// gracefully.
+    ///
+    /// # Notes
+    ///
+    /// The linger timeout has a precision of seconds, any sub-second part of
+    /// `linger` is ignored.
+    ///
+    /// On macOS this uses the `SO_LINGER_SEC` option, as `SO_LINGER` uses
+    /// clock ticks rather than seconds.
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        let linger = into_linger(linger);
+        self.setsockopt(sys::SOL_SOCKET, sys::SO_LINGER, &linger)
+    }
+
+    /// Get the value of the `SO_RCVBUF` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_buffer_size`].
+    ///
+    /// [`set_recv_buffer_size`]: Socket::set_recv_buffer_size
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.getsockopt::<c_int>(sys::SOL_SOCKET, sys::SO_RCVBUF)
+            .map(|size| size as usize)
+    }
+
+    /// Set value for the `SO_RCVBUF` option on this socket.
+    ///
+    /// Changes the size of the operating system's receive buffer associated
+    /// with the socket.
+    ///
+    /// # Notes
+    ///
+    /// On Linux the kernel doubles the value set (to allow space for
+    /// bookkeeping overhead), this doubled value is returned by
+    /// [`recv_buffer_size`]. The kernel also applies a minimum and maximum
+    /// (`net.core.rmem_max`) to the size.
+    ///
+    /// [`recv_buffer_size`]: Socket::recv_buffer_size
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.setsockopt(sys::SOL_SOCKET, sys::SO_RCVBUF, &(size as c_int))
+    }
+
+    /// Get the value of the `SO_SNDBUF` option on this socket.
+    ///
+    /// For more information about this option, see [`set_send_buffer_size`].
+    ///
+    /// [`set_send_buffer_size`]: Socket::set_send_buffer_size
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.getsockopt::<c_int>(sys::SOL_SOCKET, sys::SO_SNDBUF)
+            .map(|size| size as usize)
+    }
+
+    /// Set value for the `SO_SNDBUF` option on this socket.
+    ///
+    /// Changes the size of the operating system's send buffer associated with
+    /// the socket.
+    ///
+    /// # Notes
+    ///
+    /// On Linux the kernel doubles the value set (to allow space for
+    /// bookkeeping overhead), this doubled value is returned by
+    /// [`send_buffer_size`]. The kernel also applies a minimum and maximum
+    /// (`net.core.wmem_max`) to the size.
+    ///
+    /// [`send_buffer_size`]: Socket::send_buffer_size
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.setsockopt(sys::SOL_SOCKET, sys::SO_SNDBUF, &(size as c_int))
+    }
+
+    /// Get value for the `SO_RCVTIMEO` option on this socket.
+    ///
+    /// If the returned timeout is `None`, then `read` and `recv` calls will
+    /// block indefinitely.
+    pub fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        sys::timeout_opt(self.inner, sys::SOL_SOCKET, sys::SO_RCVTIMEO)
+    }
+
+    /// Set value for the `SO_RCVTIMEO` option on this socket.
+    ///
+    /// If `timeout` is `None`, then `read` and `recv` calls will block
+    /// indefinitely.
+    ///
+    /// # Errors
+    ///
+    /// An error of the kind [`io::ErrorKind::InvalidInput`] is returned if a
+    /// zero duration is passed, like the standard library does.
+    ///
+    /// # Notes
+    ///
+    /// On Unix the timeout has a precision of microseconds, on Windows of
+    /// milliseconds. Non-zero timeouts smaller than that are rounded up.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        sys::set_timeout_opt(self.inner, sys::SOL_SOCKET, sys::SO_RCVTIMEO, timeout)
+    }
+
+    /// Get value for the `SO_SNDTIMEO` option on this socket.
+    ///
+    /// If the returned timeout is `None`, then `write` and `send` calls will
+    /// block indefinitely.
+    pub fn write_timeout(&self) -> io::Result<Option<Duration>> {
+        sys::timeout_opt(self.inner, sys::SOL_SOCKET, sys::SO_SNDTIMEO)
+    }
+
+    /// Set value for the `SO_SNDTIMEO` option on this socket.
+    ///
+    /// If `timeout` is `None`, then `write` and `send` calls will block
+    /// indefinitely.
+    ///
+    /// See [`set_read_timeout`] for the errors returned and the precision of
+    /// the timeout.
+    ///
+    /// [`set_read_timeout`]: Socket::set_read_timeout
+    pub fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        sys::set_timeout_opt(self.inner, sys::SOL_SOCKET, sys::SO_SNDTIMEO, timeout)
+    }
+}
+
+/// Socket options for IPv4 sockets, get/set using `IPPROTO_IP`.
+///
+/// Additional documentation can be found in documentation of the OS.
+/// * Linux: <https://man7.org/linux/man-pages/man7/ip.7.html>
+/// * Windows: <https://docs.microsoft.com/en-us/windows/win32/winsock/ipproto-ip-socket-options>
+impl Socket {
+    /// Get the value of the `IP_TTL` option for this socket.
+    ///
+    /// For more information about this option, see [`set_ttl`].
+    ///
+    /// [`set_ttl`]: Socket::set_ttl
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(sys::IPPROTO_IP, sys::IP_TTL)
+            .map(|ttl| ttl as u32)
+    }
+
+    /// Set the value of the `IP_TTL` option for this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet
+    /// sent from this socket. For IPv6 sockets see [`set_unicast_hops_v6`].
+    ///
+    /// [`set_unicast_hops_v6`]: Socket::set_unicast_hops_v6
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_TTL, &(ttl as c_int))
+    }
+
+    /// Get the value of the `IP_TOS` option for this socket.
+    ///
+    /// For more information about this option, see [`set_tos`].
+    ///
+    /// [`set_tos`]: Socket::set_tos
+    pub fn tos(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(sys::IPPROTO_IP, sys::IP_TOS)
+            .map(|tos| tos as u32)
+    }
+
+    /// Set the value of the `IP_TOS` option for this socket.
+    ///
+    /// This value sets the type-of-service field that is used in every packet
+    /// sent from this socket, i.e. the DSCP and ECN bits.
+    ///
+    /// # Notes
+    ///
+    /// On Windows `IP_TOS` is documented as not supported, setting it succeeds
+    /// but the value is not used by the network stack. Use the qWAVE APIs
+    /// instead to mark traffic on Windows.
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_TOS, &(tos as c_int))
+    }
+
+    /// Get the value of the don't fragment flag for IPv4 packets sent from
+    /// this socket.
+    ///
+    /// For more information about this option, see [`set_dontfrag_v4`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux, macOS
+    /// and Windows.
+    ///
+    /// [`set_dontfrag_v4`]: Socket::set_dontfrag_v4
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        windows
+    ))]
+    pub fn dontfrag_v4(&self) -> io::Result<bool> {
+        sys::dontfrag_v4(self.inner)
+    }
+
+    /// Set the don't fragment (DF) flag for IPv4 packets sent from this
+    /// socket.
+    ///
+    /// If set, packets larger than the path MTU are not fragmented, instead
+    /// sending them fails with `EMSGSIZE`. This is required for e.g. path MTU
+    /// probing in datagram based protocols.
+    ///
+    /// # Notes
+    ///
+    /// This uses the `IP_DONTFRAG` option, or `IP_DONTFRAGMENT` on Windows. On
+    /// Android and Linux, which don't have this option, this sets the
+    /// `IP_MTU_DISCOVER` option to `IP_PMTUDISC_DO` (or `IP_PMTUDISC_DONT` if
+    /// `dontfrag` is `false`) instead.
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux, macOS
+    /// and Windows.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        windows
+    ))]
+    pub fn set_dontfrag_v4(&self, dontfrag: bool) -> io::Result<()> {
+        sys::set_dontfrag_v4(self.inner, dontfrag)
+    }
+
+    /// Get the value of the `IP_UNICAST_IF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_unicast_if_v4`].
+    ///
+    /// [`set_unicast_if_v4`]: Socket::set_unicast_if_v4
+    #[cfg(any(target_os = "android", target_os = "linux", windows))]
+    pub fn unicast_if_v4(&self) -> io::Result<u32> {
+        // The index is in network byte order.
+        self.getsockopt::<u32>(sys::IPPROTO_IP, sys::IP_UNICAST_IF)
+            .map(u32::from_be)
+    }
+
+    /// Set the value of the `IP_UNICAST_IF` option for this socket.
+    ///
+    /// This sets the interface, by index, used to send unicast packets from
+    /// this socket, regardless of the routing table. This can be used to e.g.
+    /// send traffic outside of a VPN tunnel. An `interface` of zero resets
+    /// this.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, Linux and Windows.
+    #[cfg(any(target_os = "android", target_os = "linux", windows))]
+    pub fn set_unicast_if_v4(&self, interface: u32) -> io::Result<()> {
+        // The index must be in network byte order.
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_UNICAST_IF, &interface.to_be())
+    }
+
+    /// Join a multicast group using `IP_ADD_MEMBERSHIP` option on this socket.
+    ///
+    /// This function specifies a new multicast group for this socket to join.
+    /// The address must be a valid multicast address, and `interface` is the
+    /// address of the local interface with which the system should join the
+    /// multicast group. If it's [`Ipv4Addr::UNSPECIFIED`] (`INADDR_ANY`) then
+    /// an appropriate interface is chosen by the system.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
+    pub fn join_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        let mreq = sys::IpMreq {
+            imr_multiaddr: sys::to_in_addr(multiaddr),
+            imr_interface: sys::to_in_addr(interface),
+        };
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_ADD_MEMBERSHIP, &mreq)
+    }
+
+    /// Leave a multicast group using `IP_DROP_MEMBERSHIP` option on this
+    /// socket.
+    ///
+    /// For more information about this option, see [`join_multicast_v4`].
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`join_multicast_v4`]: Socket::join_multicast_v4
+    #[cfg(not(target_os = "hermit"))]
+    pub fn leave_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        let mreq = sys::IpMreq {
+            imr_multiaddr: sys::to_in_addr(multiaddr),
+            imr_interface: sys::to_in_addr(interface),
+        };
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_DROP_MEMBERSHIP, &mreq)
+    }
+
+    /// Join a source-specific multicast (SSM) channel using the
+    /// `IP_ADD_SOURCE_MEMBERSHIP` option on this socket.
+    ///
+    /// This function specifies a new multicast channel, the combination of
+    /// `source` and `group`, for this socket to join. Only datagrams sent to
+    /// `group` by `source` are received. `interface` is the address of the
+    /// local interface with which the system should join the channel. If it's
+    /// [`Ipv4Addr::UNSPECIFIED`] (`INADDR_ANY`) then an appropriate interface
+    /// is chosen by the system.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux, macOS
+    /// and Windows.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        windows
+    ))]
+    pub fn join_ssm_v4(
+        &self,
+        source: &Ipv4Addr,
+        group: &Ipv4Addr,
+        interface: &Ipv4Addr,
+    ) -> io::Result<()> {
+        let mreqs = sys::IpMreqSource {
+            imr_multiaddr: sys::to_in_addr(group),
+            imr_interface: sys::to_in_addr(interface),
+            imr_sourceaddr: sys::to_in_addr(source),
+        };
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_ADD_SOURCE_MEMBERSHIP, &mreqs)
+    }
+
+    /// Leave a source-specific multicast (SSM) channel using the
+    /// `IP_DROP_SOURCE_MEMBERSHIP` option on this socket.
+    ///
+    /// For more information about this option, see [`join_ssm_v4`].
+    ///
+    /// [`join_ssm_v4`]: Socket::join_ssm_v4
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        windows
+    ))]
+    pub fn leave_ssm_v4(
+        &self,
+        source: &Ipv4Addr,
+        group: &Ipv4Addr,
+        interface: &Ipv4Addr,
+    ) -> io::Result<()> {
+        let mreqs = sys::IpMreqSource {
+            imr_multiaddr: sys::to_in_addr(group),
+            imr_interface: sys::to_in_addr(interface),
+            imr_sourceaddr: sys::to_in_addr(source),
+        };
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_DROP_SOURCE_MEMBERSHIP, &mreqs)
+    }
+
+    /// Get the value of the `IP_MULTICAST_IF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_if_v4`].
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`set_multicast_if_v4`]: Socket::set_multicast_if_v4
+    #[cfg(not(target_os = "hermit"))]
+    pub fn multicast_if_v4(&self) -> io::Result<Ipv4Addr> {
+        self.getsockopt::<sys::in_addr>(sys::IPPROTO_IP, sys::IP_MULTICAST_IF)
+            .map(sys::from_in_addr)
+    }
+
+    /// Set the value of the `IP_MULTICAST_IF` option for this socket.
+    ///
+    /// Specifies the interface to use for routing multicast packets, using
+    /// the address of the local interface. If [`Ipv4Addr::UNSPECIFIED`]
+    /// (`INADDR_ANY`) is used the system chooses the interface.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
+    pub fn set_multicast_if_v4(&self, interface: &Ipv4Addr) -> io::Result<()> {
+        let interface = sys::to_in_addr(interface);
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_MULTICAST_IF, &interface)
+    }
+
+    /// Get the value of the `IP_MULTICAST_LOOP` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_loop_v4`].
+    ///
+    /// [`set_multicast_loop_v4`]: Socket::set_multicast_loop_v4
+    pub fn multicast_loop_v4(&self) -> io::Result<bool> {
+        self.getsockopt::<sys::IpV4MultiCastType>(sys::IPPROTO_IP, sys::IP_MULTICAST_LOOP)
+            .map(|loop_v4| loop_v4 != 0)
+    }
+
+    /// Set the value of the `IP_MULTICAST_LOOP` option for this socket.
+    ///
+    /// If enabled, multicast packets will be looped back to the local socket.
+    /// Note that this may not have any affect on IPv6 sockets.
+    pub fn set_multicast_loop_v4(&self, loop_v4: bool) -> io::Result<()> {
+        let loop_v4 = loop_v4 as sys::IpV4MultiCastType;
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_MULTICAST_LOOP, &loop_v4)
+    }
+
+    /// Get the value of the `IP_MULTICAST_TTL` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_ttl_v4`].
+    ///
+    /// [`set_multicast_ttl_v4`]: Socket::set_multicast_ttl_v4
+    pub fn multicast_ttl_v4(&self) -> io::Result<u32> {
+        self.getsockopt::<sys::IpV4MultiCastType>(sys::IPPROTO_IP, sys::IP_MULTICAST_TTL)
+            .map(|ttl| ttl as u32)
+    }
+
+    /// Set the value of the `IP_MULTICAST_TTL` option for this socket.
+    ///
+    /// Indicates the time-to-live value of outgoing multicast packets for
+    /// this socket. The default value is 1 which means that multicast packets
+    /// don't leave the local network unless explicitly requested.
+    ///
+    /// Note that this may not have any affect on IPv6 sockets.
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> io::Result<()> {
+        let ttl = ttl as sys::IpV4MultiCastType;
+        self.setsockopt(sys::IPPROTO_IP, sys::IP_MULTICAST_TTL, &ttl)
+    }
+}
+
+/// Socket options for IPv6 sockets, get/set using `IPPROTO_IPV6`.
+///
+/// Additional documentation can be found in documentation of the OS.
+/// * Linux: <https://man7.org/linux/man-pages/man7/ipv6.7.html>
+/// * Windows: <https://docs.microsoft.com/en-us/windows/win32/winsock/ipproto-ipv6-socket-options>
+impl Socket {
+    /// Get the value of the `IPV6_V6ONLY` option for this socket.
+    ///
+    /// For more information about this option, see [`set_only_v6`].
+    ///
+    /// [`set_only_v6`]: Socket::set_only_v6
+    pub fn only_v6(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(sys::IPPROTO_IPV6, sys::IPV6_V6ONLY)
+            .map(|only_v6| only_v6 != 0)
+    }
+
+    /// Set the value of the `IPV6_V6ONLY` option for this socket.
+    ///
+    /// If this is set to `true` then the socket is restricted to sending and
+    /// receiving IPv6 packets only. In this case two IPv4 and IPv6
+    /// applications can bind the same port at the same time.
+    ///
+    /// If this is set to `false` then the socket can be used to send and
+    /// receive packets from an IPv4-mapped IPv6 address.
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.setsockopt(sys::IPPROTO_IPV6, sys::IPV6_V6ONLY, &(only_v6 as c_int))
+    }
+
+    /// Get the value of the `IPV6_UNICAST_HOPS` option for this socket.
+    ///
+    /// For more information about this option, see [`set_unicast_hops_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`set_unicast_hops_v6`]: Socket::set_unicast_hops_v6
+    #[cfg(not(target_os = "hermit"))]
+    pub fn unicast_hops_v6(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(sys::IPPROTO_IPV6, sys::IPV6_UNICAST_HOPS)
+            .map(|hops| hops as u32)
+    }
+
+    /// Set the value of the `IPV6_UNICAST_HOPS` option for this socket.
+    ///
+    /// This value sets the hop limit field that is used in every unicast
+    /// packet sent from this socket, the IPv6 equivalent of [`set_ttl`].
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`set_ttl`]: Socket::set_ttl
+    #[cfg(not(target_os = "hermit"))]
+    pub fn set_unicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        self.setsockopt(sys::IPPROTO_IPV6, sys::IPV6_UNICAST_HOPS, &(hops as c_int))
+    }
+
+    /// Get the value of the `IPV6_TCLASS` option for this socket.
+    ///
+    /// For more information about this option, see [`set_tclass_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux, macOS, NetBSD, OpenBSD and Windows.
+    ///
+    /// [`set_tclass_v6`]: Socket::set_tclass_v6
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        windows
+    ))]
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(sys::IPPROTO_IPV6, sys::IPV6_TCLASS)
+            .map(|tclass| tclass as u32)
+    }
+
+    /// Set the value of the `IPV6_TCLASS` option for this socket.
+    ///
+    /// Specifies the traffic class field that is used in every packet sent
+    /// from this socket, the IPv6 counterpart of [`set_tos`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux, macOS, NetBSD, OpenBSD and Windows.
+    ///
+    /// [`set_tos`]: Socket::set_tos
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        windows
+    ))]
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        self.setsockopt(sys::IPPROTO_IPV6, sys::IPV6_TCLASS, &(tclass as c_int))
+    }
+
+    /// Get the value of the `IPV6_DONTFRAG` option for this socket.
+    ///
+    /// For more information about this option, see [`set_dontfrag_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux, macOS, NetBSD, OpenBSD and Windows.
+    ///
+    /// [`set_dontfrag_v6`]: Socket::set_dontfrag_v6
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        windows
+    ))]
+    pub fn dontfrag_v6(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(sys::IPPROTO_IPV6, sys::IPV6_DONTFRAG)
+            .map(|dontfrag| dontfrag != 0)
+    }
+
+    /// Set the value of the `IPV6_DONTFRAG` option for this socket.
+    ///
+    /// If set, packets larger than the path MTU are not fragmented, instead
+    /// sending them fails with `EMSGSIZE`. This is the IPv6 equivalent of
+    /// [`set_dontfrag_v4`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux, macOS, NetBSD, OpenBSD and Windows.
+    ///
+    /// [`set_dontfrag_v4`]: Socket::set_dontfrag_v4
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        windows
+    ))]
+    pub fn set_dontfrag_v6(&self, dontfrag: bool) -> io::Result<()> {
+        self.setsockopt(sys::IPPROTO_IPV6, sys::IPV6_DONTFRAG, &(dontfrag as c_int))
+    }
+
+    /// Get the value of the `IPV6_UNICAST_IF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_unicast_if_v6`].
+    ///
+    /// [`set_unicast_if_v6`]: Socket::set_unicast_if_v6
+    #[cfg(any(target_os = "android", target_os = "linux", windows))]
+    pub fn unicast_if_v6(&self) -> io::Result<u32> {
+        let interface = self.getsockopt::<u32>(sys::IPPROTO_IPV6, sys::IPV6_UNICAST_IF)?;
+        // Unlike Windows, Linux uses network byte order for IPv6 as well.
+        #[cfg(unix)]
+        let interface = u32::from_be(interface);
+        Ok(interface)
+    }
+
+    /// Set the value of the `IPV6_UNICAST_IF` option for this socket.
+    ///
+    /// This is the IPv6 equivalent of [`set_unicast_if_v4`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, Linux and Windows.
+    ///
+    /// [`set_unicast_if_v4`]: Socket::set_unicast_if_v4
+    #[cfg(any(target_os = "android", target_os = "linux", windows))]
+    pub fn set_unicast_if_v6(&self, interface: u32) -> io::Result<()> {
+        // Unlike Windows, Linux uses network byte order for IPv6 as well.
+        #[cfg(unix)]
+        let interface = interface.to_be();
+        self.setsockopt(sys::IPPROTO_IPV6, sys::IPV6_UNICAST_IF, &interface)
+    }
+
+    /// Join a multicast group using `IPV6_ADD_MEMBERSHIP` option on this
+    /// socket.
+    ///
+    /// This function specifies a new multicast group for this socket to join.
+    /// The address must be a valid multicast address, and `interface` is the
+    /// index of the interface to join/leave (or 0 to indicate any interface).
+    ///
+    /// # Notes
+    ///
+    /// On platforms that don't define `IPV6_ADD_MEMBERSHIP`, such as the BSDs
+    /// and macOS, this uses the `IPV6_JOIN_GROUP` option.
+    ///
+    /// This function is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        let mreq = sys::Ipv6Mreq {
+            ipv6mr_multiaddr: sys::to_in6_addr(multiaddr),
+            // NOTE: some OSs use `c_int`, others use `c_uint`.
+            ipv6mr_interface: interface as _,
+        };
+        self.setsockopt(sys::IPPROTO_IPV6, sys::IPV6_ADD_MEMBERSHIP, &mreq)
+    }
+
+    /// Leave a multicast group using `IPV6_DROP_MEMBERSHIP` option on this
+    /// socket.
+    ///
+    /// For more information about this option, see [`join_multicast_v6`].
+    ///
+    /// # Notes
+    ///
+    /// On platforms that don't define `IPV6_DROP_MEMBERSHIP`, such as the
+    /// BSDs and macOS, this uses the `IPV6_LEAVE_GROUP` option.
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`join_multicast_v6`]: Socket::join_multicast_v6
+    #[cfg(not(target_os = "hermit"))]
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        let mreq = sys::Ipv6Mreq {
+            ipv6mr_multiaddr: sys::to_in6_addr(multiaddr),
+            // NOTE: some OSs use `c_int`, others use `c_uint`.
+            ipv6mr_interface: interface as _,
+        };
+        self.setsockopt(sys::IPPROTO_IPV6, sys::IPV6_DROP_MEMBERSHIP, &mreq)
+    }
+
+    /// Get the value of the `IPV6_MULTICAST_IF` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_if_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`set_multicast_if_v6`]: Socket::set_multicast_if_v6
+    #[cfg(not(target_os = "hermit"))]
+    pub fn multicast_if_v6(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(sys::IPPROTO_IPV6, sys::IPV6_MULTICAST_IF)
+            .map(|interface| interface as u32)
+    }
+
+    /// Set the value of the `IPV6_MULTICAST_IF` option for this socket.
+    ///
+    /// Specifies the interface to use for routing multicast packets, using
+    /// the index of the interface. Unlike IPv4, this is generally required in
+    /// IPv6 contexts where network routing prefixes may overlap. An index of
+    /// 0 lets the system choose the interface.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
+    pub fn set_multicast_if_v6(&self, interface: u32) -> io::Result<()> {
+        self.setsockopt(
+            sys::IPPROTO_IPV6,
+            sys::IPV6_MULTICAST_IF,
+            &(interface as c_int),
+        )
+    }
+
+    /// Get the value of the `IPV6_MULTICAST_HOPS` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_hops_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    ///
+    /// [`set_multicast_hops_v6`]: Socket::set_multicast_hops_v6
+    #[cfg(not(target_os = "hermit"))]
+    pub fn multicast_hops_v6(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(sys::IPPROTO_IPV6, sys::IPV6_MULTICAST_HOPS)
+            .map(|hops| hops as u32)
+    }
+
+    /// Set the value of the `IPV6_MULTICAST_HOPS` option for this socket.
+    ///
+    /// Indicates the number of "routers" multicast packets will transit for
+    /// this socket. The default value is 1 which means that multicast packets
+    /// don't leave the local network unless explicitly requested.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on Hermit.
+    #[cfg(not(target_os = "hermit"))]
+    pub fn set_multicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        self.setsockopt(
+            sys::IPPROTO_IPV6,
+            sys::IPV6_MULTICAST_HOPS,
+            &(hops as c_int),
+        )
+    }
+
+    /// Get the value of the `IPV6_MULTICAST_LOOP` option for this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_loop_v6`].
+    ///
+    /// [`set_multicast_loop_v6`]: Socket::set_multicast_loop_v6
+    pub fn multicast_loop_v6(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(sys::IPPROTO_IPV6, sys::IPV6_MULTICAST_LOOP)
+            .map(|loop_v6| loop_v6 != 0)
+    }
+
+    /// Set the value of the `IPV6_MULTICAST_LOOP` option for this socket.
+    ///
+    /// Controls whether this socket sees the multicast packets it sends
+    /// itself. Note that this may not have any affect on IPv4 sockets.
+    pub fn set_multicast_loop_v6(&self, loop_v6: bool) -> io::Result<()> {
+        self.setsockopt(
+            sys::IPPROTO_IPV6,
+            sys::IPV6_MULTICAST_LOOP,
+            &(loop_v6 as c_int),
+        )
+    }
+}
+
+/// Socket options for TCP sockets, get/set using `IPPROTO_TCP`.
+///
+/// Additional documentation can be found in documentation of the OS.
+/// * Linux: <https://man7.org/linux/man-pages/man7/tcp.7.html>
+/// * Windows: <https://docs.microsoft.com/en-us/windows/win32/winsock/ipproto-tcp-socket-options>
+impl Socket {
+    /// Get the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tcp_nodelay`].
+    ///
+    /// [`set_tcp_nodelay`]: Socket::set_tcp_nodelay
+    pub fn tcp_nodelay(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(sys::IPPROTO_TCP, sys::TCP_NODELAY)
+            .map(|nodelay| nodelay != 0)
+    }
+
+    /// Set the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// If set, this option disables the Nagle algorithm. This means that
+    /// segments are always sent as soon as possible, even if there is only a
+    /// small amount of data. When not set, data is buffered until there is a
+    /// sufficient amount to send out, thereby avoiding the frequent sending of
+    /// small packets.
+    pub fn set_tcp_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.setsockopt(sys::IPPROTO_TCP, sys::TCP_NODELAY, &(nodelay as c_int))
+    }
+
+    /// Get the value of the `TCP_FASTOPEN` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tcp_fastopen`].
+    ///
+    /// [`set_tcp_fastopen`]: Socket::set_tcp_fastopen
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        windows
+    ))]
+    pub fn tcp_fastopen(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(sys::IPPROTO_TCP, sys::TCP_FASTOPEN)
+            .map(|fastopen| fastopen as u32)
+    }
+
+    /// Set the value of the `TCP_FASTOPEN` option on this socket.
+    ///
+    /// Enables TCP Fast Open (TFO, RFC 7413), allowing data to be sent in the
+    /// SYN packet of the handshake.
+    ///
+    /// On Android and Linux `queue_len` is the maximum length of the queue of
+    /// pending TFO requests for a listening socket. On FreeBSD, iOS and macOS
+    /// any non-zero value enables TFO on a listening socket. On Windows any
+    /// non-zero value enables TFO for a socket that is about to connect, on
+    /// listening sockets TFO is enabled system wide.
+    ///
+    /// For the client side on Android and Linux see
+    /// `Socket::set_tcp_fastopen_connect` and `Socket::send_fastopen`.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux, macOS
+    /// and Windows.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        windows
+    ))]
+    pub fn set_tcp_fastopen(&self, queue_len: u32) -> io::Result<()> {
+        self.setsockopt(sys::IPPROTO_TCP, sys::TCP_FASTOPEN, &(queue_len as c_int))
+    }
+
+    /// Enables TCP keepalive (`SO_KEEPALIVE`) on this socket and configures
+    /// the keepalive parameters set in `params`.
+    ///
+    /// Parameters not set in `params` keep their current value (the OS
+    /// default unless changed), except on Windows where the time and
+    /// interval are always set together, in which case a missing value
+    /// defaults to two hours and one second respectively.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this sets the `TCP_KEEPIDLE` (`TCP_KEEPALIVE` on macOS and
+    /// iOS), `TCP_KEEPINTVL` and `TCP_KEEPCNT` options. On Windows this uses
+    /// `WSAIoctl` with `SIO_KEEPALIVE_VALS`.
+    pub fn set_tcp_keepalive(&self, params: &TcpKeepalive) -> io::Result<()> {
+        self.set_keepalive(true)?;
+        sys::set_tcp_keepalive(self.inner, params)
+    }
+}
+
+fn from_linger(linger: sys::linger) -> Option<Duration> {
+    if linger.l_onoff == 0 {
+        None
+    } else {
+        Some(Duration::from_secs(linger.l_linger as u64))
+    }
+}
+
+fn into_linger(duration: Option<Duration>) -> sys::linger {
+    match duration {
+        Some(duration) => sys::linger {
+            l_onoff: 1,
+            l_linger: duration.as_secs() as _,
+        },
+        None => sys::linger {
+            l_onoff: 0,
+            l_linger: 0,
+        },
+    }
+}
+
 impl From<TcpStream> for Socket {
     fn from(socket: TcpStream) -> Socket {
         unsafe { Socket::from_raw_fd(socket.into_raw_fd()) }
     }
 }
 
-impl Into<TcpStream> for Socket {
-    fn into(self) -> TcpStream {
-        unsafe { TcpStream::from_raw_fd(self.into_raw_fd()) }
+impl TryFrom<Socket> for TcpStream {
+    type Error = io::Error;
+
+    /// Converts the socket into a `TcpStream`, returns an error if the socket's
+    /// domain or type don't match.
+    fn try_from(socket: Socket) -> io::Result<TcpStream> {
+        check_socket(&socket, &[Domain::IPV4, Domain::IPV6], Type::STREAM)?;
+        Ok(unsafe { TcpStream::from_raw_fd(socket.into_raw_fd()) })
     }
 }
 
@@ -216,9 +1663,14 @@ impl From<TcpListener> for Socket {
     }
 }
 
-impl Into<TcpListener> for Socket {
-    fn into(self) -> TcpListener {
-        unsafe { TcpListener::from_raw_fd(self.into_raw_fd()) }
+impl TryFrom<Socket> for TcpListener {
+    type Error = io::Error;
+
+    /// Converts the socket into a `TcpListener`, returns an error if the socket's
+    /// domain or type don't match.
+    fn try_from(socket: Socket) -> io::Result<TcpListener> {
+        check_socket(&socket, &[Domain::IPV4, Domain::IPV6], Type::STREAM)?;
+        Ok(unsafe { TcpListener::from_raw_fd(socket.into_raw_fd()) })
     }
 }
 
@@ -228,10 +1680,39 @@ impl From<UdpSocket> for Socket {
     }
 }
 
-impl Into<UdpSocket> for Socket {
-    fn into(self) -> UdpSocket {
-        unsafe { UdpSocket::from_raw_fd(self.into_raw_fd()) }
+impl TryFrom<Socket> for UdpSocket {
+    type Error = io::Error;
+
+    /// Converts the socket into a `UdpSocket`, returns an error if the socket's
+    /// domain or type don't match.
+    fn try_from(socket: Socket) -> io::Result<UdpSocket> {
+        check_socket(&socket, &[Domain::IPV4, Domain::IPV6], Type::DGRAM)?;
+        Ok(unsafe { UdpSocket::from_raw_fd(socket.into_raw_fd()) })
+    }
+}
+
+/// Checks that the domain of `socket` is one of `domains` and its type is
+/// `type_`, used before converting it into one of the standard library types.
+#[cfg_attr(target_os = "hermit", allow(unused_variables))]
+pub(crate) fn check_socket(socket: &Socket, domains: &[Domain], type_: Type) -> io::Result<()> {
+    // Skip the domain check if the domain can't be determined, only the type
+    // is checked in that case.
+    let domain = sys::socket_domain(socket.inner);
+    if matches!(domain, Some(domain) if !domains.contains(&domain)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "socket has an invalid domain for the conversion",
+        ));
+    }
+    // Hermit doesn't support `SO_TYPE`, so only the domain can be checked.
+    #[cfg(not(target_os = "hermit"))]
+    if socket.r#type()? != type_ {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "socket has an invalid type for the conversion",
+        ));
     }
+    Ok(())
 }
 
 impl fmt::Debug for Socket {
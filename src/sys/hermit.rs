@@ -0,0 +1,436 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Hermit support.
+//!
+//! Hermit only supports a subset of the socket API, the unsupported methods
+//! of `Socket` are not available on Hermit.
+
+use std::cmp::min;
+use std::io;
+use std::mem::{self, size_of, MaybeUninit};
+use std::net::Shutdown;
+use std::os::hermit::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+use std::time::{Duration, Instant};
+
+use crate::{Domain, SockAddr, Socket, TcpKeepalive};
+
+// Used in conversions for `Domain`, `Type` and `Protocol`.
+#[allow(non_camel_case_types)]
+pub(crate) type c_int = libc::c_int;
+// Used in `Interest` and `Readiness`.
+#[allow(non_camel_case_types)]
+pub(crate) type c_short = libc::c_short;
+
+// Used in `Domain`.
+pub(crate) use libc::{AF_INET, AF_INET6};
+// Used in `Type`.
+pub(crate) use libc::{SOCK_DGRAM, SOCK_STREAM};
+// Used in `Protocol`.
+pub(crate) use libc::{IPPROTO_TCP, IPPROTO_UDP};
+// Used in `Socket`.
+pub(crate) use std::os::hermit::io::RawFd as RawSocket;
+// Used in `Socket`.
+pub(crate) use libc::{
+    IPPROTO_IP, IPPROTO_IPV6, IPV6_MULTICAST_LOOP, IPV6_V6ONLY, IP_MULTICAST_LOOP,
+    IP_MULTICAST_TTL, IP_TOS, IP_TTL, SOL_SOCKET, SO_ERROR, SO_KEEPALIVE, SO_LINGER, SO_RCVBUF,
+    SO_RCVTIMEO, SO_REUSEADDR, SO_SNDBUF, SO_SNDTIMEO, TCP_NODELAY,
+};
+// Used in `Socket::set_multicast_ttl_v4`.
+pub(crate) type IpV4MultiCastType = c_int;
+// Used in `Socket::send`, Hermit doesn't raise `SIGPIPE`.
+pub(crate) const MSG_NOSIGNAL: c_int = 0x0;
+// Used in `Interest` and `Readiness`.
+pub(crate) use libc::{POLLERR, POLLHUP, POLLIN, POLLOUT};
+
+// Maximum size of a buffer passed to system call like `recv` and `send`.
+const MAX_BUF_LEN: usize = libc::ssize_t::MAX as usize;
+
+// Not (yet) exposed by libc, these match the definitions of the Hermit kernel.
+#[allow(non_camel_case_types)]
+#[repr(C)]
+pub(crate) struct linger {
+    pub(crate) l_onoff: c_int,
+    pub(crate) l_linger: c_int,
+}
+
+#[allow(non_camel_case_types)]
+#[repr(C)]
+struct timeval {
+    tv_sec: libc::time_t,
+    tv_usec: i64,
+}
+
+/// Helper macro to execute a system call that returns an `io::Result`.
+///
+/// Unlike Unix, Hermit returns the (negated) error number directly.
+macro_rules! syscall {
+    ($fn: ident ( $($arg: expr),* $(,)* ) ) => {{
+        let res = unsafe { libc::$fn($($arg, )*) };
+        if res < 0 {
+            Err(std::io::Error::from_raw_os_error(-(res as i32)))
+        } else {
+            Ok(res)
+        }
+    }};
+}
+
+pub(crate) fn socket(domain: c_int, type_: c_int, protocol: c_int) -> io::Result<Socket> {
+    syscall!(socket(domain, type_, protocol)).map(|fd| Socket { inner: fd })
+}
+
+pub(crate) fn connect(
+    sockfd: RawSocket,
+    addr: *const libc::sockaddr_storage,
+    addrlen: libc::socklen_t,
+) -> io::Result<()> {
+    syscall!(connect(sockfd, addr as *const _, addrlen)).map(|_| ())
+}
+
+pub(crate) fn bind(
+    sockfd: RawSocket,
+    addr: *const libc::sockaddr_storage,
+    addrlen: libc::socklen_t,
+) -> io::Result<()> {
+    syscall!(bind(sockfd, addr as *const _, addrlen)).map(|_| ())
+}
+
+pub(crate) fn listen(sockfd: RawSocket, backlog: c_int) -> io::Result<()> {
+    syscall!(listen(sockfd, backlog)).map(|_| ())
+}
+
+pub(crate) fn accept(sockfd: RawSocket) -> io::Result<(Socket, SockAddr)> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(accept(sockfd, addr.as_mut_ptr() as *mut _, &mut addrlen)).map(|stream_fd| {
+        // This is safe because `accept` filled in the address for us.
+        let addr = unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) };
+        (Socket { inner: stream_fd }, addr)
+    })
+}
+
+pub(crate) fn try_clone(fd: RawSocket) -> io::Result<Socket> {
+    syscall!(dup(fd)).map(|fd| Socket { inner: fd })
+}
+
+pub(crate) fn getsockname(sockfd: RawSocket) -> io::Result<SockAddr> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(getsockname(
+        sockfd,
+        addr.as_mut_ptr() as *mut _,
+        &mut addrlen
+    ))
+    .map(|_| {
+        // This is safe because `getsockname` filled in the address for us.
+        unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) }
+    })
+}
+
+/// Returns the domain of the socket, or `None` if it can't be determined.
+pub(crate) fn socket_domain(sockfd: RawSocket) -> Option<Domain> {
+    getsockname(sockfd)
+        .ok()
+        .filter(|addr| addr.family() != libc::AF_UNSPEC as libc::sa_family_t)
+        .map(|addr| Domain::from(addr.family() as c_int))
+}
+
+pub(crate) fn getpeername(sockfd: RawSocket) -> io::Result<SockAddr> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(getpeername(
+        sockfd,
+        addr.as_mut_ptr() as *mut _,
+        &mut addrlen
+    ))
+    .map(|_| {
+        // This is safe because `getpeername` filled in the address for us.
+        unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) }
+    })
+}
+
+pub(crate) fn shutdown(sockfd: RawSocket, how: Shutdown) -> io::Result<()> {
+    let how = match how {
+        Shutdown::Write => libc::SHUT_WR,
+        Shutdown::Read => libc::SHUT_RD,
+        Shutdown::Both => libc::SHUT_RDWR,
+    };
+    syscall!(shutdown(sockfd, how)).map(|_| ())
+}
+
+pub(crate) fn disconnect(sockfd: RawSocket) -> io::Result<()> {
+    let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    addr.ss_family = libc::AF_UNSPEC as libc::sa_family_t;
+    connect(
+        sockfd,
+        &addr,
+        size_of::<libc::sockaddr>() as libc::socklen_t,
+    )
+}
+
+pub(crate) fn poll(
+    sockfd: RawSocket,
+    events: c_short,
+    timeout: Option<Duration>,
+) -> io::Result<c_short> {
+    let start = Instant::now();
+    let mut pollfd = libc::pollfd {
+        fd: sockfd,
+        events,
+        revents: 0,
+    };
+
+    loop {
+        let timeout = match timeout {
+            Some(timeout) => match timeout.saturating_sub(start.elapsed()) {
+                remaining if remaining.is_zero() => 0,
+                // Round up to at least 1 millisecond, `poll` would otherwise
+                // return immediately.
+                remaining => remaining.as_millis().clamp(1, c_int::MAX as u128) as c_int,
+            },
+            None => -1,
+        };
+
+        match syscall!(poll(&mut pollfd, 1, timeout)) {
+            Ok(0) => return Err(io::ErrorKind::TimedOut.into()),
+            Ok(_) => return Ok(pollfd.revents),
+            // Got interrupted, try again.
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+pub(crate) fn poll_connect(socket: &Socket, timeout: Duration) -> io::Result<()> {
+    if timeout == Duration::from_secs(0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot set a 0 duration timeout",
+        ));
+    }
+
+    let revents = poll(socket.inner, libc::POLLIN | libc::POLLOUT, Some(timeout))?;
+    // Error or hang up indicates an error (or failure to connect).
+    if (revents & libc::POLLHUP) != 0 || (revents & libc::POLLERR) != 0 {
+        match socket.take_error() {
+            Ok(Some(err)) | Err(err) => return Err(err),
+            Ok(None) => return Err(io::Error::other("no error set after POLLHUP")),
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn set_nonblocking(sockfd: RawSocket, nonblocking: bool) -> io::Result<()> {
+    let mut nonblocking = nonblocking as c_int;
+    syscall!(ioctl(
+        sockfd,
+        libc::FIONBIO,
+        &mut nonblocking as *mut c_int as *mut _
+    ))
+    .map(|_| ())
+}
+
+pub(crate) fn send(sockfd: RawSocket, buf: &[u8], flags: c_int) -> io::Result<usize> {
+    syscall!(send(
+        sockfd,
+        buf.as_ptr() as *const _,
+        min(buf.len(), MAX_BUF_LEN),
+        flags,
+    ))
+    .map(|n| n as usize)
+}
+
+pub(crate) fn send_to(
+    sockfd: RawSocket,
+    buf: &[u8],
+    addr: &SockAddr,
+    flags: c_int,
+) -> io::Result<usize> {
+    syscall!(sendto(
+        sockfd,
+        buf.as_ptr() as *const _,
+        min(buf.len(), MAX_BUF_LEN),
+        flags,
+        addr.as_ptr() as *const _,
+        addr.len(),
+    ))
+    .map(|n| n as usize)
+}
+
+pub(crate) fn recv(sockfd: RawSocket, buf: &mut [u8], flags: c_int) -> io::Result<usize> {
+    syscall!(recv(
+        sockfd,
+        buf.as_mut_ptr() as *mut _,
+        min(buf.len(), MAX_BUF_LEN),
+        flags,
+    ))
+    .map(|n| n as usize)
+}
+
+pub(crate) fn recv_from(
+    sockfd: RawSocket,
+    buf: &mut [u8],
+    flags: c_int,
+) -> io::Result<(usize, SockAddr)> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(recvfrom(
+        sockfd,
+        buf.as_mut_ptr() as *mut _,
+        min(buf.len(), MAX_BUF_LEN),
+        flags,
+        addr.as_mut_ptr() as *mut _,
+        &mut addrlen
+    ))
+    .map(|n| {
+        // This is safe because `recvfrom` filled in the address for us.
+        let addr = unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) };
+        (n as usize, addr)
+    })
+}
+
+pub(crate) fn peek_sender(sockfd: RawSocket) -> io::Result<SockAddr> {
+    recv_from(sockfd, &mut [], libc::MSG_PEEK).map(|(_, addr)| addr)
+}
+
+pub(crate) fn setsockopt<T>(
+    sockfd: RawSocket,
+    level: c_int,
+    optname: c_int,
+    opt: &T,
+) -> io::Result<()> {
+    syscall!(setsockopt(
+        sockfd,
+        level,
+        optname,
+        opt as *const _ as *const _,
+        size_of::<T>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+pub(crate) fn getsockopt<T>(sockfd: RawSocket, level: c_int, optname: c_int) -> io::Result<T> {
+    let mut optval: MaybeUninit<T> = MaybeUninit::uninit();
+    let mut optlen = size_of::<T>() as libc::socklen_t;
+    syscall!(getsockopt(
+        sockfd,
+        level,
+        optname,
+        optval.as_mut_ptr() as *mut _,
+        &mut optlen
+    ))
+    .map(|_| unsafe {
+        // Safe because `getsockopt` initialised the value for us.
+        debug_assert_eq!(optlen as usize, size_of::<T>());
+        optval.assume_init()
+    })
+}
+
+pub(crate) fn timeout_opt(
+    sockfd: RawSocket,
+    level: c_int,
+    optname: c_int,
+) -> io::Result<Option<Duration>> {
+    getsockopt::<timeval>(sockfd, level, optname).map(|timeout| {
+        if timeout.tv_sec == 0 && timeout.tv_usec == 0 {
+            None
+        } else {
+            let secs = timeout.tv_sec as u64;
+            let nanos = (timeout.tv_usec as u32) * 1000;
+            Some(Duration::new(secs, nanos))
+        }
+    })
+}
+
+pub(crate) fn set_timeout_opt(
+    sockfd: RawSocket,
+    level: c_int,
+    optname: c_int,
+    duration: Option<Duration>,
+) -> io::Result<()> {
+    let timeout = match duration {
+        Some(duration) if duration.as_secs() == 0 && duration.subsec_nanos() == 0 => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot set a 0 duration timeout",
+            ));
+        }
+        Some(duration) => {
+            let mut timeout = timeval {
+                tv_sec: min(duration.as_secs(), libc::time_t::MAX as u64) as libc::time_t,
+                tv_usec: duration.subsec_micros() as i64,
+            };
+            // Don't round sub-microsecond timeouts down to no timeout.
+            if timeout.tv_sec == 0 && timeout.tv_usec == 0 {
+                timeout.tv_usec = 1;
+            }
+            timeout
+        }
+        None => timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        },
+    };
+    setsockopt(sockfd, level, optname, &timeout)
+}
+
+pub(crate) fn set_tcp_keepalive(_: RawSocket, _: &TcpKeepalive) -> io::Result<()> {
+    // Hermit doesn't provide a way to set the keepalive parameters per socket.
+    Ok(())
+}
+
+impl FromRawFd for Socket {
+    unsafe fn from_raw_fd(fd: RawFd) -> Socket {
+        Socket { inner: fd }
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.inner;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl AsFd for Socket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // Safety: the file descriptor is valid for the lifetime of `self`.
+        unsafe { BorrowedFd::borrow_raw(self.inner) }
+    }
+}
+
+impl From<OwnedFd> for Socket {
+    fn from(fd: OwnedFd) -> Socket {
+        Socket {
+            inner: fd.into_raw_fd(),
+        }
+    }
+}
+
+impl From<Socket> for OwnedFd {
+    fn from(socket: Socket) -> OwnedFd {
+        // Safety: `Socket` owns the file descriptor.
+        unsafe { OwnedFd::from_raw_fd(socket.into_raw_fd()) }
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        // Can't handle the error here, nor can we do much with it.
+        let _ = unsafe { libc::close(self.inner) };
+    }
+}
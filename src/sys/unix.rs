@@ -8,26 +8,278 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use std::io;
+use std::cmp::min;
+use std::convert::TryFrom;
+use std::ffi::{CStr, OsStr};
+use std::fmt;
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+use std::fs::File;
+use std::io::{self, IoSlice, IoSliceMut};
 use std::mem::{self, size_of, MaybeUninit};
-use std::net::Shutdown;
-use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+use std::net::IpAddr;
+use std::net::{Ipv4Addr, Ipv6Addr, Shutdown};
+#[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+use std::net::{SocketAddr, SocketAddrV4, SocketAddrV6};
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use std::num::NonZeroU32;
+#[cfg(target_os = "linux")]
+use std::ops::{BitOr, BitOrAssign};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
 use std::os::unix::net::{UnixDatagram, UnixListener, UnixStream};
+use std::path::Path;
+use std::time::{Duration, Instant};
 
-use crate::{Domain, Protocol, SockAddr, Socket, Type};
+use crate::socket::{check_socket, set_common_flags, set_common_type};
+use crate::RecvFlags;
+use crate::{Domain, Protocol, SockAddr, Socket, TcpKeepalive, Type};
 
 // Used in conversions for `Domain`, `Type` and `Protocol`.
 #[allow(non_camel_case_types)]
 pub(crate) type c_int = libc::c_int;
+// Used in `Interest` and `Readiness`.
+#[allow(non_camel_case_types)]
+pub(crate) type c_short = libc::c_short;
 
 // Used in `Domain`.
 pub(crate) use libc::{AF_INET, AF_INET6};
 // Used in `Type`.
-pub(crate) use libc::{SOCK_DGRAM, SOCK_RAW, SOCK_SEQPACKET, SOCK_STREAM};
+pub(crate) use libc::{SOCK_DGRAM, SOCK_STREAM};
+#[cfg(not(target_os = "espidf"))]
+pub(crate) use libc::{SOCK_RAW, SOCK_SEQPACKET};
 // Used in `Protocol`.
 pub(crate) use libc::{IPPROTO_ICMP, IPPROTO_ICMPV6, IPPROTO_TCP, IPPROTO_UDP};
 // Used in `Socket`.
 pub(crate) use std::os::unix::io::RawFd as RawSocket;
+// Used in `Socket`.
+pub(crate) use libc::IP_TOS;
+#[cfg(not(any(target_os = "ios", target_os = "macos")))]
+pub(crate) use libc::SO_LINGER;
+pub(crate) use libc::{
+    IPPROTO_IP, IPPROTO_IPV6, IPV6_MULTICAST_HOPS, IPV6_MULTICAST_IF, IPV6_MULTICAST_LOOP,
+    IPV6_UNICAST_HOPS, IPV6_V6ONLY, IP_ADD_MEMBERSHIP, IP_DROP_MEMBERSHIP, IP_MULTICAST_IF,
+    IP_MULTICAST_LOOP, IP_MULTICAST_TTL, IP_TTL, SOL_SOCKET, SO_ERROR, SO_KEEPALIVE, SO_RCVBUF,
+    SO_RCVTIMEO, SO_REUSEADDR, SO_SNDBUF, SO_SNDTIMEO, SO_TYPE, TCP_NODELAY,
+};
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub(crate) use libc::{IPV6_DONTFRAG, IPV6_TCLASS};
+// `SO_LINGER` uses clock ticks rather than seconds on macOS.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+pub(crate) use libc::SO_LINGER_SEC as SO_LINGER;
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub(crate) use libc::TCP_FASTOPEN;
+#[cfg(any(
+    target_os = "android",
+    target_os = "emscripten",
+    target_os = "linux",
+    target_os = "redox"
+))]
+pub(crate) use libc::{IPV6_ADD_MEMBERSHIP, IPV6_DROP_MEMBERSHIP};
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "emscripten",
+    target_os = "linux",
+    target_os = "redox"
+)))]
+pub(crate) use libc::{
+    IPV6_JOIN_GROUP as IPV6_ADD_MEMBERSHIP, IPV6_LEAVE_GROUP as IPV6_DROP_MEMBERSHIP,
+};
+// Used in `Socket::linger`.
+pub(crate) use libc::linger;
+// Used in `Socket::join_multicast_v4`.
+pub(crate) use libc::ip_mreq as IpMreq;
+// Used in `Socket::join_ssm_v4`.
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+pub(crate) use libc::{
+    ip_mreq_source as IpMreqSource, IP_ADD_SOURCE_MEMBERSHIP, IP_DROP_SOURCE_MEMBERSHIP,
+};
+// Used in `Socket::send` and `Socket::send_to`, to prevent `SIGPIPE` when
+// writing to a closed peer. Platforms without it should use `SO_NOSIGPIPE`.
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub(crate) use libc::MSG_NOSIGNAL;
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+pub(crate) const MSG_NOSIGNAL: c_int = 0x0;
+// Used in `Socket::set_unicast_if_v4` and `Socket::set_unicast_if_v6`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) use libc::{IPV6_UNICAST_IF, IP_UNICAST_IF};
+// Used in `Socket::multicast_if_v4`.
+pub(crate) use libc::in_addr;
+// Used in `Socket::join_multicast_v6`.
+pub(crate) use libc::ipv6_mreq as Ipv6Mreq;
+// Used in `Socket::set_multicast_loop_v4` and `Socket::set_multicast_ttl_v4`.
+#[cfg(not(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+pub(crate) type IpV4MultiCastType = c_int;
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub(crate) type IpV4MultiCastType = libc::c_uchar;
+// Used in `Socket::recv_exact`.
+pub(crate) use libc::MSG_WAITALL;
+// Used in `RecvFlags`.
+pub(crate) use libc::{MSG_CTRUNC, MSG_TRUNC};
+// Used in `Interest` and `Readiness`.
+pub(crate) use libc::{POLLERR, POLLHUP, POLLIN, POLLOUT};
+
+// Maximum size of a buffer passed to system call like `recv` and `send`.
+#[cfg(not(target_os = "macos"))]
+const MAX_BUF_LEN: usize = libc::ssize_t::MAX as usize;
+
+// The maximum read limit on most POSIX-like systems is `SSIZE_MAX`, with the
+// man page quoting that if the count of bytes to read is greater than
+// `SSIZE_MAX` the result is "unspecified".
+//
+// On macOS, however, apparently the 64-bit libc is either buggy or
+// intentionally showing odd behavior by rejecting any read with a size larger
+// than or equal to INT_MAX. To handle both of these the read size is capped on
+// both platforms.
+#[cfg(target_os = "macos")]
+const MAX_BUF_LEN: usize = c_int::MAX as usize - 1;
+
+// libc doesn't expose these for ESP-IDF, the values are taken from lwIP. The
+// `CMSG_*` macros are defined below.
+#[cfg(target_os = "espidf")]
+pub(crate) const SOCK_RAW: c_int = 3;
+#[cfg(target_os = "espidf")]
+const FIONREAD: libc::c_ulong = 0x4004667f;
+#[cfg(not(target_os = "espidf"))]
+use libc::FIONREAD;
+#[cfg(not(target_os = "espidf"))]
+use libc::{CMSG_DATA, CMSG_LEN, CMSG_SPACE};
+
+#[cfg(target_os = "linux")]
+use libc::SO_ZEROCOPY;
+// Not (yet) exposed by libc.
+#[cfg(target_os = "android")]
+const SO_PEERGROUPS: c_int = 59;
+#[cfg(target_os = "linux")]
+use libc::SO_PEERGROUPS;
+#[cfg(target_os = "linux")]
+const SO_EE_ORIGIN_ZEROCOPY: u8 = 5;
+#[cfg(target_os = "linux")]
+const SO_EE_CODE_ZEROCOPY_COPIED: u8 = 1;
+#[cfg(target_os = "linux")]
+const SCM_TSTAMP_SND: u32 = 0;
+#[cfg(target_os = "linux")]
+const SCM_TSTAMP_SCHED: u32 = 1;
+#[cfg(target_os = "linux")]
+const SCM_TSTAMP_ACK: u32 = 2;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_NO_QUEUE: c_int = 0;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_RECV_QUEUE: c_int = 1;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const TCP_SEND_QUEUE: c_int = 2;
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+const TCP_NOTSENT_LOWAT: c_int = 0x201;
+// `SIOCOUTQ` is the same as `TIOCOUTQ`, but not defined in libc.
+#[cfg(any(target_os = "freebsd", target_os = "netbsd"))]
+use libc::FIONWRITE as SIOCOUTQ;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use libc::TCP_CORK;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use libc::TCP_NOTSENT_LOWAT;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+use libc::TIOCOUTQ as SIOCOUTQ;
+// `TCP_NOPUSH` is the BSD equivalent of `TCP_CORK`.
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "openbsd"
+))]
+use libc::TCP_NOPUSH as TCP_CORK;
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+const IP_PORTRANGE: c_int = 19;
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+const IP_PORTRANGE_DEFAULT: c_int = 0;
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+const IP_PORTRANGE_HIGH: c_int = 1;
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+const IP_PORTRANGE_LOW: c_int = 2;
 
 /// Unix only API.
 impl Domain {
@@ -36,6 +288,8 @@ impl Domain {
 
     /// Domain for low-level packet interface, corresponding to `AF_PACKET`.
     ///
+    /// Use [`SockAddr::packet`] to create an address for these sockets.
+    ///
     /// # Notes
     ///
     /// This function is only available on Linux.
@@ -50,14 +304,17 @@ impl Type {
     /// # Notes
     ///
     /// This function is only available on Android, DragonFlyBSD, FreeBSD,
-    /// Linux, NetBSD and OpenBSD.
+    /// illumos, Linux, NetBSD, OpenBSD, Redox and Solaris.
     #[cfg(any(
         target_os = "android",
         target_os = "dragonfly",
         target_os = "freebsd",
+        target_os = "illumos",
         target_os = "linux",
         target_os = "netbsd",
-        target_os = "openbsd"
+        target_os = "openbsd",
+        target_os = "redox",
+        target_os = "solaris"
     ))]
     pub fn non_blocking(self) -> Type {
         Type(self.0 | libc::SOCK_NONBLOCK)
@@ -68,249 +325,5947 @@ impl Type {
     /// # Notes
     ///
     /// This function is only available on Android, DragonFlyBSD, FreeBSD,
-    /// Linux, NetBSD and OpenBSD.
+    /// illumos, Linux, NetBSD, OpenBSD, Redox and Solaris.
     #[cfg(any(
         target_os = "android",
         target_os = "dragonfly",
         target_os = "freebsd",
+        target_os = "illumos",
         target_os = "linux",
         target_os = "netbsd",
-        target_os = "openbsd"
+        target_os = "openbsd",
+        target_os = "redox",
+        target_os = "solaris"
     ))]
     pub fn cloexec(self) -> Type {
         Type(self.0 | libc::SOCK_CLOEXEC)
     }
 }
 
-/// Helper macro to execute a system call that returns an `io::Result`.
-macro_rules! syscall {
-    ($fn: ident ( $($arg: expr),* $(,)* ) ) => {{
-        let res = unsafe { libc::$fn($($arg, )*) };
-        if res == -1 {
-            Err(std::io::Error::last_os_error())
-        } else {
-            Ok(res)
-        }
-    }};
+/// Unix only API.
+impl RecvFlags {
+    /// Check if the message terminates a record.
+    ///
+    /// Not all socket types support the notion of records. For socket types
+    /// that do support it (such as [`SEQPACKET`][Type::SEQPACKET]), a record
+    /// is terminated by sending a message with the end-of-record flag set.
+    ///
+    /// This corresponds to the `MSG_EOR` flag.
+    pub fn is_end_of_record(self) -> bool {
+        self.0 & libc::MSG_EOR != 0
+    }
 }
 
-pub(crate) fn socket(domain: c_int, type_: c_int, protocol: c_int) -> io::Result<Socket> {
-    syscall!(socket(domain, type_, protocol)).map(|fd| Socket { inner: fd })
-}
+/// Unix only API.
+impl SockAddr {
+    /// Constructs a `SockAddr` with the family `AF_UNIX` and the provided path.
+    ///
+    /// If the path starts with a null byte it's used as is, which on Android
+    /// and Linux is an address in the abstract namespace, see also
+    /// [`SockAddr::unix_abstract`].
+    ///
+    /// # Failure
+    ///
+    /// Returns an error if the path (including the null terminator) is longer
+    /// than `sun_path`. Paths starting with a null byte are not null
+    /// terminated, so they may be as long as `sun_path`.
+    pub fn unix<P>(path: P) -> io::Result<SockAddr>
+    where
+        P: AsRef<Path>,
+    {
+        let bytes = path.as_ref().as_os_str().as_bytes();
+        // SAFETY: all zeros is a valid representation for `sockaddr_un`.
+        let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+        // Pathnames must be null terminated, which is already the case as the
+        // address is zeroed, so the last byte can't be used. Abstract addresses
+        // don't need a null terminator.
+        let terminator = if bytes.first() == Some(&0) { 0 } else { 1 };
+        if bytes.len() + terminator > addr.sun_path.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "path must be shorter than SUN_LEN",
+            ));
+        }
+        addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+        for (dst, src) in addr.sun_path.iter_mut().zip(bytes) {
+            *dst = *src as libc::c_char;
+        }
 
-pub(crate) fn connect(
-    sockfd: RawSocket,
-    addr: *const libc::sockaddr_storage,
-    addrlen: libc::socklen_t,
-) -> io::Result<()> {
-    // Most OSes don't actually use `sockaddr_storage` in the `connect(2)` call,
-    // but `sockaddr_storage` can be converted safely into the correct type.
-    syscall!(connect(sockfd, addr as *const _, addrlen)).map(|_| ())
-}
+        let len = path_offset(&addr) + bytes.len() + terminator;
+        unix_sockaddr(&addr, len)
+    }
 
-pub(crate) fn bind(
-    sockfd: RawSocket,
-    addr: *const libc::sockaddr_storage,
-    addrlen: libc::socklen_t,
-) -> io::Result<()> {
-    // Most OSes don't actually use `sockaddr_storage` in the `bind(2)` call,
-    // but `sockaddr_storage` can be converted safely into the correct type.
-    syscall!(bind(sockfd, addr as *const _, addrlen)).map(|_| ())
-}
+    /// Constructs a `SockAddr` with the family `AF_UNIX` in the abstract
+    /// namespace, using `name` as address.
+    ///
+    /// `name` should not include the leading null byte, it's added by this
+    /// function. Abstract addresses are not null terminated, the length of the
+    /// address determines the name, so no trailing padding is included.
+    ///
+    /// # Failure
+    ///
+    /// Returns an error if the name (including the leading null byte) is
+    /// longer than `sun_path`.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn unix_abstract(name: &[u8]) -> io::Result<SockAddr> {
+        // SAFETY: all zeros is a valid representation for `sockaddr_un`.
+        let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+        if name.len() >= addr.sun_path.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "abstract name must be shorter than SUN_LEN",
+            ));
+        }
+        addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+        // The first byte of `sun_path` is left zero, marking the address as
+        // abstract.
+        for (dst, src) in addr.sun_path[1..].iter_mut().zip(name) {
+            *dst = *src as libc::c_char;
+        }
 
-pub(crate) fn listen(sockfd: RawSocket, backlog: c_int) -> io::Result<()> {
-    syscall!(listen(sockfd, backlog)).map(|_| ())
-}
+        let len = path_offset(&addr) + 1 + name.len();
+        unix_sockaddr(&addr, len)
+    }
 
-pub(crate) fn accept(sockfd: RawSocket) -> io::Result<(Socket, SockAddr)> {
-    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
-    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
-    syscall!(accept(sockfd, addr.as_mut_ptr() as *mut _, &mut addrlen)).map(|stream_fd| {
-        // This is safe because `accept(2)` filled in the address for us.
-        let addr = unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) };
-        (Socket { inner: stream_fd }, addr)
-    })
+    /// Returns `true` if this address is in the `AF_UNIX` family, `false`
+    /// otherwise.
+    pub fn is_unix(&self) -> bool {
+        self.family() == libc::AF_UNIX as libc::sa_family_t
+    }
+
+    /// Returns this address as a `Path` if it is an `AF_UNIX` pathname
+    /// address, otherwise returns `None`.
+    pub fn as_pathname(&self) -> Option<&Path> {
+        let path = sun_path(self)?;
+        match path.first() {
+            None | Some(0) => None,
+            Some(_) => {
+                // Some OSes include the null terminator (and padding) in the
+                // address length, so stop at the first null byte.
+                let len = path.iter().position(|b| *b == 0).unwrap_or(path.len());
+                Some(Path::new(OsStr::from_bytes(&path[..len])))
+            }
+        }
+    }
+
+    /// Returns the name of this address, without the leading null byte, if it
+    /// is an `AF_UNIX` address in the abstract namespace, otherwise returns
+    /// `None`.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn as_abstract_name(&self) -> Option<&[u8]> {
+        match sun_path(self)? {
+            [0, name @ ..] => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this is an unnamed `AF_UNIX` address, e.g. the
+    /// address of a socket that was never bound or a socket created by
+    /// [`Socket::pair`].
+    pub fn is_unnamed(&self) -> bool {
+        match sun_path(self) {
+            Some([]) => true,
+            // Outside of Android and Linux there is no abstract namespace, so
+            // an empty path is (sometimes) returned with padding included in
+            // the length.
+            Some([0, ..]) => cfg!(not(any(target_os = "android", target_os = "linux"))),
+            _ => false,
+        }
+    }
 }
 
-pub(crate) fn getsockname(sockfd: RawSocket) -> io::Result<SockAddr> {
-    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
-    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
-    syscall!(getsockname(
-        sockfd,
-        addr.as_mut_ptr() as *mut _,
-        &mut addrlen
-    ))
-    .map(|_| {
-        // This is safe because `getsockname(2)` filled in the address for us.
-        unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) }
-    })
+/// Linux only API.
+#[cfg(target_os = "linux")]
+impl SockAddr {
+    /// Constructs a `SockAddr` with the family `AF_PACKET`, i.e. a
+    /// `sockaddr_ll`, used with [`Domain::PACKET`] sockets.
+    ///
+    /// `protocol` is the `ETH_P_*` protocol in host byte order, `ifindex` the
+    /// index of the interface (zero meaning any interface) and `addr` the
+    /// link-layer (e.g. MAC) address, which may be empty when binding.
+    ///
+    /// # Failure
+    ///
+    /// Returns an error if `addr` is longer than `sll_addr` (8 bytes).
+    pub fn packet(protocol: u16, ifindex: u32, addr: &[u8]) -> io::Result<SockAddr> {
+        // SAFETY: all zeros is a valid representation for `sockaddr_storage`.
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        // SAFETY: `sockaddr_storage` is large enough and suitably aligned to
+        // hold any socket address, including `sockaddr_ll`.
+        let ll = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_ll) };
+        if addr.len() > ll.sll_addr.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "link-layer address must be no longer than 8 bytes",
+            ));
+        }
+        ll.sll_family = libc::AF_PACKET as libc::sa_family_t;
+        ll.sll_protocol = protocol.to_be();
+        ll.sll_ifindex = ifindex as c_int;
+        ll.sll_halen = addr.len() as u8;
+        ll.sll_addr[..addr.len()].copy_from_slice(addr);
+        let len = size_of::<libc::sockaddr_ll>() as libc::socklen_t;
+        // SAFETY: `storage` holds a valid `sockaddr_ll`.
+        Ok(unsafe { SockAddr::from_raw_parts(storage, len) })
+    }
+
+    /// Returns `true` if this address is in the `AF_PACKET` family, `false`
+    /// otherwise.
+    pub fn is_packet(&self) -> bool {
+        self.family() == libc::AF_PACKET as libc::sa_family_t
+    }
+
+    /// Returns the `ETH_P_*` protocol, in host byte order, if this is an
+    /// `AF_PACKET` address.
+    pub fn packet_protocol(&self) -> Option<u16> {
+        sockaddr_ll(self).map(|ll| u16::from_be(ll.sll_protocol))
+    }
+
+    /// Returns the interface index if this is an `AF_PACKET` address.
+    pub fn packet_ifindex(&self) -> Option<u32> {
+        sockaddr_ll(self).map(|ll| ll.sll_ifindex as u32)
+    }
+
+    /// Returns the ARP hardware type (`ARPHRD_*`) if this is an `AF_PACKET`
+    /// address, only set on addresses of received frames.
+    pub fn packet_hatype(&self) -> Option<u16> {
+        sockaddr_ll(self).map(|ll| ll.sll_hatype)
+    }
+
+    /// Returns the link-layer (e.g. MAC) address if this is an `AF_PACKET`
+    /// address.
+    pub fn packet_addr(&self) -> Option<&[u8]> {
+        sockaddr_ll(self).map(|ll| {
+            let len = min(ll.sll_halen as usize, ll.sll_addr.len());
+            &ll.sll_addr[..len]
+        })
+    }
 }
 
-pub(crate) fn getpeername(sockfd: RawSocket) -> io::Result<SockAddr> {
-    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
-    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
-    syscall!(getpeername(
-        sockfd,
-        addr.as_mut_ptr() as *mut _,
-        &mut addrlen
-    ))
-    .map(|_| {
-        // This is safe because `getpeername(2)` filled in the address for us.
-        unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) }
-    })
+/// Returns `addr` as `sockaddr_ll`, or `None` if it's not an `AF_PACKET`
+/// address.
+#[cfg(target_os = "linux")]
+fn sockaddr_ll(addr: &SockAddr) -> Option<&libc::sockaddr_ll> {
+    if !addr.is_packet() {
+        return None;
+    }
+    // SAFETY: the family is `AF_PACKET`, so the storage holds a `sockaddr_ll`.
+    Some(unsafe { &*(addr.as_ptr() as *const libc::sockaddr_ll) })
 }
 
-pub(crate) fn shutdown(sockfd: RawSocket, how: Shutdown) -> io::Result<()> {
-    let how = match how {
-        Shutdown::Write => libc::SHUT_WR,
-        Shutdown::Read => libc::SHUT_RD,
-        Shutdown::Both => libc::SHUT_RDWR,
-    };
-    syscall!(shutdown(sockfd, how)).map(|_| ())
+/// Creates a `SockAddr` from the first `len` bytes of `addr`.
+///
+/// `sockaddr_un` doesn't fit in `sockaddr_storage` on all platforms (e.g.
+/// ESP-IDF), so this returns an error if `len` bytes don't fit either.
+fn unix_sockaddr(addr: &libc::sockaddr_un, len: usize) -> io::Result<SockAddr> {
+    if len > size_of::<libc::sockaddr_storage>() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "path is too long for sockaddr_storage",
+        ));
+    }
+    // SAFETY: all zeros is a valid representation for `sockaddr_storage`.
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    // SAFETY: `len` bytes are within bounds of both `addr` and `storage`, which
+    // don't overlap.
+    unsafe {
+        std::ptr::copy_nonoverlapping(
+            (addr as *const libc::sockaddr_un).cast::<u8>(),
+            (&mut storage as *mut libc::sockaddr_storage).cast::<u8>(),
+            len,
+        );
+    }
+    // SAFETY: `storage` holds a valid `sockaddr_un` of length `len`.
+    Ok(unsafe { SockAddr::from_raw_parts(storage, len as libc::socklen_t) })
 }
 
-pub(crate) fn setsockopt<T>(
-    sockfd: RawSocket,
-    level: c_int,
-    optname: c_int,
-    opt: &T,
-) -> io::Result<()> {
-    syscall!(setsockopt(
-        sockfd,
-        level,
-        optname,
-        opt as *const _ as *const _,
-        size_of::<T>() as libc::socklen_t,
-    ))
-    .map(|_| ())
+/// Returns the offset of the `sun_path` field in `sockaddr_un`.
+fn path_offset(addr: &libc::sockaddr_un) -> usize {
+    let base = addr as *const _ as usize;
+    let path = &addr.sun_path as *const _ as usize;
+    path - base
 }
 
-pub(crate) fn getsockopt<T>(sockfd: RawSocket, level: c_int, optname: c_int) -> io::Result<T> {
-    let mut optval: MaybeUninit<T> = MaybeUninit::uninit();
-    let mut optlen = size_of::<T>() as libc::socklen_t;
-    syscall!(getsockopt(
-        sockfd,
-        level,
-        optname,
-        optval.as_mut_ptr() as *mut _,
-        &mut optlen
-    ))
-    .map(|_| unsafe {
-        // Safe because `getsockopt(2)` initialised the value for us.
-        debug_assert_eq!(optlen as usize, size_of::<T>());
-        optval.assume_init()
-    })
+/// Returns the bytes of `sun_path` that are part of `addr`, or `None` if it's
+/// not an `AF_UNIX` address.
+fn sun_path(addr: &SockAddr) -> Option<&[u8]> {
+    if !addr.is_unix() {
+        return None;
+    }
+    // `sockaddr_storage` can be smaller than `sockaddr_un` (e.g. on ESP-IDF), so
+    // only the bytes that are part of the address are read, without creating a
+    // reference to a `sockaddr_un`.
+    // SAFETY: all zeros is a valid representation for `sockaddr_un`.
+    let offset = path_offset(&unsafe { mem::zeroed() });
+    let len = (addr.len() as usize)
+        .min(size_of::<libc::sockaddr_storage>())
+        .min(size_of::<libc::sockaddr_un>())
+        .saturating_sub(offset);
+    // SAFETY: the `len` bytes starting at `offset` are within bounds of both
+    // the storage and `sun_path`.
+    Some(unsafe { std::slice::from_raw_parts(addr.as_ptr().cast::<u8>().add(offset), len) })
 }
 
-pub(crate) fn fcntl<T>(sockfd: RawSocket, cmd: c_int, arg: T) -> io::Result<c_int> {
-    syscall!(fcntl(sockfd, cmd, arg))
+/// Completion notification of [`Socket::send_zerocopy`] calls.
+///
+/// Every call to `send_zerocopy` is assigned a 32-bit sequence number (per
+/// socket), starting at zero. A single notification can complete a range of
+/// these calls, once a call is completed the buffer passed to it may be
+/// reused.
+///
+/// See [`Socket::recv_zerocopy_completion`].
+///
+/// # Notes
+///
+/// This type is only available on Linux.
+#[cfg(target_os = "linux")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ZerocopyCompletion {
+    start: u32,
+    end: u32,
+    copied: bool,
 }
 
-/// Unix only API.
-impl Socket {
-    /// Creates a pair of sockets which are connected to each other.
-    ///
-    /// This function corresponds to `socketpair(2)`.
-    pub fn pair(
-        domain: Domain,
-        type_: Type,
-        protocol: Option<Protocol>,
-    ) -> io::Result<(Socket, Socket)> {
-        let mut fds = [0, 0];
-        let protocol = protocol.map(|p| p.0).unwrap_or(0);
-        syscall!(socketpair(domain.0, type_.0, protocol, fds.as_mut_ptr()))
-            .map(|_| (Socket { inner: fds[0] }, Socket { inner: fds[1] }))
+#[cfg(target_os = "linux")]
+impl ZerocopyCompletion {
+    /// Sequence number of the first completed `send_zerocopy` call.
+    pub fn start(&self) -> u32 {
+        self.start
     }
 
-    /// Accept a new incoming connection from this listener.
-    ///
-    /// This function directly corresponds to the `accept4(2)` function.
+    /// Sequence number of the last (inclusive) completed `send_zerocopy`
+    /// call.
     ///
-    /// # Notes
+    /// Note that the sequence number can wrap around, so this can be smaller
+    /// than [`start`].
     ///
-    /// This only available on Android, DragonFlyBSD, FreeBSD, Linux and
-    /// OpenBSD. Once https://github.com/rust-lang/libc/issues/1636 is fixed
-    /// NetBSD will also support it.
-    #[cfg(any(
-        target_os = "android",
-        target_os = "dragonfly",
-        target_os = "freebsd",
-        target_os = "linux",
-        // NetBSD 8.0 actually has `accept4(2)`, but libc doesn't expose it
-        // (yet). See https://github.com/rust-lang/libc/issues/1636.
-        //target_os = "netbsd",
-        target_os = "openbsd"
-    ))]
-    pub fn accept4(&self, flags: c_int) -> io::Result<(Socket, SockAddr)> {
-        let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
-        let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
-        syscall!(accept4(
-            self.inner,
-            addr.as_mut_ptr() as *mut _,
-            &mut addrlen,
-            flags
-        ))
-        .map(|stream_fd| {
-            // This is safe because `accept(2)` filled in the address for us.
-            let addr = unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) };
-            (Socket { inner: stream_fd }, addr)
-        })
+    /// [`start`]: ZerocopyCompletion::start
+    pub fn end(&self) -> u32 {
+        self.end
     }
-}
 
-impl From<UnixStream> for Socket {
-    fn from(socket: UnixStream) -> Socket {
-        unsafe { Socket::from_raw_fd(socket.into_raw_fd()) }
+    /// Returns `true` if the kernel fell back to copying the data, e.g. when
+    /// sending to the loopback device. In that case `MSG_ZEROCOPY` has no
+    /// benefit and only adds overhead.
+    pub fn copied(&self) -> bool {
+        self.copied
     }
 }
 
-impl Into<UnixStream> for Socket {
-    fn into(self) -> UnixStream {
-        unsafe { UnixStream::from_raw_fd(self.into_raw_fd()) }
+/// Flags for the `SO_TIMESTAMPING` option, see [`Socket::set_timestamping`].
+///
+/// Flags can be combined using the `|` operator. The flags fall into three
+/// groups: generation flags (e.g. [`Timestamping::RX_SOFTWARE`]) determine
+/// which timestamps are generated, reporting flags (e.g.
+/// [`Timestamping::SOFTWARE`]) determine which of the generated timestamps
+/// are reported, and option flags (e.g. [`Timestamping::OPT_ID`]) modify
+/// the reporting. See the Linux kernel's timestamping documentation for the
+/// full details.
+///
+/// # Notes
+///
+/// This type is only available on Linux.
+#[cfg(target_os = "linux")]
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Timestamping(u32);
+
+#[cfg(target_os = "linux")]
+impl Timestamping {
+    /// Generate hardware timestamps for outgoing packets,
+    /// `SOF_TIMESTAMPING_TX_HARDWARE`.
+    pub const TX_HARDWARE: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_TX_HARDWARE);
+    /// Generate software timestamps for outgoing packets,
+    /// `SOF_TIMESTAMPING_TX_SOFTWARE`.
+    pub const TX_SOFTWARE: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_TX_SOFTWARE);
+    /// Generate hardware timestamps for incoming packets,
+    /// `SOF_TIMESTAMPING_RX_HARDWARE`.
+    pub const RX_HARDWARE: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_RX_HARDWARE);
+    /// Generate software timestamps for incoming packets,
+    /// `SOF_TIMESTAMPING_RX_SOFTWARE`.
+    pub const RX_SOFTWARE: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_RX_SOFTWARE);
+    /// Generate a timestamp when an outgoing packet enters the packet
+    /// scheduler, `SOF_TIMESTAMPING_TX_SCHED`.
+    pub const TX_SCHED: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_TX_SCHED);
+    /// Generate a timestamp when all data in an outgoing TCP segment is
+    /// acknowledged, `SOF_TIMESTAMPING_TX_ACK`.
+    pub const TX_ACK: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_TX_ACK);
+    /// Report software timestamps, `SOF_TIMESTAMPING_SOFTWARE`.
+    pub const SOFTWARE: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_SOFTWARE);
+    /// Report hardware timestamps, `SOF_TIMESTAMPING_RAW_HARDWARE`.
+    pub const RAW_HARDWARE: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_RAW_HARDWARE);
+    /// Assign an identifier to every sent packet, returned in
+    /// [`TxTimestamp::id`], `SOF_TIMESTAMPING_OPT_ID`.
+    pub const OPT_ID: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_OPT_ID);
+    /// Also report the receive timestamp of looped packets as control message
+    /// on the error queue, `SOF_TIMESTAMPING_OPT_CMSG`.
+    pub const OPT_CMSG: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_OPT_CMSG);
+    /// Only return the timestamp on the error queue, not the outgoing packet
+    /// itself, `SOF_TIMESTAMPING_OPT_TSONLY`.
+    pub const OPT_TSONLY: Timestamping = Timestamping(libc::SOF_TIMESTAMPING_OPT_TSONLY);
+
+    /// No flags set, disabling timestamping.
+    pub const fn empty() -> Timestamping {
+        Timestamping(0)
+    }
+
+    /// Returns `true` if all flags in `other` are set in `self`.
+    pub const fn contains(self, other: Timestamping) -> bool {
+        self.0 & other.0 == other.0
     }
 }
 
-impl From<UnixListener> for Socket {
-    fn from(socket: UnixListener) -> Socket {
-        unsafe { Socket::from_raw_fd(socket.into_raw_fd()) }
+#[cfg(target_os = "linux")]
+impl BitOr for Timestamping {
+    type Output = Timestamping;
+
+    fn bitor(self, other: Timestamping) -> Timestamping {
+        Timestamping(self.0 | other.0)
     }
 }
 
-impl Into<UnixListener> for Socket {
-    fn into(self) -> UnixListener {
-        unsafe { UnixListener::from_raw_fd(self.into_raw_fd()) }
+#[cfg(target_os = "linux")]
+impl BitOrAssign for Timestamping {
+    fn bitor_assign(&mut self, other: Timestamping) {
+        self.0 |= other.0;
     }
 }
 
-impl From<UnixDatagram> for Socket {
-    fn from(socket: UnixDatagram) -> Socket {
-        unsafe { Socket::from_raw_fd(socket.into_raw_fd()) }
+#[cfg(target_os = "linux")]
+impl From<Timestamping> for u32 {
+    fn from(flags: Timestamping) -> u32 {
+        flags.0
     }
 }
 
-impl Into<UnixDatagram> for Socket {
-    fn into(self) -> UnixDatagram {
-        unsafe { UnixDatagram::from_raw_fd(self.into_raw_fd()) }
+#[cfg(target_os = "linux")]
+impl From<u32> for Timestamping {
+    fn from(flags: u32) -> Timestamping {
+        Timestamping(flags)
     }
 }
 
-impl FromRawFd for Socket {
-    unsafe fn from_raw_fd(fd: RawFd) -> Socket {
-        Socket { inner: fd }
+/// Timestamps of a packet, read from a `SCM_TIMESTAMPING` control message.
+///
+/// See [`Cmsg::timestamping`] and [`TxTimestamp::timestamps`].
+///
+/// # Notes
+///
+/// This type is only available on Linux.
+#[cfg(target_os = "linux")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Timestamps {
+    software: Option<Duration>,
+    hardware: Option<Duration>,
+}
+
+#[cfg(target_os = "linux")]
+impl Timestamps {
+    /// Software timestamp, relative to the Unix epoch (`CLOCK_REALTIME`).
+    ///
+    /// Requires [`Timestamping::SOFTWARE`] to be set.
+    pub fn software(&self) -> Option<Duration> {
+        self.software
+    }
+
+    /// Hardware timestamp, relative to the clock of the network interface.
+    ///
+    /// Requires [`Timestamping::RAW_HARDWARE`] to be set.
+    pub fn hardware(&self) -> Option<Duration> {
+        self.hardware
     }
 }
 
-impl AsRawFd for Socket {
-    fn as_raw_fd(&self) -> RawFd {
-        self.inner
+/// Timestamps of a sent packet, read from the socket's error queue using
+/// [`Socket::recv_tx_timestamp`].
+///
+/// # Notes
+///
+/// This type is only available on Linux.
+#[cfg(target_os = "linux")]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TxTimestamp {
+    timestamps: Timestamps,
+    id: u32,
+    kind: u32,
+}
+
+#[cfg(target_os = "linux")]
+impl TxTimestamp {
+    /// The timestamps of the packet.
+    pub fn timestamps(&self) -> Timestamps {
+        self.timestamps
+    }
+
+    /// Identifier of the sent packet, if [`Timestamping::OPT_ID`] is set.
+    ///
+    /// For datagram sockets this is the number of the send call, starting at
+    /// zero. For stream sockets it's the byte offset in the stream.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns `true` if the timestamp was taken when the packet entered the
+    /// packet scheduler, see [`Timestamping::TX_SCHED`].
+    pub fn is_scheduled(&self) -> bool {
+        self.kind == SCM_TSTAMP_SCHED
+    }
+
+    /// Returns `true` if the timestamp was taken when the packet was sent,
+    /// see [`Timestamping::TX_SOFTWARE`] and [`Timestamping::TX_HARDWARE`].
+    pub fn is_sent(&self) -> bool {
+        self.kind == SCM_TSTAMP_SND
+    }
+
+    /// Returns `true` if the timestamp was taken when the packet was
+    /// acknowledged by the peer, see [`Timestamping::TX_ACK`].
+    pub fn is_acked(&self) -> bool {
+        self.kind == SCM_TSTAMP_ACK
     }
 }
 
-impl IntoRawFd for Socket {
-    fn into_raw_fd(self) -> RawFd {
-        let fd = self.inner;
-        mem::forget(self);
-        fd
+/// Extended error read from the socket's error queue, i.e. a `struct
+/// sock_extended_err`.
+///
+/// See [`Socket::recv_error`] and [`Cmsg::extended_error`].
+///
+/// # Notes
+///
+/// This type is only available on Android and Linux.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ExtendedError {
+    errno: u32,
+    origin: u8,
+    r#type: u8,
+    code: u8,
+    info: u32,
+    data: u32,
+    offender: Option<SocketAddr>,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl ExtendedError {
+    /// The error, e.g. `ECONNREFUSED` for an ICMP port unreachable message
+    /// or `EMSGSIZE` if the packet was too big for the path MTU.
+    pub fn error(&self) -> io::Error {
+        io::Error::from_raw_os_error(self.errno as i32)
+    }
+
+    /// Raw error number of [`error`].
+    ///
+    /// [`error`]: ExtendedError::error
+    pub fn errno(&self) -> u32 {
+        self.errno
+    }
+
+    /// Origin of the error, one of the `SO_EE_ORIGIN_*` constants, e.g.
+    /// `SO_EE_ORIGIN_ICMP`.
+    pub fn origin(&self) -> u8 {
+        self.origin
+    }
+
+    /// Returns `true` if the error originates from an ICMP or ICMPv6
+    /// message.
+    pub fn is_icmp(&self) -> bool {
+        self.origin == libc::SO_EE_ORIGIN_ICMP || self.origin == libc::SO_EE_ORIGIN_ICMP6
+    }
+
+    /// Type of the ICMP message, only meaningful if [`is_icmp`] returns
+    /// `true`.
+    ///
+    /// [`is_icmp`]: ExtendedError::is_icmp
+    pub fn icmp_type(&self) -> u8 {
+        self.r#type
+    }
+
+    /// Code of the ICMP message, only meaningful if [`is_icmp`] returns
+    /// `true`.
+    ///
+    /// [`is_icmp`]: ExtendedError::is_icmp
+    pub fn icmp_code(&self) -> u8 {
+        self.code
+    }
+
+    /// Additional information about the error, e.g. the path MTU for
+    /// `EMSGSIZE` errors.
+    pub fn info(&self) -> u32 {
+        self.info
+    }
+
+    /// Additional data about the error, the meaning depends on the origin.
+    pub fn data(&self) -> u32 {
+        self.data
+    }
+
+    /// Address of the node that caused the error, e.g. the router that sent
+    /// the ICMP message. Returns `None` if the address is unknown.
+    pub fn offender(&self) -> Option<SocketAddr> {
+        self.offender
+    }
+}
+
+/// `struct scm_timestamping`, not defined in `libc`.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct scm_timestamping {
+    ts: [libc::timespec; 3],
+}
+
+/// Converts a timestamp to a `Duration`, returns `None` if the timestamp is
+/// zero, i.e. not set.
+#[cfg(target_os = "linux")]
+fn from_timespec(ts: libc::timespec) -> Option<Duration> {
+    if ts.tv_sec == 0 && ts.tv_nsec == 0 {
+        None
+    } else {
+        Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+    }
+}
+
+/// TCP connection information, as returned by [`Socket::tcp_info`].
+///
+/// This mirrors the start of Linux's `struct tcp_info`. Older kernels may
+/// return fewer fields than defined here, in that case the missing fields are
+/// zero.
+///
+/// # Notes
+///
+/// This type is only available on Linux.
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+// Not all fields have a getter (yet), but they're needed for the layout.
+#[allow(dead_code)]
+pub struct TcpInfo {
+    state: u8,
+    ca_state: u8,
+    retransmits: u8,
+    probes: u8,
+    backoff: u8,
+    options: u8,
+    snd_rcv_wscale: u8,
+    flags: u8,
+    rto: u32,
+    ato: u32,
+    snd_mss: u32,
+    rcv_mss: u32,
+    unacked: u32,
+    sacked: u32,
+    lost: u32,
+    retrans: u32,
+    fackets: u32,
+    last_data_sent: u32,
+    last_ack_sent: u32,
+    last_data_recv: u32,
+    last_ack_recv: u32,
+    pmtu: u32,
+    rcv_ssthresh: u32,
+    rtt: u32,
+    rttvar: u32,
+    snd_ssthresh: u32,
+    snd_cwnd: u32,
+    advmss: u32,
+    reordering: u32,
+    rcv_rtt: u32,
+    rcv_space: u32,
+    total_retrans: u32,
+    pacing_rate: u64,
+    max_pacing_rate: u64,
+    bytes_acked: u64,
+    bytes_received: u64,
+    segs_out: u32,
+    segs_in: u32,
+    notsent_bytes: u32,
+    min_rtt: u32,
+}
+
+#[cfg(target_os = "linux")]
+impl TcpInfo {
+    /// State of the connection, one of the `TCP_*` states, e.g.
+    /// `TCP_ESTABLISHED` (1).
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// State of the congestion control algorithm, one of the `TCP_CA_*`
+    /// states.
+    pub fn ca_state(&self) -> u8 {
+        self.ca_state
+    }
+
+    /// Number of unrecovered retransmission timeouts.
+    pub fn retransmits(&self) -> u8 {
+        self.retransmits
+    }
+
+    /// Retransmission timeout.
+    pub fn rto(&self) -> Duration {
+        Duration::from_micros(self.rto as u64)
+    }
+
+    /// Delayed acknowledgement timeout.
+    pub fn ato(&self) -> Duration {
+        Duration::from_micros(self.ato as u64)
+    }
+
+    /// Maximum segment size for sending, in bytes.
+    pub fn snd_mss(&self) -> u32 {
+        self.snd_mss
+    }
+
+    /// Maximum segment size for receiving, in bytes.
+    pub fn rcv_mss(&self) -> u32 {
+        self.rcv_mss
+    }
+
+    /// Number of segments sent but not yet acknowledged.
+    pub fn unacked(&self) -> u32 {
+        self.unacked
+    }
+
+    /// Number of segments considered lost.
+    pub fn lost(&self) -> u32 {
+        self.lost
+    }
+
+    /// Number of segments currently being retransmitted.
+    pub fn retrans(&self) -> u32 {
+        self.retrans
+    }
+
+    /// Path MTU.
+    pub fn pmtu(&self) -> u32 {
+        self.pmtu
+    }
+
+    /// Smoothed round trip time.
+    pub fn rtt(&self) -> Duration {
+        Duration::from_micros(self.rtt as u64)
+    }
+
+    /// Round trip time variance.
+    pub fn rttvar(&self) -> Duration {
+        Duration::from_micros(self.rttvar as u64)
+    }
+
+    /// Slow start threshold, in segments.
+    pub fn snd_ssthresh(&self) -> u32 {
+        self.snd_ssthresh
+    }
+
+    /// Congestion window, in segments.
+    pub fn snd_cwnd(&self) -> u32 {
+        self.snd_cwnd
+    }
+
+    /// Reordering metric, in segments.
+    pub fn reordering(&self) -> u32 {
+        self.reordering
+    }
+
+    /// Total number of retransmitted segments over the lifetime of the
+    /// connection.
+    pub fn total_retrans(&self) -> u32 {
+        self.total_retrans
+    }
+
+    /// Current pacing rate, in bytes per second.
+    ///
+    /// Requires Linux 3.15, zero on older kernels.
+    pub fn pacing_rate(&self) -> u64 {
+        self.pacing_rate
+    }
+
+    /// Number of bytes acknowledged by the peer.
+    ///
+    /// Requires Linux 4.1, zero on older kernels.
+    pub fn bytes_acked(&self) -> u64 {
+        self.bytes_acked
+    }
+
+    /// Number of bytes received from the peer.
+    ///
+    /// Requires Linux 4.1, zero on older kernels.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Number of segments sent.
+    ///
+    /// Requires Linux 4.2, zero on older kernels.
+    pub fn segs_out(&self) -> u32 {
+        self.segs_out
+    }
+
+    /// Number of segments received.
+    ///
+    /// Requires Linux 4.2, zero on older kernels.
+    pub fn segs_in(&self) -> u32 {
+        self.segs_in
+    }
+
+    /// Number of bytes in the send queue that are not yet sent.
+    ///
+    /// Requires Linux 4.6, zero on older kernels.
+    pub fn notsent_bytes(&self) -> u32 {
+        self.notsent_bytes
+    }
+
+    /// Minimum round trip time observed.
+    ///
+    /// Requires Linux 4.6, zero on older kernels.
+    pub fn min_rtt(&self) -> Duration {
+        Duration::from_micros(self.min_rtt as u64)
+    }
+}
+
+/// TCP connection information, as returned by
+/// [`Socket::tcp_connection_info`].
+///
+/// This mirrors Apple's `struct tcp_connection_info`, the equivalent of
+/// [`TcpInfo`] on Linux.
+///
+/// # Notes
+///
+/// This type is only available on iOS and macOS.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+// Not all fields have a getter (yet), but they're needed for the layout.
+#[allow(dead_code)]
+pub struct TcpConnectionInfo {
+    state: u8,
+    snd_wscale: u8,
+    rcv_wscale: u8,
+    _pad1: u8,
+    options: u32,
+    flags: u32,
+    rto: u32,
+    maxseg: u32,
+    snd_ssthresh: u32,
+    snd_cwnd: u32,
+    snd_wnd: u32,
+    snd_sbbytes: u32,
+    rcv_wnd: u32,
+    rttcur: u32,
+    srtt: u32,
+    rttvar: u32,
+    // TCP Fast Open bitfields.
+    tfo: u32,
+    txpackets: u64,
+    txbytes: u64,
+    txretransmitbytes: u64,
+    rxpackets: u64,
+    rxbytes: u64,
+    rxoutoforderbytes: u64,
+    rxretransmitpackets: u64,
+}
+
+// `libc::tcp_connection_info` declares the TCP Fast Open bitfields as separate
+// `u32` fields, which doesn't match the OS header and moves all counters after
+// it, so it can't be wrapped. Instead the fields before the bitfields are
+// checked against it.
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+const _: () = {
+    use std::mem::offset_of;
+    assert!(
+        offset_of!(TcpConnectionInfo, state) == offset_of!(libc::tcp_connection_info, tcpi_state)
+    );
+    assert!(
+        offset_of!(TcpConnectionInfo, options)
+            == offset_of!(libc::tcp_connection_info, tcpi_options)
+    );
+    assert!(offset_of!(TcpConnectionInfo, rto) == offset_of!(libc::tcp_connection_info, tcpi_rto));
+    assert!(
+        offset_of!(TcpConnectionInfo, rttvar) == offset_of!(libc::tcp_connection_info, tcpi_rttvar)
+    );
+    assert!(
+        offset_of!(TcpConnectionInfo, tfo)
+            == offset_of!(libc::tcp_connection_info, tcpi_tfo_cookie_req)
+    );
+};
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+impl TcpConnectionInfo {
+    /// State of the connection, one of the `TCPS_*` states, e.g.
+    /// `TCPS_ESTABLISHED` (4).
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// Window scale for sending.
+    pub fn snd_wscale(&self) -> u8 {
+        self.snd_wscale
+    }
+
+    /// Window scale for receiving.
+    pub fn rcv_wscale(&self) -> u8 {
+        self.rcv_wscale
+    }
+
+    /// Retransmission timeout.
+    pub fn rto(&self) -> Duration {
+        Duration::from_millis(self.rto as u64)
+    }
+
+    /// Maximum segment size, in bytes.
+    pub fn maxseg(&self) -> u32 {
+        self.maxseg
+    }
+
+    /// Slow start threshold, in bytes.
+    pub fn snd_ssthresh(&self) -> u32 {
+        self.snd_ssthresh
+    }
+
+    /// Congestion window, in bytes.
+    pub fn snd_cwnd(&self) -> u32 {
+        self.snd_cwnd
+    }
+
+    /// Send window, in bytes.
+    pub fn snd_wnd(&self) -> u32 {
+        self.snd_wnd
+    }
+
+    /// Number of bytes in the send buffer, including in-flight data.
+    pub fn snd_sbbytes(&self) -> u32 {
+        self.snd_sbbytes
+    }
+
+    /// Receive window, in bytes.
+    pub fn rcv_wnd(&self) -> u32 {
+        self.rcv_wnd
+    }
+
+    /// Most recent round trip time.
+    pub fn rttcur(&self) -> Duration {
+        Duration::from_millis(self.rttcur as u64)
+    }
+
+    /// Smoothed round trip time.
+    pub fn srtt(&self) -> Duration {
+        Duration::from_millis(self.srtt as u64)
+    }
+
+    /// Round trip time variance.
+    pub fn rttvar(&self) -> Duration {
+        Duration::from_millis(self.rttvar as u64)
+    }
+
+    /// Number of packets sent.
+    pub fn txpackets(&self) -> u64 {
+        self.txpackets
+    }
+
+    /// Number of bytes sent.
+    pub fn txbytes(&self) -> u64 {
+        self.txbytes
+    }
+
+    /// Number of bytes retransmitted.
+    pub fn txretransmitbytes(&self) -> u64 {
+        self.txretransmitbytes
+    }
+
+    /// Number of packets received.
+    pub fn rxpackets(&self) -> u64 {
+        self.rxpackets
+    }
+
+    /// Number of bytes received.
+    pub fn rxbytes(&self) -> u64 {
+        self.rxbytes
+    }
+
+    /// Number of bytes received out of order.
+    pub fn rxoutoforderbytes(&self) -> u64 {
+        self.rxoutoforderbytes
+    }
+
+    /// Number of retransmitted packets received.
+    pub fn rxretransmitpackets(&self) -> u64 {
+        self.rxretransmitpackets
+    }
+}
+
+/// Credentials of a process, as returned by [`Socket::peer_cred`].
+///
+/// # Notes
+///
+/// This type is only available on Android, DragonFlyBSD, FreeBSD, iOS, Linux,
+/// macOS, NetBSD and OpenBSD.
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct UCred {
+    pid: Option<libc::pid_t>,
+    uid: libc::uid_t,
+    gid: libc::gid_t,
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+impl UCred {
+    /// Process id of the process.
+    ///
+    /// This is `None` if the platform doesn't provide the process id, e.g.
+    /// for [`Socket::peer_cred`] on the BSDs other than iOS and macOS.
+    pub fn pid(&self) -> Option<libc::pid_t> {
+        self.pid
+    }
+
+    /// Effective user id of the process.
+    pub fn uid(&self) -> libc::uid_t {
+        self.uid
+    }
+
+    /// Effective group id of the process.
+    pub fn gid(&self) -> libc::gid_t {
+        self.gid
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl UCred {
+    /// Create new credentials, e.g. to send in a `SCM_CREDENTIALS` control
+    /// message using [`CmsgBuffer::push_credentials`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    pub fn new(pid: libc::pid_t, uid: libc::uid_t, gid: libc::gid_t) -> UCred {
+        UCred {
+            pid: Some(pid),
+            uid,
+            gid,
+        }
+    }
+}
+
+/// Path MTU discovery mode, see [`Socket::set_mtu_discover`].
+///
+/// # Notes
+///
+/// This type is only available on Android and Linux.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PmtudMode {
+    /// Never send packets with the Don't Fragment (DF) flag set, i.e.
+    /// `IP_PMTUDISC_DONT`.
+    Dont,
+    /// Use per-route settings, i.e. `IP_PMTUDISC_WANT`.
+    Want,
+    /// Always set the Don't Fragment (DF) flag and perform path MTU discovery,
+    /// i.e. `IP_PMTUDISC_DO`. Sending a datagram larger than the path MTU
+    /// fails with `EMSGSIZE`.
+    Do,
+    /// Set the Don't Fragment (DF) flag but ignore the path MTU, i.e.
+    /// `IP_PMTUDISC_PROBE`. Useful for probing the path MTU.
+    Probe,
+    /// Always use the interface MTU and ignore ICMP fragmentation needed
+    /// messages, i.e. `IP_PMTUDISC_INTERFACE`.
+    Interface,
+    /// Like `Interface`, but allow fragmentation if the packet is larger than
+    /// the interface MTU, i.e. `IP_PMTUDISC_OMIT`.
+    Omit,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl PmtudMode {
+    fn from_raw(mode: c_int) -> io::Result<PmtudMode> {
+        match mode {
+            libc::IP_PMTUDISC_DONT => Ok(PmtudMode::Dont),
+            libc::IP_PMTUDISC_WANT => Ok(PmtudMode::Want),
+            libc::IP_PMTUDISC_DO => Ok(PmtudMode::Do),
+            libc::IP_PMTUDISC_PROBE => Ok(PmtudMode::Probe),
+            libc::IP_PMTUDISC_INTERFACE => Ok(PmtudMode::Interface),
+            libc::IP_PMTUDISC_OMIT => Ok(PmtudMode::Omit),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unknown path MTU discovery mode",
+            )),
+        }
+    }
+
+    // NOTE: the `IPV6_PMTUDISC_*` constants have the same values.
+    fn into_raw(self) -> c_int {
+        match self {
+            PmtudMode::Dont => libc::IP_PMTUDISC_DONT,
+            PmtudMode::Want => libc::IP_PMTUDISC_WANT,
+            PmtudMode::Do => libc::IP_PMTUDISC_DO,
+            PmtudMode::Probe => libc::IP_PMTUDISC_PROBE,
+            PmtudMode::Interface => libc::IP_PMTUDISC_INTERFACE,
+            PmtudMode::Omit => libc::IP_PMTUDISC_OMIT,
+        }
+    }
+}
+
+/// TCP queue selected in repair mode, see [`Socket::set_tcp_repair_queue`].
+///
+/// # Notes
+///
+/// This type is only available on Android and Linux.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum TcpRepairQueue {
+    /// No queue selected, i.e. `TCP_NO_QUEUE`.
+    None,
+    /// The receive queue, i.e. `TCP_RECV_QUEUE`.
+    Recv,
+    /// The send queue, i.e. `TCP_SEND_QUEUE`.
+    Send,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl TcpRepairQueue {
+    fn from_raw(queue: c_int) -> io::Result<TcpRepairQueue> {
+        match queue {
+            TCP_NO_QUEUE => Ok(TcpRepairQueue::None),
+            TCP_RECV_QUEUE => Ok(TcpRepairQueue::Recv),
+            TCP_SEND_QUEUE => Ok(TcpRepairQueue::Send),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unknown TCP repair queue",
+            )),
+        }
+    }
+
+    fn into_raw(self) -> c_int {
+        match self {
+            TcpRepairQueue::None => TCP_NO_QUEUE,
+            TcpRepairQueue::Recv => TCP_RECV_QUEUE,
+            TcpRepairQueue::Send => TCP_SEND_QUEUE,
+        }
+    }
+}
+
+/// Range from which ephemeral ports are selected, see
+/// [`Socket::set_port_range`].
+///
+/// # Notes
+///
+/// This type is only available on DragonFlyBSD, FreeBSD, iOS, macOS, NetBSD
+/// and OpenBSD.
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum PortRange {
+    /// Use the system's default range, i.e. `IP_PORTRANGE_DEFAULT`.
+    Default,
+    /// Use the high range, often used for firewall friendly port numbers,
+    /// i.e. `IP_PORTRANGE_HIGH`.
+    High,
+    /// Use the low range, i.e. ports below `IPPORT_RESERVED`. Usually
+    /// requires superuser privileges, i.e. `IP_PORTRANGE_LOW`.
+    Low,
+}
+
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+impl PortRange {
+    fn from_raw(range: c_int) -> io::Result<PortRange> {
+        match range {
+            IP_PORTRANGE_DEFAULT => Ok(PortRange::Default),
+            IP_PORTRANGE_HIGH => Ok(PortRange::High),
+            IP_PORTRANGE_LOW => Ok(PortRange::Low),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "unknown port range",
+            )),
+        }
+    }
+
+    fn into_raw(self) -> c_int {
+        match self {
+            PortRange::Default => IP_PORTRANGE_DEFAULT,
+            PortRange::High => IP_PORTRANGE_HIGH,
+            PortRange::Low => IP_PORTRANGE_LOW,
+        }
+    }
+}
+
+/// Packet information of a datagram, read from or sent in an `IP_PKTINFO` or
+/// `IPV6_PKTINFO` control message.
+///
+/// For received datagrams this contains the destination address of the
+/// packet and the index of the interface it was received on, see
+/// [`Socket::set_recv_pktinfo`] and [`Cmsg::pktinfo`]. When sending, it sets
+/// the source address and outgoing interface of the datagram, see
+/// [`CmsgBuffer::push_pktinfo`].
+///
+/// # Notes
+///
+/// This type is only available on Android, iOS, Linux and macOS.
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct PktInfo {
+    addr: IpAddr,
+    interface: u32,
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+impl PktInfo {
+    /// Create new packet information. An `interface` of zero means any
+    /// interface.
+    pub fn new(addr: IpAddr, interface: u32) -> PktInfo {
+        PktInfo { addr, interface }
+    }
+
+    /// Destination address of a received datagram, or source address of a
+    /// datagram to send.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// Index of the interface the datagram was received on, or should be
+    /// sent on.
+    pub fn interface(&self) -> u32 {
+        self.interface
+    }
+}
+
+// `CMSG_*` macros as defined by lwIP.
+#[cfg(target_os = "espidf")]
+const fn cmsg_align(len: usize) -> usize {
+    (len + size_of::<libc::c_long>() - 1) & !(size_of::<libc::c_long>() - 1)
+}
+
+#[cfg(target_os = "espidf")]
+#[allow(non_snake_case)]
+unsafe fn CMSG_SPACE(length: libc::c_uint) -> libc::c_uint {
+    (cmsg_align(size_of::<libc::cmsghdr>()) + cmsg_align(length as usize)) as libc::c_uint
+}
+
+#[cfg(target_os = "espidf")]
+#[allow(non_snake_case)]
+unsafe fn CMSG_LEN(length: libc::c_uint) -> libc::c_uint {
+    (cmsg_align(size_of::<libc::cmsghdr>()) + length as usize) as libc::c_uint
+}
+
+#[cfg(target_os = "espidf")]
+#[allow(non_snake_case)]
+unsafe fn CMSG_DATA(cmsg: *const libc::cmsghdr) -> *mut libc::c_uchar {
+    (cmsg as *mut libc::c_uchar).add(cmsg_align(size_of::<libc::cmsghdr>()))
+}
+
+/// A control message to send, see [`CmsgBuffer::push_message`].
+///
+/// # Notes
+///
+/// This type is not available on ESP-IDF.
+#[cfg(not(target_os = "espidf"))]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ControlMessage<'a> {
+    /// `SCM_RIGHTS`, send file descriptors over a Unix socket.
+    ScmRights(&'a [RawFd]),
+    /// `SCM_CREDENTIALS`, see [`CmsgBuffer::push_credentials`].
+    ///
+    /// This is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    ScmCredentials(&'a UCred),
+    /// `SCM_CREDS`, see [`CmsgBuffer::push_creds`].
+    ///
+    /// This is only available on DragonFlyBSD and FreeBSD.
+    #[cfg(any(target_os = "dragonfly", target_os = "freebsd"))]
+    ScmCreds,
+    /// `IP_PKTINFO` or `IPV6_PKTINFO`, see [`CmsgBuffer::push_pktinfo`].
+    ///
+    /// This is only available on Android, iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    PktInfo(&'a PktInfo),
+    /// `UDP_SEGMENT`, see [`CmsgBuffer::push_udp_segment`].
+    ///
+    /// This is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    UdpSegment(u16),
+    /// `SCM_TXTIME`, see [`CmsgBuffer::push_txtime`].
+    ///
+    /// This is only available on Linux.
+    #[cfg(target_os = "linux")]
+    TxTime(Duration),
+}
+
+/// A received control message, see [`Cmsg::to_message`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ControlMessageOwned {
+    /// `SCM_RIGHTS`, the received file descriptors.
+    ///
+    /// The caller is responsible for closing the file descriptors. They are
+    /// not close-on-exec, unlike the sockets created by this crate, unless
+    /// `MSG_CMSG_CLOEXEC` was passed to [`Socket::recv_msg`] (on platforms
+    /// that support it).
+    ///
+    /// This is not available on ESP-IDF.
+    #[cfg(not(target_os = "espidf"))]
+    ScmRights(Vec<RawFd>),
+    /// `SCM_CREDENTIALS` (or `SCM_CREDS`), see [`Cmsg::credentials`].
+    ///
+    /// This is only available on Android, DragonFlyBSD, FreeBSD and Linux.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "linux"
+    ))]
+    ScmCredentials(UCred),
+    /// `IP_PKTINFO` or `IPV6_PKTINFO`, see [`Cmsg::pktinfo`].
+    ///
+    /// This is only available on Android, iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    PktInfo(PktInfo),
+    /// `IP_ORIGDSTADDR` or `IPV6_ORIGDSTADDR`, see [`Cmsg::orig_dst_addr`].
+    ///
+    /// This is only available on Android, FreeBSD and Linux.
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    OrigDstAddr(SocketAddr),
+    /// `IP_TOS` (or `IP_RECVTOS`), see [`Cmsg::tos`].
+    ///
+    /// This is only available on Android, FreeBSD, iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    Tos(u32),
+    /// `IP_TTL` (or `IP_RECVTTL`), see [`Cmsg::ttl`].
+    ///
+    /// This is only available on Android, DragonFlyBSD, FreeBSD, iOS, Linux
+    /// and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    Ttl(u32),
+    /// `IPV6_FLOWINFO`, see [`Cmsg::flowinfo`].
+    ///
+    /// This is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    FlowInfo(u32),
+    /// `IPV6_HOPLIMIT`, see [`Cmsg::hop_limit`].
+    ///
+    /// This is only available on Android, DragonFlyBSD, FreeBSD, iOS, Linux
+    /// and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    HopLimit(u32),
+    /// `IP_RECVIF`, see [`Cmsg::recv_interface`].
+    ///
+    /// This is only available on DragonFlyBSD, FreeBSD, iOS, macOS, NetBSD
+    /// and OpenBSD.
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    RecvInterface(u32),
+    /// `UDP_GRO`, see [`Cmsg::udp_gro`].
+    ///
+    /// This is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    UdpGro(u16),
+    /// `SCM_TIMESTAMPING`, see [`Cmsg::timestamping`].
+    ///
+    /// This is only available on Linux.
+    #[cfg(target_os = "linux")]
+    Timestamps(Timestamps),
+    /// `IP_RECVERR` or `IPV6_RECVERR`, see [`Cmsg::extended_error`].
+    ///
+    /// This is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    ExtendedError(ExtendedError),
+    /// A control message not known (or not valid) on this platform.
+    Unknown {
+        /// Originating protocol of the control message.
+        level: c_int,
+        /// Protocol specific type of the control message.
+        r#type: c_int,
+        /// Data of the control message.
+        data: Vec<u8>,
+    },
+}
+
+/// Buffer for control messages, also known as ancillary data, used with
+/// [`Socket::send_msg`] and [`Socket::recv_msg`].
+///
+/// When sending, control messages are added using [`CmsgBuffer::push`]. When
+/// receiving, the capacity of the buffer determines how much control data can
+/// be received, use [`CmsgBuffer::space`] to determine the required capacity.
+/// The received control messages can be iterated over using
+/// [`CmsgBuffer::iter`].
+#[derive(Clone)]
+pub struct CmsgBuffer {
+    // Using `u64` to ensure the buffer is properly aligned for `cmsghdr`.
+    buf: Vec<u64>,
+    /// Number of bytes used in `buf`.
+    len: usize,
+}
+
+impl CmsgBuffer {
+    /// Create an empty buffer.
+    pub fn new() -> CmsgBuffer {
+        CmsgBuffer {
+            buf: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Create an empty buffer that can hold at least `capacity` bytes of
+    /// control messages.
+    pub fn with_capacity(capacity: usize) -> CmsgBuffer {
+        let words = capacity.div_ceil(size_of::<u64>());
+        CmsgBuffer {
+            buf: vec![0; words],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of bytes required to hold a single control message
+    /// with `data_len` bytes of data, i.e. `CMSG_SPACE`.
+    pub fn space(data_len: usize) -> usize {
+        unsafe { CMSG_SPACE(data_len as _) as usize }
+    }
+
+    /// Returns the capacity of the buffer, in bytes.
+    pub fn capacity(&self) -> usize {
+        self.buf.len() * size_of::<u64>()
+    }
+
+    /// Returns the number of bytes of control messages in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the buffer contains no control messages.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all control messages from the buffer, keeping its capacity.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Add a control message with the given `level`, `type` and `data`,
+    /// growing the buffer if required.
+    ///
+    /// For example to send file descriptors use `SOL_SOCKET` as `level`,
+    /// `SCM_RIGHTS` as `type` and the file descriptors as `data`.
+    pub fn push(&mut self, level: c_int, r#type: c_int, data: &[u8]) {
+        let space = CmsgBuffer::space(data.len());
+        if self.capacity() < self.len + space {
+            let words = (self.len + space).div_ceil(size_of::<u64>());
+            self.buf.resize(words, 0);
+        }
+        unsafe {
+            let start = (self.buf.as_mut_ptr() as *mut u8).add(self.len);
+            // Zero the padding.
+            std::ptr::write_bytes(start, 0, space);
+            let cmsg = start as *mut libc::cmsghdr;
+            (*cmsg).cmsg_len = CMSG_LEN(data.len() as _) as _;
+            (*cmsg).cmsg_level = level;
+            (*cmsg).cmsg_type = r#type;
+            std::ptr::copy_nonoverlapping(data.as_ptr(), CMSG_DATA(cmsg), data.len());
+        }
+        self.len += space;
+    }
+
+    /// Add a `SCM_RIGHTS` control message containing the file descriptors
+    /// `fds`, only supported on Unix sockets.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on ESP-IDF.
+    #[cfg(not(target_os = "espidf"))]
+    pub fn push_rights(&mut self, fds: &[RawFd]) {
+        let data = fds
+            .iter()
+            .flat_map(|fd| fd.to_ne_bytes())
+            .collect::<Vec<u8>>();
+        self.push(libc::SOL_SOCKET, libc::SCM_RIGHTS, &data)
+    }
+
+    /// Add the control message `msg`.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on ESP-IDF.
+    #[cfg(not(target_os = "espidf"))]
+    pub fn push_message(&mut self, msg: ControlMessage<'_>) {
+        match msg {
+            ControlMessage::ScmRights(fds) => self.push_rights(fds),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::ScmCredentials(cred) => self.push_credentials(cred),
+            #[cfg(any(target_os = "dragonfly", target_os = "freebsd"))]
+            ControlMessage::ScmCreds => self.push_creds(),
+            #[cfg(any(
+                target_os = "android",
+                target_os = "ios",
+                target_os = "linux",
+                target_os = "macos"
+            ))]
+            ControlMessage::PktInfo(pktinfo) => self.push_pktinfo(pktinfo),
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            ControlMessage::UdpSegment(size) => self.push_udp_segment(size),
+            #[cfg(target_os = "linux")]
+            ControlMessage::TxTime(time) => self.push_txtime(time),
+        }
+    }
+
+    /// Add a `SCM_CREDENTIALS` control message containing `cred`.
+    ///
+    /// Unless the process has the `CAP_SYS_ADMIN`, `CAP_SETUID` or
+    /// `CAP_SETGID` capability, the credentials must match those of the
+    /// sending process.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn push_credentials(&mut self, cred: &UCred) {
+        let cred = libc::ucred {
+            pid: cred.pid.unwrap_or(0),
+            uid: cred.uid,
+            gid: cred.gid,
+        };
+        self.push(libc::SOL_SOCKET, libc::SCM_CREDENTIALS, as_bytes(&cred))
+    }
+
+    /// Add a `SCM_CREDS` control message. The kernel fills in the credentials
+    /// of the sending process, which the receiver can read using
+    /// [`Cmsg::credentials`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFlyBSD and FreeBSD. NetBSD
+    /// only supports receiving credentials using the `LOCAL_CREDS` option,
+    /// which is not supported.
+    #[cfg(any(target_os = "dragonfly", target_os = "freebsd"))]
+    pub fn push_creds(&mut self) {
+        // SAFETY: all zeros is a valid representation for `cmsgcred`.
+        let cred: libc::cmsgcred = unsafe { mem::zeroed() };
+        self.push(libc::SOL_SOCKET, libc::SCM_CREDS, as_bytes(&cred))
+    }
+
+    /// Add a `IP_PKTINFO` or `IPV6_PKTINFO` control message, depending on
+    /// the address family of `pktinfo`, to set the source address and
+    /// outgoing interface of the datagram.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn push_pktinfo(&mut self, pktinfo: &PktInfo) {
+        match pktinfo.addr {
+            IpAddr::V4(ref addr) => {
+                let mut info: libc::in_pktinfo = unsafe { mem::zeroed() };
+                info.ipi_ifindex = pktinfo.interface as _;
+                info.ipi_spec_dst = to_in_addr(addr);
+                self.push(libc::IPPROTO_IP, libc::IP_PKTINFO, as_bytes(&info))
+            }
+            IpAddr::V6(ref addr) => {
+                let mut info: libc::in6_pktinfo = unsafe { mem::zeroed() };
+                info.ipi6_ifindex = pktinfo.interface as _;
+                info.ipi6_addr = to_in6_addr(addr);
+                self.push(libc::IPPROTO_IPV6, libc::IPV6_PKTINFO, as_bytes(&info))
+            }
+        }
+    }
+
+    /// Add a `UDP_SEGMENT` control message to set the segment size for a
+    /// single send call, overriding the value set by
+    /// [`Socket::set_udp_segment`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn push_udp_segment(&mut self, size: u16) {
+        self.push(libc::SOL_UDP, libc::UDP_SEGMENT, &size.to_ne_bytes())
+    }
+
+    /// Add a `SCM_TXTIME` control message to set the time at which the
+    /// datagram should be transmitted, relative to the clock configured using
+    /// [`Socket::set_txtime`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn push_txtime(&mut self, time: Duration) {
+        let nanos = min(time.as_nanos(), u64::MAX as u128) as u64;
+        self.push(libc::SOL_SOCKET, libc::SCM_TXTIME, &nanos.to_ne_bytes())
+    }
+
+    /// Returns an iterator over the control messages in the buffer.
+    pub fn iter(&self) -> CmsgIter<'_> {
+        CmsgIter {
+            buf: self.as_bytes(),
+            offset: 0,
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.buf.as_ptr() as *const u8, self.len) }
+    }
+}
+
+/// Returns the bytes of `value`, used to create control messages.
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn as_bytes<T>(value: &T) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(value as *const T as *const u8, size_of::<T>()) }
+}
+
+impl Default for CmsgBuffer {
+    fn default() -> CmsgBuffer {
+        CmsgBuffer::new()
+    }
+}
+
+impl fmt::Debug for CmsgBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Iterator over the control messages in a [`CmsgBuffer`].
+#[derive(Clone, Debug)]
+pub struct CmsgIter<'a> {
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for CmsgIter<'a> {
+    type Item = Cmsg<'a>;
+
+    fn next(&mut self) -> Option<Cmsg<'a>> {
+        let remaining = &self.buf[self.offset..];
+        if remaining.len() < size_of::<libc::cmsghdr>() {
+            return None;
+        }
+        // Safety: `CmsgBuffer` ensures the buffer is aligned for `cmsghdr`.
+        let cmsg = unsafe { &*(remaining.as_ptr() as *const libc::cmsghdr) };
+        let header_len = unsafe { CMSG_LEN(0) } as usize;
+        // `cmsg_len` is not a `usize` on all platforms.
+        let cmsg_len: usize = cmsg.cmsg_len as _;
+        if cmsg_len < header_len || cmsg_len > remaining.len() {
+            return None;
+        }
+        let data_len = cmsg_len - header_len;
+        self.offset = min(self.offset + CmsgBuffer::space(data_len), self.buf.len());
+        Some(Cmsg {
+            level: cmsg.cmsg_level,
+            r#type: cmsg.cmsg_type,
+            data: &remaining[header_len..cmsg_len],
+        })
+    }
+}
+
+/// A single control message, as returned by [`CmsgIter`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Cmsg<'a> {
+    level: c_int,
+    r#type: c_int,
+    data: &'a [u8],
+}
+
+impl<'a> Cmsg<'a> {
+    /// Originating protocol of the control message, e.g. `SOL_SOCKET`.
+    pub fn level(&self) -> c_int {
+        self.level
+    }
+
+    /// Protocol specific type of the control message, e.g. `SCM_RIGHTS`.
+    pub fn r#type(&self) -> c_int {
+        self.r#type
+    }
+
+    /// Data of the control message.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the file descriptors if this is a `SCM_RIGHTS` control
+    /// message.
+    ///
+    /// The caller is responsible for closing the returned file descriptors.
+    /// They are not close-on-exec, unlike the sockets created by this crate,
+    /// unless `MSG_CMSG_CLOEXEC` was passed to [`Socket::recv_msg`] (on
+    /// platforms that support it).
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on ESP-IDF.
+    #[cfg(not(target_os = "espidf"))]
+    pub fn rights(&self) -> Option<Vec<RawFd>> {
+        if self.level != libc::SOL_SOCKET || self.r#type != libc::SCM_RIGHTS {
+            return None;
+        }
+        let fds = self
+            .data
+            .chunks_exact(size_of::<RawFd>())
+            .map(|fd| unsafe { std::ptr::read_unaligned(fd.as_ptr() as *const RawFd) })
+            .collect();
+        Some(fds)
+    }
+
+    /// Converts the control message into a [`ControlMessageOwned`], using
+    /// [`ControlMessageOwned::Unknown`] for control messages not known on this
+    /// platform.
+    pub fn to_message(&self) -> ControlMessageOwned {
+        #[cfg(not(target_os = "espidf"))]
+        if let Some(fds) = self.rights() {
+            return ControlMessageOwned::ScmRights(fds);
+        }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "linux"
+        ))]
+        if let Some(cred) = self.credentials() {
+            return ControlMessageOwned::ScmCredentials(cred);
+        }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos"
+        ))]
+        if let Some(pktinfo) = self.pktinfo() {
+            return ControlMessageOwned::PktInfo(pktinfo);
+        }
+        #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+        if let Some(addr) = self.orig_dst_addr() {
+            return ControlMessageOwned::OrigDstAddr(addr);
+        }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos"
+        ))]
+        if let Some(tos) = self.tos() {
+            return ControlMessageOwned::Tos(tos);
+        }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos"
+        ))]
+        if let Some(ttl) = self.ttl() {
+            return ControlMessageOwned::Ttl(ttl);
+        }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        if let Some(flowinfo) = self.flowinfo() {
+            return ControlMessageOwned::FlowInfo(flowinfo);
+        }
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos"
+        ))]
+        if let Some(hops) = self.hop_limit() {
+            return ControlMessageOwned::HopLimit(hops);
+        }
+        #[cfg(any(
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        if let Some(interface) = self.recv_interface() {
+            return ControlMessageOwned::RecvInterface(interface);
+        }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        if let Some(size) = self.udp_gro() {
+            return ControlMessageOwned::UdpGro(size);
+        }
+        #[cfg(target_os = "linux")]
+        if let Some(timestamps) = self.timestamping() {
+            return ControlMessageOwned::Timestamps(timestamps);
+        }
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        if let Some(err) = self.extended_error() {
+            return ControlMessageOwned::ExtendedError(err);
+        }
+        ControlMessageOwned::Unknown {
+            level: self.level,
+            r#type: self.r#type,
+            data: self.data.to_vec(),
+        }
+    }
+
+    /// Returns the credentials if this is a `SCM_CREDENTIALS` control
+    /// message.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn credentials(&self) -> Option<UCred> {
+        if self.level != libc::SOL_SOCKET
+            || self.r#type != libc::SCM_CREDENTIALS
+            || self.data.len() < size_of::<libc::ucred>()
+        {
+            return None;
+        }
+        let cred = unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const libc::ucred) };
+        Some(UCred {
+            pid: Some(cred.pid),
+            uid: cred.uid,
+            gid: cred.gid,
+        })
+    }
+
+    /// Returns the credentials if this is a `SCM_CREDS` control message, see
+    /// [`CmsgBuffer::push_creds`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFlyBSD and FreeBSD.
+    #[cfg(any(target_os = "dragonfly", target_os = "freebsd"))]
+    pub fn credentials(&self) -> Option<UCred> {
+        if self.level != libc::SOL_SOCKET
+            || self.r#type != libc::SCM_CREDS
+            || self.data.len() < size_of::<libc::cmsgcred>()
+        {
+            return None;
+        }
+        let cred = unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const libc::cmsgcred) };
+        // The first group is the effective group id.
+        let gid = if cred.cmcred_ngroups > 0 {
+            cred.cmcred_groups[0]
+        } else {
+            cred.cmcred_gid
+        };
+        Some(UCred {
+            pid: Some(cred.cmcred_pid),
+            uid: cred.cmcred_euid,
+            gid,
+        })
+    }
+
+    /// Returns the packet information if this is a `IP_PKTINFO` or
+    /// `IPV6_PKTINFO` control message.
+    ///
+    /// See [`Socket::set_recv_pktinfo`] and [`Socket::set_recv_pktinfo_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn pktinfo(&self) -> Option<PktInfo> {
+        match (self.level, self.r#type) {
+            (libc::IPPROTO_IP, libc::IP_PKTINFO)
+                if self.data.len() >= size_of::<libc::in_pktinfo>() =>
+            {
+                let info: libc::in_pktinfo =
+                    unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const _) };
+                Some(PktInfo {
+                    addr: IpAddr::V4(from_in_addr(info.ipi_addr)),
+                    interface: info.ipi_ifindex as u32,
+                })
+            }
+            (libc::IPPROTO_IPV6, libc::IPV6_PKTINFO)
+                if self.data.len() >= size_of::<libc::in6_pktinfo>() =>
+            {
+                let info: libc::in6_pktinfo =
+                    unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const _) };
+                Some(PktInfo {
+                    addr: IpAddr::V6(Ipv6Addr::from(info.ipi6_addr.s6_addr)),
+                    interface: info.ipi6_ifindex as u32,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the original destination address of the received datagram if
+    /// this is a `IP_ORIGDSTADDR` or `IPV6_ORIGDSTADDR` control message.
+    ///
+    /// See [`Socket::set_recv_orig_dst_addr`] and
+    /// [`Socket::set_recv_orig_dst_addr_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD and Linux.
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    pub fn orig_dst_addr(&self) -> Option<SocketAddr> {
+        match (self.level, self.r#type) {
+            (libc::IPPROTO_IP, libc::IP_ORIGDSTADDR)
+                if self.data.len() >= size_of::<libc::sockaddr_in>() =>
+            {
+                let addr: libc::sockaddr_in =
+                    unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const _) };
+                let ip = from_in_addr(addr.sin_addr);
+                let port = u16::from_be(addr.sin_port);
+                Some(SocketAddr::V4(SocketAddrV4::new(ip, port)))
+            }
+            (libc::IPPROTO_IPV6, libc::IPV6_ORIGDSTADDR)
+                if self.data.len() >= size_of::<libc::sockaddr_in6>() =>
+            {
+                let addr: libc::sockaddr_in6 =
+                    unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const _) };
+                Some(SocketAddr::V6(SocketAddrV6::new(
+                    Ipv6Addr::from(addr.sin6_addr.s6_addr),
+                    u16::from_be(addr.sin6_port),
+                    addr.sin6_flowinfo,
+                    addr.sin6_scope_id,
+                )))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the type-of-service field of the received packet if this is a
+    /// `IP_TOS` (or `IP_RECVTOS`) control message.
+    ///
+    /// See [`Socket::set_recv_tos`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn tos(&self) -> Option<u32> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        const TYPE: c_int = libc::IP_TOS;
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        const TYPE: c_int = libc::IP_RECVTOS;
+        match (self.level, self.r#type, self.data) {
+            (libc::IPPROTO_IP, TYPE, [tos, ..]) => Some(*tos as u32),
+            _ => None,
+        }
+    }
+
+    /// Returns the time-to-live field of the received packet if this is a
+    /// `IP_TTL` (or `IP_RECVTTL`) control message.
+    ///
+    /// See [`Socket::set_recv_ttl`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn ttl(&self) -> Option<u32> {
+        match (self.level, self.r#type) {
+            // Linux uses an `int`.
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            (libc::IPPROTO_IP, libc::IP_TTL) if self.data.len() >= size_of::<c_int>() => {
+                let ttl = unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const c_int) };
+                Some(ttl as u32)
+            }
+            // The BSDs use a single byte.
+            #[cfg(not(any(target_os = "android", target_os = "linux")))]
+            (libc::IPPROTO_IP, libc::IP_RECVTTL) if !self.data.is_empty() => {
+                Some(self.data[0] as u32)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the flow information (traffic class and flow label) of the
+    /// received packet if this is a `IPV6_FLOWINFO` control message.
+    ///
+    /// Like [`SocketAddrV6::flowinfo`], the value is in network byte order.
+    ///
+    /// See [`Socket::set_recv_flowinfo`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`SocketAddrV6::flowinfo`]: std::net::SocketAddrV6::flowinfo
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn flowinfo(&self) -> Option<u32> {
+        match (self.level, self.r#type) {
+            (libc::IPPROTO_IPV6, libc::IPV6_FLOWINFO) if self.data.len() >= size_of::<u32>() => {
+                Some(unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const u32) })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the hop limit field of the received packet if this is a
+    /// `IPV6_HOPLIMIT` control message.
+    ///
+    /// See [`Socket::set_recv_hoplimit_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn hop_limit(&self) -> Option<u32> {
+        match (self.level, self.r#type) {
+            (libc::IPPROTO_IPV6, libc::IPV6_HOPLIMIT) if self.data.len() >= size_of::<c_int>() => {
+                let hops = unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const c_int) };
+                Some(hops as u32)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the index of the interface the datagram was received on if
+    /// this is a `IP_RECVIF` control message.
+    ///
+    /// See [`Socket::set_recv_interface`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFlyBSD, FreeBSD, iOS, macOS,
+    /// NetBSD and OpenBSD.
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn recv_interface(&self) -> Option<u32> {
+        if self.level != libc::IPPROTO_IP || self.r#type != libc::IP_RECVIF {
+            return None;
+        }
+        // The kernel may truncate the `sockaddr_dl`, so we can't read the
+        // entire structure.
+        let mut addr: libc::sockaddr_dl = unsafe { mem::zeroed() };
+        let offset = mem::offset_of!(libc::sockaddr_dl, sdl_index);
+        if self.data.len() < offset + size_of::<u16>() {
+            return None;
+        }
+        let len = min(self.data.len(), size_of::<libc::sockaddr_dl>());
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                self.data.as_ptr(),
+                (&mut addr as *mut libc::sockaddr_dl).cast::<u8>(),
+                len,
+            )
+        };
+        Some(u32::from(addr.sdl_index))
+    }
+
+    /// Returns the size of the coalesced datagrams if this is a `UDP_GRO`
+    /// control message.
+    ///
+    /// See [`Socket::set_udp_gro`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn udp_gro(&self) -> Option<u16> {
+        match (self.level, self.r#type) {
+            (libc::SOL_UDP, libc::UDP_GRO) if self.data.len() >= size_of::<c_int>() => {
+                let size = unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const c_int) };
+                Some(size as u16)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the timestamps if this is a `SCM_TIMESTAMPING` control
+    /// message.
+    ///
+    /// See [`Socket::set_timestamping`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn timestamping(&self) -> Option<Timestamps> {
+        if self.level != libc::SOL_SOCKET
+            || self.r#type != libc::SCM_TIMESTAMPING
+            || self.data.len() < size_of::<scm_timestamping>()
+        {
+            return None;
+        }
+        let tss: scm_timestamping =
+            unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const _) };
+        Some(Timestamps {
+            software: from_timespec(tss.ts[0]),
+            // `ts[1]` is deprecated and always zero.
+            hardware: from_timespec(tss.ts[2]),
+        })
+    }
+
+    /// Returns the extended error if this is a `IP_RECVERR` or
+    /// `IPV6_RECVERR` control message, as read from the socket's error queue.
+    ///
+    /// See [`Socket::set_recv_err`] and [`Socket::recv_error`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn extended_error(&self) -> Option<ExtendedError> {
+        match (self.level, self.r#type) {
+            (libc::SOL_IP, libc::IP_RECVERR) | (libc::SOL_IPV6, libc::IPV6_RECVERR)
+                if self.data.len() >= size_of::<libc::sock_extended_err>() => {}
+            _ => return None,
+        }
+        // Safety: checked the size above, the data is not guaranteed to be
+        // aligned.
+        let err: libc::sock_extended_err =
+            unsafe { std::ptr::read_unaligned(self.data.as_ptr() as *const _) };
+        // The offending address directly follows the error, see
+        // `SO_EE_OFFENDER`.
+        let offender = &self.data[size_of::<libc::sock_extended_err>()..];
+        let family = if offender.len() >= size_of::<libc::sa_family_t>() {
+            unsafe { std::ptr::read_unaligned(offender.as_ptr() as *const libc::sa_family_t) }
+        } else {
+            libc::AF_UNSPEC as libc::sa_family_t
+        };
+        let offender = match c_int::from(family) {
+            libc::AF_INET if offender.len() >= size_of::<libc::sockaddr_in>() => {
+                let addr: libc::sockaddr_in =
+                    unsafe { std::ptr::read_unaligned(offender.as_ptr() as *const _) };
+                let ip = from_in_addr(addr.sin_addr);
+                let port = u16::from_be(addr.sin_port);
+                Some(SocketAddr::V4(SocketAddrV4::new(ip, port)))
+            }
+            libc::AF_INET6 if offender.len() >= size_of::<libc::sockaddr_in6>() => {
+                let addr: libc::sockaddr_in6 =
+                    unsafe { std::ptr::read_unaligned(offender.as_ptr() as *const _) };
+                Some(SocketAddr::V6(SocketAddrV6::new(
+                    Ipv6Addr::from(addr.sin6_addr.s6_addr),
+                    u16::from_be(addr.sin6_port),
+                    addr.sin6_flowinfo,
+                    addr.sin6_scope_id,
+                )))
+            }
+            _ => None,
+        };
+        Some(ExtendedError {
+            errno: err.ee_errno,
+            origin: err.ee_origin,
+            r#type: err.ee_type,
+            code: err.ee_code,
+            info: err.ee_info,
+            data: err.ee_data,
+            offender,
+        })
+    }
+}
+
+/// Helper macro to execute a system call that returns an `io::Result`.
+macro_rules! syscall {
+    ($fn: ident ( $($arg: expr),* $(,)* ) ) => {{
+        let res = unsafe { libc::$fn($($arg, )*) };
+        if res == -1 {
+            Err(std::io::Error::last_os_error())
+        } else {
+            Ok(res)
+        }
+    }};
+}
+
+pub(crate) fn socket(domain: c_int, type_: c_int, protocol: c_int) -> io::Result<Socket> {
+    syscall!(socket(domain, type_, protocol)).map(|fd| Socket { inner: fd })
+}
+
+pub(crate) fn connect(
+    sockfd: RawSocket,
+    addr: *const libc::sockaddr_storage,
+    addrlen: libc::socklen_t,
+) -> io::Result<()> {
+    // Most OSes don't actually use `sockaddr_storage` in the `connect(2)` call,
+    // but `sockaddr_storage` can be converted safely into the correct type.
+    syscall!(connect(sockfd, addr as *const _, addrlen)).map(|_| ())
+}
+
+pub(crate) fn bind(
+    sockfd: RawSocket,
+    addr: *const libc::sockaddr_storage,
+    addrlen: libc::socklen_t,
+) -> io::Result<()> {
+    // Most OSes don't actually use `sockaddr_storage` in the `bind(2)` call,
+    // but `sockaddr_storage` can be converted safely into the correct type.
+    syscall!(bind(sockfd, addr as *const _, addrlen)).map(|_| ())
+}
+
+pub(crate) fn listen(sockfd: RawSocket, backlog: c_int) -> io::Result<()> {
+    syscall!(listen(sockfd, backlog)).map(|_| ())
+}
+
+pub(crate) fn accept(sockfd: RawSocket) -> io::Result<(Socket, SockAddr)> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(accept(sockfd, addr.as_mut_ptr() as *mut _, &mut addrlen)).map(|stream_fd| {
+        // This is safe because `accept(2)` filled in the address for us.
+        let addr = unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) };
+        (Socket { inner: stream_fd }, addr)
+    })
+}
+
+pub(crate) fn try_clone(fd: RawSocket) -> io::Result<Socket> {
+    match syscall!(fcntl(fd, libc::F_DUPFD_CLOEXEC, 0)) {
+        Ok(fd) => Ok(Socket { inner: fd }),
+        // Old kernels don't support `F_DUPFD_CLOEXEC`, fall back to `dup(2)`
+        // and set `FD_CLOEXEC` separately.
+        Err(ref err) if err.raw_os_error() == Some(libc::EINVAL) => {
+            let socket = syscall!(dup(fd)).map(|fd| Socket { inner: fd })?;
+            fcntl(socket.inner, libc::F_SETFD, libc::FD_CLOEXEC)?;
+            Ok(socket)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub(crate) fn getsockname(sockfd: RawSocket) -> io::Result<SockAddr> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(getsockname(
+        sockfd,
+        addr.as_mut_ptr() as *mut _,
+        &mut addrlen
+    ))
+    .map(|_| {
+        // This is safe because `getsockname(2)` filled in the address for us.
+        unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) }
+    })
+}
+
+/// Returns the domain of the socket, or `None` if it can't be determined.
+#[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+pub(crate) fn socket_domain(sockfd: RawSocket) -> Option<Domain> {
+    // Unlike the address family returned by `getsockname(2)`, `SO_DOMAIN` is
+    // also set for sockets that aren't bound yet.
+    getsockopt::<c_int>(sockfd, libc::SOL_SOCKET, libc::SO_DOMAIN)
+        .ok()
+        .map(Domain::from)
+}
+
+/// Returns the domain of the socket, or `None` if it can't be determined.
+#[cfg(not(any(target_os = "android", target_os = "freebsd", target_os = "linux")))]
+pub(crate) fn socket_domain(sockfd: RawSocket) -> Option<Domain> {
+    getsockname(sockfd)
+        .ok()
+        .filter(|addr| addr.family() != libc::AF_UNSPEC as libc::sa_family_t)
+        .map(|addr| Domain::from(addr.family() as c_int))
+}
+
+pub(crate) fn getpeername(sockfd: RawSocket) -> io::Result<SockAddr> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(getpeername(
+        sockfd,
+        addr.as_mut_ptr() as *mut _,
+        &mut addrlen
+    ))
+    .map(|_| {
+        // This is safe because `getpeername(2)` filled in the address for us.
+        unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) }
+    })
+}
+
+pub(crate) fn shutdown(sockfd: RawSocket, how: Shutdown) -> io::Result<()> {
+    let how = match how {
+        Shutdown::Write => libc::SHUT_WR,
+        Shutdown::Read => libc::SHUT_RD,
+        Shutdown::Both => libc::SHUT_RDWR,
+    };
+    syscall!(shutdown(sockfd, how)).map(|_| ())
+}
+
+pub(crate) fn disconnect(sockfd: RawSocket) -> io::Result<()> {
+    let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    addr.ss_family = libc::AF_UNSPEC as libc::sa_family_t;
+    match connect(
+        sockfd,
+        &addr,
+        size_of::<libc::sockaddr>() as libc::socklen_t,
+    ) {
+        // The BSDs dissolve the association, but still return an error as
+        // `AF_UNSPEC` is not a supported address family.
+        #[cfg(not(any(target_os = "android", target_os = "linux")))]
+        Err(ref err) if err.raw_os_error() == Some(libc::EAFNOSUPPORT) => Ok(()),
+        res => res,
+    }
+}
+
+pub(crate) fn poll(
+    sockfd: RawSocket,
+    events: c_short,
+    timeout: Option<Duration>,
+) -> io::Result<c_short> {
+    let start = Instant::now();
+    let mut pollfd = libc::pollfd {
+        fd: sockfd,
+        events,
+        revents: 0,
+    };
+
+    loop {
+        let timeout = match timeout {
+            Some(timeout) => match timeout.saturating_sub(start.elapsed()) {
+                remaining if remaining.is_zero() => 0,
+                // Round up to at least 1 millisecond, `poll(2)` would
+                // otherwise return immediately.
+                remaining => remaining.as_millis().clamp(1, c_int::MAX as u128) as c_int,
+            },
+            None => -1,
+        };
+
+        match syscall!(poll(&mut pollfd, 1, timeout)) {
+            Ok(0) => return Err(io::ErrorKind::TimedOut.into()),
+            Ok(_) => return Ok(pollfd.revents),
+            // Got interrupted, try again.
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+pub(crate) fn poll_connect(socket: &Socket, timeout: Duration) -> io::Result<()> {
+    if timeout == Duration::from_secs(0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot set a 0 duration timeout",
+        ));
+    }
+
+    let start = Instant::now();
+    let mut pollfd = libc::pollfd {
+        fd: socket.inner,
+        events: libc::POLLIN | libc::POLLOUT,
+        revents: 0,
+    };
+
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+
+        // Round up to at least 1 millisecond, `poll(2)` would otherwise
+        // return immediately.
+        let timeout = (timeout - elapsed).as_millis();
+        let timeout = timeout.clamp(1, c_int::MAX as u128) as c_int;
+
+        match syscall!(poll(&mut pollfd, 1, timeout)) {
+            Ok(0) => return Err(io::ErrorKind::TimedOut.into()),
+            Ok(_) => {
+                // Error or hang up indicates an error (or failure to connect).
+                if (pollfd.revents & libc::POLLHUP) != 0 || (pollfd.revents & libc::POLLERR) != 0 {
+                    match socket.take_error() {
+                        Ok(Some(err)) | Err(err) => return Err(err),
+                        Ok(None) => return Err(io::Error::other("no error set after POLLHUP")),
+                    }
+                }
+                return Ok(());
+            }
+            // Got interrupted, try again.
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+pub(crate) fn set_nonblocking(sockfd: RawSocket, nonblocking: bool) -> io::Result<()> {
+    let flags = fcntl(sockfd, libc::F_GETFL, ())?;
+    let new = if nonblocking {
+        flags | libc::O_NONBLOCK
+    } else {
+        flags & !libc::O_NONBLOCK
+    };
+    if new != flags {
+        fcntl(sockfd, libc::F_SETFL, new)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn if_nametoindex(name: &CStr) -> io::Result<u32> {
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => Err(io::Error::last_os_error()),
+        index => Ok(index),
+    }
+}
+
+pub(crate) fn if_indextoname(index: u32) -> io::Result<String> {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    let name = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+    if name.is_null() {
+        Err(io::Error::last_os_error())
+    } else {
+        // Safety: `if_indextoname(3)` wrote a null terminated string to `buf`.
+        let name = unsafe { CStr::from_ptr(name) };
+        Ok(name.to_string_lossy().into_owned())
+    }
+}
+
+pub(crate) fn bytes_available(sockfd: RawSocket) -> io::Result<usize> {
+    let mut available: c_int = 0;
+    // SAFETY: `FIONREAD` expects a pointer to a `c_int`.
+    unsafe { ioctl(sockfd, FIONREAD as _, &mut available as *mut c_int) }
+        .map(|_| available as usize)
+}
+
+pub(crate) fn send(sockfd: RawSocket, buf: &[u8], flags: c_int) -> io::Result<usize> {
+    syscall!(send(
+        sockfd,
+        buf.as_ptr() as *const _,
+        min(buf.len(), MAX_BUF_LEN),
+        flags,
+    ))
+    .map(|n| n as usize)
+}
+
+pub(crate) fn send_to(
+    sockfd: RawSocket,
+    buf: &[u8],
+    addr: &SockAddr,
+    flags: c_int,
+) -> io::Result<usize> {
+    syscall!(sendto(
+        sockfd,
+        buf.as_ptr() as *const _,
+        min(buf.len(), MAX_BUF_LEN),
+        flags,
+        addr.as_ptr() as *const _,
+        addr.len(),
+    ))
+    .map(|n| n as usize)
+}
+
+pub(crate) fn recv(sockfd: RawSocket, buf: &mut [u8], flags: c_int) -> io::Result<usize> {
+    syscall!(recv(
+        sockfd,
+        buf.as_mut_ptr() as *mut _,
+        min(buf.len(), MAX_BUF_LEN),
+        flags,
+    ))
+    .map(|n| n as usize)
+}
+
+pub(crate) fn recv_from(
+    sockfd: RawSocket,
+    buf: &mut [u8],
+    flags: c_int,
+) -> io::Result<(usize, SockAddr)> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(recvfrom(
+        sockfd,
+        buf.as_mut_ptr() as *mut _,
+        min(buf.len(), MAX_BUF_LEN),
+        flags,
+        addr.as_mut_ptr() as *mut _,
+        &mut addrlen
+    ))
+    .map(|n| {
+        // This is safe because `recvfrom(2)` filled in the address for us.
+        let addr = unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) };
+        (n as usize, addr)
+    })
+}
+
+pub(crate) fn peek_sender(sockfd: RawSocket) -> io::Result<SockAddr> {
+    recv_from(sockfd, &mut [], libc::MSG_PEEK).map(|(_, addr)| addr)
+}
+
+pub(crate) fn recv_vectored(
+    sockfd: RawSocket,
+    bufs: &mut [IoSliceMut<'_>],
+    flags: c_int,
+) -> io::Result<(usize, RecvFlags)> {
+    recvmsg(sockfd, std::ptr::null_mut(), bufs, None, flags)
+        .map(|(n, _, recv_flags)| (n, recv_flags))
+}
+
+pub(crate) fn recv_from_vectored(
+    sockfd: RawSocket,
+    bufs: &mut [IoSliceMut<'_>],
+    flags: c_int,
+) -> io::Result<(usize, RecvFlags, SockAddr)> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    recvmsg(sockfd, addr.as_mut_ptr(), bufs, None, flags).map(|(n, addrlen, recv_flags)| {
+        // This is safe because `recvmsg(2)` filled in the address for us.
+        let addr = unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) };
+        (n, recv_flags, addr)
+    })
+}
+
+pub(crate) fn recv_msg(
+    sockfd: RawSocket,
+    bufs: &mut [IoSliceMut<'_>],
+    control: &mut CmsgBuffer,
+    flags: c_int,
+) -> io::Result<(usize, RecvFlags, SockAddr)> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+    recvmsg(sockfd, addr.as_mut_ptr(), bufs, Some(control), flags).map(
+        |(n, addrlen, recv_flags)| {
+            // This is safe because `recvmsg(2)` filled in the address for us.
+            let addr = unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) };
+            (n, recv_flags, addr)
+        },
+    )
+}
+
+/// Returns the (bytes received, sending address len, `RecvFlags`).
+fn recvmsg(
+    sockfd: RawSocket,
+    msg_name: *mut libc::sockaddr_storage,
+    bufs: &mut [IoSliceMut<'_>],
+    mut control: Option<&mut CmsgBuffer>,
+    flags: c_int,
+) -> io::Result<(usize, libc::socklen_t, RecvFlags)> {
+    let msg_namelen = if msg_name.is_null() {
+        0
+    } else {
+        size_of::<libc::sockaddr_storage>() as libc::socklen_t
+    };
+    // Some platforms have private padding fields in `msghdr`, so we zero it
+    // rather than using a struct literal.
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_name = msg_name as *mut _;
+    msg.msg_namelen = msg_namelen;
+    // `IoSliceMut` is guaranteed to be ABI compatible with `iovec`.
+    msg.msg_iov = bufs.as_mut_ptr() as *mut libc::iovec;
+    msg.msg_iovlen = min(bufs.len(), c_int::MAX as usize) as _;
+    if let Some(control) = control.as_mut() {
+        msg.msg_control = control.buf.as_mut_ptr() as *mut _;
+        msg.msg_controllen = control.capacity() as _;
+    }
+    let n = syscall!(recvmsg(sockfd, &mut msg, flags))?;
+    if let Some(control) = control {
+        control.len = msg.msg_controllen as usize;
+    }
+    Ok((n as usize, msg.msg_namelen, RecvFlags(msg.msg_flags)))
+}
+
+pub(crate) fn send_msg(
+    sockfd: RawSocket,
+    bufs: &[IoSlice<'_>],
+    addr: Option<&SockAddr>,
+    control: &CmsgBuffer,
+    flags: c_int,
+) -> io::Result<usize> {
+    // Some platforms have private padding fields in `msghdr`, so we zero it
+    // rather than using a struct literal.
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    if let Some(addr) = addr {
+        msg.msg_name = addr.as_ptr() as *mut _;
+        msg.msg_namelen = addr.len();
+    }
+    // `IoSlice` is guaranteed to be ABI compatible with `iovec`.
+    msg.msg_iov = bufs.as_ptr() as *mut libc::iovec;
+    msg.msg_iovlen = min(bufs.len(), c_int::MAX as usize) as _;
+    if !control.is_empty() {
+        msg.msg_control = control.buf.as_ptr() as *mut _;
+        msg.msg_controllen = control.len() as _;
+    }
+    syscall!(sendmsg(sockfd, &msg, flags)).map(|n| n as usize)
+}
+
+pub(crate) fn setsockopt<T>(
+    sockfd: RawSocket,
+    level: c_int,
+    optname: c_int,
+    opt: &T,
+) -> io::Result<()> {
+    syscall!(setsockopt(
+        sockfd,
+        level,
+        optname,
+        opt as *const _ as *const _,
+        size_of::<T>() as libc::socklen_t,
+    ))
+    .map(|_| ())
+}
+
+pub(crate) fn getsockopt<T>(sockfd: RawSocket, level: c_int, optname: c_int) -> io::Result<T> {
+    let mut optval: MaybeUninit<T> = MaybeUninit::uninit();
+    let mut optlen = size_of::<T>() as libc::socklen_t;
+    syscall!(getsockopt(
+        sockfd,
+        level,
+        optname,
+        optval.as_mut_ptr() as *mut _,
+        &mut optlen
+    ))
+    .map(|_| unsafe {
+        // Safe because `getsockopt(2)` initialised the value for us.
+        debug_assert_eq!(optlen as usize, size_of::<T>());
+        optval.assume_init()
+    })
+}
+
+/// Get a socket option that returns an address, e.g. `SO_ORIGINAL_DST`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn getsockopt_addr(sockfd: RawSocket, level: c_int, optname: c_int) -> io::Result<SockAddr> {
+    let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::zeroed();
+    let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    syscall!(getsockopt(
+        sockfd,
+        level,
+        optname,
+        addr.as_mut_ptr() as *mut _,
+        &mut addrlen
+    ))
+    .map(|_| {
+        // This is safe because `getsockopt(2)` filled in the address for us.
+        unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) }
+    })
+}
+
+/// Get a socket option with a variable length value, e.g. `SO_PEERSEC`.
+///
+/// If the buffer is too small the kernel returns an error (`ERANGE` or
+/// `EINVAL`, depending on the option) and sets the required length, in which
+/// case we retry with a larger buffer.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn getsockopt_bytes(
+    sockfd: RawSocket,
+    level: c_int,
+    optname: c_int,
+    mut capacity: usize,
+) -> io::Result<Vec<u8>> {
+    loop {
+        let mut buf: Vec<u8> = Vec::with_capacity(capacity);
+        let mut len = capacity as libc::socklen_t;
+        match syscall!(getsockopt(
+            sockfd,
+            level,
+            optname,
+            buf.as_mut_ptr().cast(),
+            &mut len,
+        )) {
+            Ok(_) => {
+                // Safety: `len` bytes are initialised by the OS.
+                unsafe { buf.set_len(min(len as usize, capacity)) };
+                return Ok(buf);
+            }
+            Err(ref err)
+                if matches!(err.raw_os_error(), Some(libc::ERANGE | libc::EINVAL))
+                    && len as usize > capacity =>
+            {
+                capacity = len as usize;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+pub(crate) fn timeout_opt(
+    sockfd: RawSocket,
+    level: c_int,
+    optname: c_int,
+) -> io::Result<Option<Duration>> {
+    getsockopt::<libc::timeval>(sockfd, level, optname).map(|timeout| {
+        if timeout.tv_sec == 0 && timeout.tv_usec == 0 {
+            None
+        } else {
+            let secs = timeout.tv_sec as u64;
+            let nanos = (timeout.tv_usec as u32) * 1000;
+            Some(Duration::new(secs, nanos))
+        }
+    })
+}
+
+pub(crate) fn set_timeout_opt(
+    sockfd: RawSocket,
+    level: c_int,
+    optname: c_int,
+    duration: Option<Duration>,
+) -> io::Result<()> {
+    let timeout = match duration {
+        Some(duration) if duration.as_secs() == 0 && duration.subsec_nanos() == 0 => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot set a 0 duration timeout",
+            ));
+        }
+        Some(duration) => {
+            let mut timeout = libc::timeval {
+                tv_sec: min(duration.as_secs(), libc::time_t::MAX as u64) as libc::time_t,
+                tv_usec: duration.subsec_micros() as libc::suseconds_t,
+            };
+            // Don't round sub-microsecond timeouts down to no timeout.
+            if timeout.tv_sec == 0 && timeout.tv_usec == 0 {
+                timeout.tv_usec = 1;
+            }
+            timeout
+        }
+        None => libc::timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        },
+    };
+    setsockopt(sockfd, level, optname, &timeout)
+}
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+use libc::TCP_KEEPALIVE as KEEPALIVE_TIME;
+#[cfg(not(any(target_os = "ios", target_os = "macos")))]
+use libc::TCP_KEEPIDLE as KEEPALIVE_TIME;
+
+pub(crate) fn set_tcp_keepalive(sockfd: RawSocket, keepalive: &TcpKeepalive) -> io::Result<()> {
+    // OpenBSD doesn't provide a way to set the keepalive time per socket.
+    #[cfg(not(target_os = "openbsd"))]
+    {
+        if let Some(time) = keepalive.time {
+            let secs = into_secs(time);
+            setsockopt(sockfd, libc::IPPROTO_TCP, KEEPALIVE_TIME, &secs)?;
+        }
+    }
+
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "illumos",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "redox",
+        target_os = "solaris"
+    ))]
+    {
+        if let Some(interval) = keepalive.interval {
+            let secs = into_secs(interval);
+            setsockopt(sockfd, libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, &secs)?;
+        }
+
+        if let Some(retries) = keepalive.retries {
+            let retries = min(retries, c_int::MAX as u32) as c_int;
+            setsockopt(sockfd, libc::IPPROTO_TCP, libc::TCP_KEEPCNT, &retries)?;
+        }
+    }
+
+    #[cfg(target_os = "openbsd")]
+    let _ = keepalive;
+    Ok(())
+}
+
+#[cfg(not(target_os = "openbsd"))]
+fn into_secs(duration: Duration) -> c_int {
+    min(duration.as_secs(), c_int::MAX as u64) as c_int
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn dontfrag_v4(sockfd: RawSocket) -> io::Result<bool> {
+    getsockopt::<c_int>(sockfd, libc::IPPROTO_IP, libc::IP_MTU_DISCOVER)
+        .map(|mode| mode == libc::IP_PMTUDISC_DO)
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+pub(crate) fn dontfrag_v4(sockfd: RawSocket) -> io::Result<bool> {
+    getsockopt::<c_int>(sockfd, libc::IPPROTO_IP, libc::IP_DONTFRAG).map(|dontfrag| dontfrag != 0)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+pub(crate) fn set_dontfrag_v4(sockfd: RawSocket, dontfrag: bool) -> io::Result<()> {
+    // Linux doesn't have `IP_DONTFRAG`, but the `IP_PMTUDISC_DO` path MTU
+    // discovery mode sets the DF flag on all packets.
+    let mode = if dontfrag {
+        libc::IP_PMTUDISC_DO
+    } else {
+        libc::IP_PMTUDISC_DONT
+    };
+    setsockopt(sockfd, libc::IPPROTO_IP, libc::IP_MTU_DISCOVER, &mode)
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+pub(crate) fn set_dontfrag_v4(sockfd: RawSocket, dontfrag: bool) -> io::Result<()> {
+    setsockopt(
+        sockfd,
+        libc::IPPROTO_IP,
+        libc::IP_DONTFRAG,
+        &(dontfrag as c_int),
+    )
+}
+
+pub(crate) fn to_in_addr(addr: &Ipv4Addr) -> libc::in_addr {
+    // `s_addr` is stored as BE on all machines, and the array is in BE order.
+    // So the native endian conversion method is used so that it's never
+    // swapped.
+    libc::in_addr {
+        s_addr: u32::from_ne_bytes(addr.octets()),
+    }
+}
+
+pub(crate) fn from_in_addr(addr: in_addr) -> Ipv4Addr {
+    Ipv4Addr::from(addr.s_addr.to_ne_bytes())
+}
+
+pub(crate) fn to_in6_addr(addr: &Ipv6Addr) -> libc::in6_addr {
+    let mut ret: libc::in6_addr = unsafe { mem::zeroed() };
+    ret.s6_addr = addr.octets();
+    ret
+}
+
+pub(crate) fn fcntl<T>(sockfd: RawSocket, cmd: c_int, arg: T) -> io::Result<c_int> {
+    syscall!(fcntl(sockfd, cmd, arg))
+}
+
+/// # Safety
+///
+/// See [`Socket::ioctl`].
+pub(crate) unsafe fn ioctl<T>(
+    sockfd: RawSocket,
+    request: libc::c_ulong,
+    arg: T,
+) -> io::Result<c_int> {
+    // Not all platforms use `c_ulong` for `request`, e.g. Android and musl
+    // use `c_int`.
+    syscall!(ioctl(sockfd, request as _, arg))
+}
+
+/// Unix only API.
+impl Socket {
+    /// Creates a pair of sockets which are connected to each other.
+    ///
+    /// This function corresponds to `socketpair(2)`.
+    ///
+    /// This function sets the same flags as is done for [`Socket::new`],
+    /// [`Socket::pair_raw`] can be used if you don't want to set those flags.
+    pub fn pair(
+        domain: Domain,
+        type_: Type,
+        protocol: Option<Protocol>,
+    ) -> io::Result<(Socket, Socket)> {
+        let type_ = set_common_type(type_);
+        let (a, b) = Socket::pair_raw(domain, type_, protocol)?;
+        Ok((set_common_flags(a)?, set_common_flags(b)?))
+    }
+
+    /// Creates a pair of sockets which are connected to each other.
+    ///
+    /// This function directly corresponds to `socketpair(2)` and doesn't set
+    /// any flags on the returned sockets, unlike [`pair`].
+    ///
+    /// [`pair`]: Socket::pair
+    pub fn pair_raw(
+        domain: Domain,
+        type_: Type,
+        protocol: Option<Protocol>,
+    ) -> io::Result<(Socket, Socket)> {
+        let mut fds = [0, 0];
+        let protocol = protocol.map(|p| p.0).unwrap_or(0);
+        syscall!(socketpair(domain.0, type_.0, protocol, fds.as_mut_ptr()))
+            .map(|_| (Socket { inner: fds[0] }, Socket { inner: fds[1] }))
+    }
+
+    /// Returns `true` if this socket is set to non-blocking mode, `false`
+    /// otherwise.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Unix, Windows doesn't provide a way
+    /// to retrieve the non-blocking mode of a socket.
+    ///
+    /// On Unix this corresponds to calling `fcntl` returning the value of
+    /// `O_NONBLOCK`.
+    pub fn nonblocking(&self) -> io::Result<bool> {
+        self.fcntl(libc::F_GETFL, ())
+            .map(|flags| flags & libc::O_NONBLOCK != 0)
+    }
+
+    /// Returns `true` if the close-on-exec flag is set on this socket, `false`
+    /// otherwise.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this corresponds to calling `fcntl` returning the value of
+    /// `FD_CLOEXEC`.
+    pub fn cloexec(&self) -> io::Result<bool> {
+        self.fcntl(libc::F_GETFD, ())
+            .map(|flags| flags & libc::FD_CLOEXEC != 0)
+    }
+
+    /// Sets or unsets the close-on-exec flag on this socket.
+    ///
+    /// If set the socket is closed when the process executes a new program
+    /// (e.g. using `execve(2)`), preventing it from leaking into the child
+    /// process. This is useful for sockets received from another process or
+    /// from `accept(2)`, which don't set the flag.
+    ///
+    /// # Notes
+    ///
+    /// On Unix this corresponds to calling `fcntl` returning the current
+    /// flags, with `FD_CLOEXEC` set or unset, to `fcntl` (using `F_SETFD`).
+    pub fn set_cloexec(&self, close_on_exec: bool) -> io::Result<()> {
+        let flags = self.fcntl(libc::F_GETFD, ())?;
+        let new = if close_on_exec {
+            flags | libc::FD_CLOEXEC
+        } else {
+            flags & !libc::FD_CLOEXEC
+        };
+        if new != flags {
+            self.fcntl(libc::F_SETFD, new)?;
+        }
+        Ok(())
+    }
+
+    /// Manipulate the underlying device parameters of the socket.
+    ///
+    /// This function directly corresponds to the `ioctl(2)` function. As
+    /// different requests have different arguments the user must define the
+    /// correct type `T`!
+    ///
+    /// # Examples
+    ///
+    /// The following example retrieves the number of bytes available for
+    /// reading.
+    ///
+    /// ```
+    /// use std::io;
+    /// use socket2::{Domain, Socket, Type};
+    ///
+    /// # fn main() -> io::Result<()> {
+    /// let socket = Socket::new(Domain::IPV4, Type::DGRAM, None)?;
+    ///
+    /// let mut available: libc::c_int = 0;
+    /// // SAFETY: `FIONREAD` expects a pointer to a `c_int`.
+    /// unsafe { socket.ioctl(libc::FIONREAD as _, &mut available) }?;
+    /// assert_eq!(available, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure `arg` is of the type expected by `request`, e.g.
+    /// a pointer must be valid (and point to enough memory) for the reads and
+    /// writes `ioctl(2)` does for the request.
+    ///
+    /// # Notes
+    ///
+    /// The type of `request` differs per platform (and C library), the value
+    /// is converted to the type `ioctl(2)` expects.
+    pub unsafe fn ioctl<T>(&self, request: libc::c_ulong, arg: T) -> io::Result<c_int> {
+        ioctl(self.inner, request, arg)
+    }
+
+    /// Returns the number of bytes in the send queue of the socket, i.e. data
+    /// that has not been sent or (for TCP) not yet been acknowledged by the
+    /// peer.
+    ///
+    /// This can be used to detect backpressure, or to wait for all data to be
+    /// delivered before closing the socket.
+    ///
+    /// # Notes
+    ///
+    /// On Android and Linux this corresponds to calling `ioctl` with
+    /// `SIOCOUTQ`, on FreeBSD and NetBSD with `FIONWRITE`.
+    ///
+    /// This function is only available on Android, FreeBSD, Linux and NetBSD.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd"
+    ))]
+    pub fn send_queue_size(&self) -> io::Result<usize> {
+        let mut size: c_int = 0;
+        // SAFETY: `SIOCOUTQ` expects a pointer to a `c_int`.
+        unsafe { self.ioctl(SIOCOUTQ as _, &mut size as *mut c_int) }.map(|_| size as usize)
+    }
+
+    /// Returns the number of bytes in the receive queue of the socket, i.e.
+    /// data that has not yet been read.
+    ///
+    /// For TCP sockets this is the same as [`bytes_available`].
+    ///
+    /// # Notes
+    ///
+    /// On Android and Linux this corresponds to calling `ioctl` with
+    /// `SIOCINQ`, on FreeBSD and NetBSD with `FIONREAD`.
+    ///
+    /// This function is only available on Android, FreeBSD, Linux and NetBSD.
+    ///
+    /// [`bytes_available`]: Socket::bytes_available
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_os = "netbsd"
+    ))]
+    pub fn recv_queue_size(&self) -> io::Result<usize> {
+        let mut size: c_int = 0;
+        // NOTE: `SIOCINQ` is the same as `FIONREAD`.
+        // SAFETY: `FIONREAD` expects a pointer to a `c_int`.
+        unsafe { self.ioctl(libc::FIONREAD as _, &mut size as *mut c_int) }.map(|_| size as usize)
+    }
+
+    /// Accept a new incoming connection from this listener.
+    ///
+    /// This function directly corresponds to the `accept4(2)` function.
+    ///
+    /// # Notes
+    ///
+    /// This only available on Android, DragonFlyBSD, FreeBSD, illumos, Linux,
+    /// OpenBSD and Solaris. Once https://github.com/rust-lang/libc/issues/1636
+    /// is fixed NetBSD will also support it.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "illumos",
+        target_os = "linux",
+        // NetBSD 8.0 actually has `accept4(2)`, but libc doesn't expose it
+        // (yet). See https://github.com/rust-lang/libc/issues/1636.
+        //target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "solaris"
+    ))]
+    pub fn accept4(&self, flags: c_int) -> io::Result<(Socket, SockAddr)> {
+        let mut addr: MaybeUninit<libc::sockaddr_storage> = MaybeUninit::uninit();
+        let mut addrlen = size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+        syscall!(accept4(
+            self.inner,
+            addr.as_mut_ptr() as *mut _,
+            &mut addrlen,
+            flags
+        ))
+        .map(|stream_fd| {
+            // This is safe because `accept(2)` filled in the address for us.
+            let addr = unsafe { SockAddr::from_raw_parts(addr.assume_init(), addrlen) };
+            (Socket { inner: stream_fd }, addr)
+        })
+    }
+
+    /// Receives data and control messages from the socket. Returns the amount
+    /// of bytes read, the [`RecvFlags`] and the address from whence the data
+    /// came.
+    ///
+    /// The received control messages are stored in `control`, replacing any
+    /// messages it contained. If `control` is too small to hold all control
+    /// messages they are truncated, which is indicated by
+    /// [`RecvFlags::is_control_truncated`].
+    ///
+    /// This function directly corresponds to the `recvmsg(2)` function.
+    pub fn recv_msg(
+        &self,
+        bufs: &mut [IoSliceMut<'_>],
+        control: &mut CmsgBuffer,
+        flags: c_int,
+    ) -> io::Result<(usize, RecvFlags, SockAddr)> {
+        recv_msg(self.inner, bufs, control, flags)
+    }
+
+    /// Sends data and the control messages in `control` on the socket. If
+    /// `addr` is `Some` the data is sent to that address, otherwise the socket
+    /// must be connected. On success returns the number of bytes that were
+    /// sent.
+    ///
+    /// Unlike [`send`] this doesn't pass `MSG_NOSIGNAL`, callers that want to
+    /// avoid `SIGPIPE` must include it in `flags` themselves.
+    ///
+    /// This function directly corresponds to the `sendmsg(2)` function.
+    ///
+    /// [`send`]: Socket::send
+    pub fn send_msg(
+        &self,
+        bufs: &[IoSlice<'_>],
+        addr: Option<&SockAddr>,
+        control: &CmsgBuffer,
+        flags: c_int,
+    ) -> io::Result<usize> {
+        send_msg(self.inner, bufs, addr, control, flags)
+    }
+
+    /// Get the value of the `SO_ZEROCOPY` option on this socket.
+    ///
+    /// For more information about this option, see [`set_zerocopy`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_zerocopy`]: Socket::set_zerocopy
+    #[cfg(target_os = "linux")]
+    pub fn zerocopy(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, SO_ZEROCOPY)
+            .map(|zerocopy| zerocopy != 0)
+    }
+
+    /// Set the value of the `SO_ZEROCOPY` option on this socket.
+    ///
+    /// This must be enabled before [`Socket::send_zerocopy`] can be used.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn set_zerocopy(&self, zerocopy: bool) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, SO_ZEROCOPY, &(zerocopy as c_int))
+    }
+
+    /// Sends data on the socket to a connected peer, without copying `buf`
+    /// into the kernel.
+    ///
+    /// This calls [`send_with_flags`] using the `MSG_ZEROCOPY` and
+    /// `MSG_NOSIGNAL` flags. The socket must have [`SO_ZEROCOPY`] enabled.
+    ///
+    /// The kernel will read directly from `buf` until the send is completed,
+    /// this means that `buf` must not be modified until the completion
+    /// notification for this call is received using
+    /// [`recv_zerocopy_completion`]. Modifying the buffer earlier will not
+    /// cause memory unsafety, but can change the data that is sent.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`send_with_flags`]: Socket::send_with_flags
+    /// [`SO_ZEROCOPY`]: Socket::set_zerocopy
+    /// [`recv_zerocopy_completion`]: Socket::recv_zerocopy_completion
+    #[cfg(target_os = "linux")]
+    pub fn send_zerocopy(&self, buf: &[u8]) -> io::Result<usize> {
+        self.send_with_flags(buf, libc::MSG_ZEROCOPY | MSG_NOSIGNAL)
+    }
+
+    /// Receives a completion notification for [`send_zerocopy`] calls from
+    /// the socket's error queue.
+    ///
+    /// Reading from the error queue never blocks, if no notification is
+    /// queued this returns an error of the kind
+    /// [`io::ErrorKind::WouldBlock`]. The arrival of notifications is
+    /// signaled by `POLLERR`.
+    ///
+    /// Returns `None` if the dequeued message is not a zerocopy completion
+    /// notification, e.g. an ICMP error.
+    ///
+    /// This function corresponds to `recvmsg(2)` using the `MSG_ERRQUEUE`
+    /// flag.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`send_zerocopy`]: Socket::send_zerocopy
+    #[cfg(target_os = "linux")]
+    pub fn recv_zerocopy_completion(&self) -> io::Result<Option<ZerocopyCompletion>> {
+        // Large enough to hold a `sock_extended_err` and the offending
+        // address.
+        let mut control = [0u64; 16];
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_control = control.as_mut_ptr() as *mut _;
+        msg.msg_controllen = mem::size_of_val(&control) as _;
+        syscall!(recvmsg(self.inner, &mut msg, libc::MSG_ERRQUEUE))?;
+
+        let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(&msg) };
+        while !cmsg.is_null() {
+            let (level, ty) = unsafe { ((*cmsg).cmsg_level, (*cmsg).cmsg_type) };
+            if (level == libc::SOL_IP && ty == libc::IP_RECVERR)
+                || (level == libc::SOL_IPV6 && ty == libc::IPV6_RECVERR)
+            {
+                // Safety: the kernel wrote a `sock_extended_err` for these
+                // control messages. The data is not guaranteed to be aligned.
+                let err = unsafe {
+                    std::ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::sock_extended_err)
+                };
+                if err.ee_errno == 0 && err.ee_origin == SO_EE_ORIGIN_ZEROCOPY {
+                    return Ok(Some(ZerocopyCompletion {
+                        start: err.ee_info,
+                        end: err.ee_data,
+                        copied: err.ee_code & SO_EE_CODE_ZEROCOPY_COPIED != 0,
+                    }));
+                }
+            }
+            cmsg = unsafe { libc::CMSG_NXTHDR(&msg, cmsg) };
+        }
+        Ok(None)
+    }
+
+    /// Get the value of the `SO_TIMESTAMPING` option on this socket.
+    ///
+    /// For more information about this option, see [`set_timestamping`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_timestamping`]: Socket::set_timestamping
+    #[cfg(target_os = "linux")]
+    pub fn timestamping(&self) -> io::Result<Timestamping> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_TIMESTAMPING)
+            .map(|flags| Timestamping(flags as u32))
+    }
+
+    /// Set the value of the `SO_TIMESTAMPING` option on this socket.
+    ///
+    /// This enables the generation and reporting of timestamps for incoming
+    /// and outgoing packets. Timestamps of received packets are returned as
+    /// `SCM_TIMESTAMPING` control message, which can be read using
+    /// [`recv_msg`] and [`Cmsg::timestamping`]. Timestamps of sent packets
+    /// are queued on the socket's error queue and can be read using
+    /// [`recv_tx_timestamp`].
+    ///
+    /// Hardware timestamps also require the network interface to be
+    /// configured using the `SIOCSHWTSTAMP` ioctl.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`recv_msg`]: Socket::recv_msg
+    /// [`recv_tx_timestamp`]: Socket::recv_tx_timestamp
+    #[cfg(target_os = "linux")]
+    pub fn set_timestamping(&self, flags: Timestamping) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_TIMESTAMPING, &(flags.0 as c_int))
+    }
+
+    /// Receives the timestamps of a sent packet from the socket's error
+    /// queue.
+    ///
+    /// Reading from the error queue never blocks, if no timestamp is queued
+    /// this returns an error of the kind [`io::ErrorKind::WouldBlock`]. The
+    /// arrival of timestamps is signaled by `POLLERR`.
+    ///
+    /// Returns `None` if the dequeued message doesn't contain a timestamp,
+    /// e.g. an ICMP error.
+    ///
+    /// This function corresponds to `recvmsg(2)` using the `MSG_ERRQUEUE`
+    /// flag.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn recv_tx_timestamp(&self) -> io::Result<Option<TxTimestamp>> {
+        // Large enough to hold the timestamps, a `sock_extended_err` and the
+        // offending address. The packet itself (if not using
+        // `Timestamping::OPT_TSONLY`) is discarded.
+        let mut control = CmsgBuffer::with_capacity(
+            CmsgBuffer::space(size_of::<scm_timestamping>())
+                + CmsgBuffer::space(
+                    size_of::<libc::sock_extended_err>() + size_of::<libc::sockaddr_in6>(),
+                ),
+        );
+        let _ = self.recv_msg(&mut [], &mut control, libc::MSG_ERRQUEUE)?;
+
+        let mut timestamps = None;
+        let mut info = None;
+        for cmsg in control.iter() {
+            if let Some(ts) = cmsg.timestamping() {
+                timestamps = Some(ts);
+            } else if ((cmsg.level == libc::SOL_IP && cmsg.r#type == libc::IP_RECVERR)
+                || (cmsg.level == libc::SOL_IPV6 && cmsg.r#type == libc::IPV6_RECVERR))
+                && cmsg.data.len() >= size_of::<libc::sock_extended_err>()
+            {
+                // Safety: checked the size above, the data is not guaranteed
+                // to be aligned.
+                let err: libc::sock_extended_err =
+                    unsafe { std::ptr::read_unaligned(cmsg.data.as_ptr() as *const _) };
+                if err.ee_errno == libc::ENOMSG as u32
+                    && err.ee_origin == libc::SO_EE_ORIGIN_TIMESTAMPING
+                {
+                    info = Some((err.ee_data, err.ee_info));
+                }
+            }
+        }
+        Ok(match (timestamps, info) {
+            (Some(timestamps), Some((id, kind))) => Some(TxTimestamp {
+                timestamps,
+                id,
+                kind,
+            }),
+            _ => None,
+        })
+    }
+
+    /// Get the value of the `IP_RECVERR` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_err`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recv_err`]: Socket::set_recv_err
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn recv_err(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::SOL_IP, libc::IP_RECVERR)
+            .map(|recv| recv != 0)
+    }
+
+    /// Set the value of the `IP_RECVERR` option on this socket.
+    ///
+    /// If enabled, extended errors, such as ICMP destination unreachable
+    /// messages, are queued on the socket's error queue and can be read using
+    /// [`recv_error`]. This allows datagram based protocols to learn about
+    /// unreachable destinations and path MTU changes.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`recv_error`]: Socket::recv_error
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_recv_err(&self, recv: bool) -> io::Result<()> {
+        self.setsockopt(libc::SOL_IP, libc::IP_RECVERR, &(recv as c_int))
+    }
+
+    /// Get the value of the `IPV6_RECVERR` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_err_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recv_err_v6`]: Socket::set_recv_err_v6
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn recv_err_v6(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::SOL_IPV6, libc::IPV6_RECVERR)
+            .map(|recv| recv != 0)
+    }
+
+    /// Set the value of the `IPV6_RECVERR` option on this socket.
+    ///
+    /// This is the IPv6 counterpart of [`set_recv_err`], for ICMPv6 errors.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recv_err`]: Socket::set_recv_err
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_recv_err_v6(&self, recv: bool) -> io::Result<()> {
+        self.setsockopt(libc::SOL_IPV6, libc::IPV6_RECVERR, &(recv as c_int))
+    }
+
+    /// Receives an extended error, e.g. an ICMP error, from the socket's
+    /// error queue.
+    ///
+    /// Reading from the error queue never blocks, if no error is queued this
+    /// returns an error of the kind [`io::ErrorKind::WouldBlock`]. The
+    /// arrival of errors is signaled by `POLLERR`. The packet that caused
+    /// the error is discarded.
+    ///
+    /// Returns `None` if the dequeued message doesn't contain an extended
+    /// error.
+    ///
+    /// This function corresponds to `recvmsg(2)` using the `MSG_ERRQUEUE`
+    /// flag, see [`set_recv_err`] and [`set_recv_err_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recv_err`]: Socket::set_recv_err
+    /// [`set_recv_err_v6`]: Socket::set_recv_err_v6
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn recv_error(&self) -> io::Result<Option<ExtendedError>> {
+        // Large enough to hold a `sock_extended_err` and the offending
+        // address.
+        let mut control = CmsgBuffer::with_capacity(CmsgBuffer::space(
+            size_of::<libc::sock_extended_err>() + size_of::<libc::sockaddr_in6>(),
+        ));
+        let _ = self.recv_msg(&mut [], &mut control, libc::MSG_ERRQUEUE)?;
+        Ok(control.iter().find_map(|cmsg| cmsg.extended_error()))
+    }
+
+    /// Sends `len` bytes of `file`, starting at `offset`, to the socket
+    /// without copying the data through userspace.
+    ///
+    /// Returns the number of bytes written, which may be less than `len`. If
+    /// `len` is zero no data is send and this returns zero. The file's
+    /// position is not modified. Returns an `InvalidInput` error if `offset`
+    /// doesn't fit in `off_t`.
+    ///
+    /// This function corresponds to `sendfile(2)`, abstracting the different
+    /// signatures of the platforms.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn sendfile(&self, file: &File, offset: u64, len: usize) -> io::Result<usize> {
+        if len == 0 {
+            return Ok(0);
+        }
+        let offset = libc::off_t::try_from(offset)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "offset too large"))?;
+        sendfile(file.as_raw_fd(), self.inner, offset, len)
+    }
+
+    /// Moves up to `len` bytes of data from the socket into the pipe `pipe`
+    /// without copying the data through userspace.
+    ///
+    /// `pipe` must be the write end of a pipe. The `flags` argument is a bit
+    /// mask of the `SPLICE_F_*` flags, e.g. `SPLICE_F_MOVE` or
+    /// `SPLICE_F_NONBLOCK`. Returns the number of bytes moved, zero means the
+    /// socket reached end-of-file.
+    ///
+    /// Together with [`Socket::splice_from`] this can be used to proxy data
+    /// between two sockets without copying it into userspace.
+    ///
+    /// This function corresponds to `splice(2)`.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn splice_to(&self, pipe: RawFd, len: usize, flags: c_int) -> io::Result<usize> {
+        splice(self.inner, pipe, len, flags)
+    }
+
+    /// Moves up to `len` bytes of data from the pipe `pipe` into the socket
+    /// without copying the data through userspace.
+    ///
+    /// `pipe` must be the read end of a pipe. The `flags` argument is a bit
+    /// mask of the `SPLICE_F_*` flags, e.g. `SPLICE_F_MOVE` or
+    /// `SPLICE_F_NONBLOCK`. Returns the number of bytes moved.
+    ///
+    /// This function corresponds to `splice(2)`.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn splice_from(&self, pipe: RawFd, len: usize, flags: c_int) -> io::Result<usize> {
+        splice(pipe, self.inner, len, flags)
+    }
+
+    /// Get the value of the `SO_MARK` option on this socket.
+    ///
+    /// For more information about this option, see [`set_mark`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_mark`]: Socket::set_mark
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mark(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_MARK)
+            .map(|mark| mark as u32)
+    }
+
+    /// Set the value of the `SO_MARK` option on this socket.
+    ///
+    /// This value sets the socket mark field for each packet sent through
+    /// this socket, which can be used for mark-based routing and packet
+    /// filtering (e.g. matching in nftables).
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// Changing the mark requires the `CAP_NET_ADMIN` capability.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_MARK, &(mark as c_int))
+    }
+
+    /// Attach a classic BPF filter to this socket, using the
+    /// `SO_ATTACH_FILTER` option.
+    ///
+    /// Packets that don't pass the filter are dropped by the kernel, before
+    /// they're queued on the socket. Attaching a filter replaces any filter
+    /// previously attached to the socket.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn attach_filter(&self, filters: &[libc::sock_filter]) -> io::Result<()> {
+        if filters.len() > u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many BPF instructions",
+            ));
+        }
+        // The kernel copies the program, it doesn't modify `filters`.
+        let prog = libc::sock_fprog {
+            len: filters.len() as _,
+            filter: filters.as_ptr() as *mut _,
+        };
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_ATTACH_FILTER, &prog)
+    }
+
+    /// Detach the BPF filter previously attached to this socket using
+    /// [`attach_filter`], using the `SO_DETACH_FILTER` option.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`attach_filter`]: Socket::attach_filter
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn detach_filter(&self) -> io::Result<()> {
+        // The value is ignored by the kernel.
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_DETACH_FILTER, &0 as &c_int)
+    }
+
+    /// Gets the value for the `SO_BINDTODEVICE` option on this socket.
+    ///
+    /// This returns the interface name of the device the socket is bound to,
+    /// or `None` if the socket is not bound to a device.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn device(&self) -> io::Result<Option<Vec<u8>>> {
+        // TODO: replace with `MaybeUninit::uninit_array` once stable.
+        let mut buf: [MaybeUninit<u8>; libc::IFNAMSIZ] =
+            unsafe { MaybeUninit::uninit().assume_init() };
+        let mut len = buf.len() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.inner,
+            libc::SOL_SOCKET,
+            libc::SO_BINDTODEVICE,
+            buf.as_mut_ptr().cast(),
+            &mut len,
+        ))?;
+        if len == 0 {
+            Ok(None)
+        } else {
+            // Safety: `len` bytes are initialised by the OS.
+            let buf = &buf[..len as usize - 1];
+            // TODO: use `MaybeUninit::slice_assume_init_ref` once stable.
+            Ok(Some(unsafe { &*(buf as *const [_] as *const [u8]) }.into()))
+        }
+    }
+
+    /// Sets the value for the `SO_BINDTODEVICE` option on this socket.
+    ///
+    /// If a socket is bound to an interface, only packets received from that
+    /// particular interface are processed by the socket. Note that this only
+    /// works for some socket types, particularly `AF_INET` sockets.
+    ///
+    /// If `interface` is `None` or an empty string it removes the binding.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        let (value, len) = if let Some(interface) = interface {
+            (interface.as_ptr(), interface.len())
+        } else {
+            (std::ptr::null(), 0)
+        };
+        syscall!(setsockopt(
+            self.inner,
+            libc::SOL_SOCKET,
+            libc::SO_BINDTODEVICE,
+            value.cast(),
+            len as libc::socklen_t,
+        ))
+        .map(|_| ())
+    }
+
+    /// Gets the value for the `SO_BINDTOIFINDEX` option on this socket.
+    ///
+    /// This returns the index of the interface the socket is bound to, or
+    /// `None` if the socket is not bound to an interface.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn device_index(&self) -> io::Result<Option<NonZeroU32>> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_BINDTOIFINDEX)
+            .map(|index| NonZeroU32::new(index as u32))
+    }
+
+    /// Sets the value for the `SO_BINDTOIFINDEX` option on this socket.
+    ///
+    /// This is the same as [`bind_device`], but uses the index of the
+    /// interface instead of its name. This avoids having to look up the
+    /// interface by name and races with the interface being renamed.
+    ///
+    /// If `interface` is `None` it removes the binding.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux, and requires
+    /// Linux 5.0 or later.
+    ///
+    /// [`bind_device`]: Socket::bind_device
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn bind_device_by_index(&self, interface: Option<NonZeroU32>) -> io::Result<()> {
+        let index = interface.map_or(0, NonZeroU32::get) as c_int;
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_BINDTOIFINDEX, &index)
+    }
+
+    /// Get the value of the `IP_TRANSPARENT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_ip_transparent`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_ip_transparent`]: Socket::set_ip_transparent
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn ip_transparent(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, libc::IP_TRANSPARENT)
+            .map(|transparent| transparent != 0)
+    }
+
+    /// Set the value of the `IP_TRANSPARENT` option on this socket.
+    ///
+    /// Setting this boolean option enables transparent proxying on this
+    /// socket. This socket option allows the calling application to bind to a
+    /// nonlocal IP address and operate both as a client and a server with the
+    /// foreign address as the local endpoint. This is used by transparent
+    /// proxies in combination with the `TPROXY` iptables target.
+    ///
+    /// Note that the routing of packets also needs to be set up such that
+    /// packets going to the foreign address are routed through the TProxy
+    /// box.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// Setting this option requires the `CAP_NET_ADMIN` capability.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_ip_transparent(&self, transparent: bool) -> io::Result<()> {
+        self.setsockopt(
+            libc::IPPROTO_IP,
+            libc::IP_TRANSPARENT,
+            &(transparent as c_int),
+        )
+    }
+
+    /// Get the value of the `TCP_FASTOPEN_CONNECT` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_tcp_fastopen_connect`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_fastopen_connect`]: Socket::set_tcp_fastopen_connect
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn tcp_fastopen_connect(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_TCP, libc::TCP_FASTOPEN_CONNECT)
+            .map(|connect| connect != 0)
+    }
+
+    /// Set the value of the `TCP_FASTOPEN_CONNECT` option on this socket.
+    ///
+    /// When enabled a call to [`connect`] doesn't start the handshake,
+    /// instead the data of the first write (e.g. [`send`]) is sent in the
+    /// SYN packet using TCP Fast Open. This allows TFO to be used without
+    /// changing the order of the `connect` and `send` calls, as opposed to
+    /// [`send_fastopen`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux (4.11+).
+    ///
+    /// [`connect`]: Socket::connect
+    /// [`send`]: Socket::send
+    /// [`send_fastopen`]: Socket::send_fastopen
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_tcp_fastopen_connect(&self, connect: bool) -> io::Result<()> {
+        self.setsockopt(
+            libc::IPPROTO_TCP,
+            libc::TCP_FASTOPEN_CONNECT,
+            &(connect as c_int),
+        )
+    }
+
+    /// Connects the socket to `addr` sending `buf` in the SYN packet using
+    /// TCP Fast Open.
+    ///
+    /// This calls [`send_to_with_flags`] using the `MSG_FASTOPEN` flag, which
+    /// replaces the call to [`connect`]. If no TFO cookie is available for
+    /// `addr` a regular handshake is performed and the data is sent after
+    /// it.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`send_to_with_flags`]: Socket::send_to_with_flags
+    /// [`connect`]: Socket::connect
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn send_fastopen(&self, buf: &[u8], addr: &SockAddr) -> io::Result<usize> {
+        self.send_to_with_flags(buf, addr, libc::MSG_FASTOPEN | MSG_NOSIGNAL)
+    }
+
+    /// Connects the socket to `addr`, optionally binding it to the source
+    /// address `src` and sending the data in `bufs` as part of the
+    /// connection setup.
+    ///
+    /// `flags` is a combination of the `CONNECT_*` constants, e.g.
+    /// `CONNECT_DATA_IDEMPOTENT` to send `bufs` in the SYN packet using TCP
+    /// Fast Open (similar to [`send_fastopen`] on Linux), or
+    /// `CONNECT_RESUME_ON_READ_WRITE` to delay the connection setup until the
+    /// first read or write.
+    ///
+    /// Returns the number of bytes of `bufs` that were queued to be sent.
+    /// Like [`connect`], on a nonblocking socket this may return an error of
+    /// the kind `EINPROGRESS`, in which case the data is still queued.
+    ///
+    /// This function corresponds to `connectx(2)`.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on iOS and macOS.
+    ///
+    /// [`send_fastopen`]: Socket::send_fastopen
+    /// [`connect`]: Socket::connect
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    pub fn connectx(
+        &self,
+        addr: &SockAddr,
+        src: Option<&SockAddr>,
+        bufs: &[IoSlice<'_>],
+        flags: libc::c_uint,
+    ) -> io::Result<usize> {
+        let mut endpoints: libc::sa_endpoints_t = unsafe { mem::zeroed() };
+        if let Some(src) = src {
+            endpoints.sae_srcaddr = src.as_ptr() as *const libc::sockaddr;
+            endpoints.sae_srcaddrlen = src.len();
+        }
+        endpoints.sae_dstaddr = addr.as_ptr() as *const libc::sockaddr;
+        endpoints.sae_dstaddrlen = addr.len();
+        let mut len = 0;
+        // `IoSlice` is guaranteed to be ABI compatible with `iovec`.
+        syscall!(connectx(
+            self.inner,
+            &endpoints,
+            libc::SAE_ASSOCID_ANY,
+            flags,
+            bufs.as_ptr() as *const libc::iovec,
+            min(bufs.len(), libc::c_uint::MAX as usize) as libc::c_uint,
+            &mut len,
+            std::ptr::null_mut(),
+        ))
+        .map(|_| len)
+    }
+
+    /// Get the value of the `TCP_QUICKACK` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tcp_quickack`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_quickack`]: Socket::set_tcp_quickack
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn tcp_quickack(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_TCP, libc::TCP_QUICKACK)
+            .map(|quickack| quickack != 0)
+    }
+
+    /// Set the value of the `TCP_QUICKACK` option on this socket.
+    ///
+    /// If set, acks are sent immediately, rather than delayed if needed in
+    /// accordance to normal TCP operation. This flag is not permanent, it only
+    /// enables a switch to or from quickack mode. Subsequent operation of the
+    /// TCP protocol will once again enter/leave quickack mode depending on
+    /// internal protocol processing and factors such as delayed ack timeouts
+    /// occurring and data transfer.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_tcp_quickack(&self, quickack: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_TCP, libc::TCP_QUICKACK, &(quickack as c_int))
+    }
+
+    /// Get the value of the `TCP_SYNCNT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_syn_count`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_syn_count`]: Socket::set_syn_count
+    #[cfg(target_os = "linux")]
+    pub fn syn_count(&self) -> io::Result<u8> {
+        self.getsockopt::<c_int>(libc::IPPROTO_TCP, libc::TCP_SYNCNT)
+            .map(|count| count as u8)
+    }
+
+    /// Set the value of the `TCP_SYNCNT` option on this socket.
+    ///
+    /// Sets the number of SYN retransmits that TCP should send before aborting
+    /// the attempt to connect. This (indirectly) bounds the time a blocking
+    /// `connect` can take. The kernel doesn't accept values larger than 127.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn set_syn_count(&self, count: u8) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_TCP, libc::TCP_SYNCNT, &(count as c_int))
+    }
+
+    /// Get the value of the `TCP_REPAIR` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tcp_repair`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_repair`]: Socket::set_tcp_repair
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn tcp_repair(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_TCP, libc::TCP_REPAIR)
+            .map(|repair| repair != 0)
+    }
+
+    /// Set the value of the `TCP_REPAIR` option on this socket.
+    ///
+    /// Puts the socket in (or takes it out of) repair mode. In repair mode
+    /// the state of the connection can be read and restored without any
+    /// packets being exchanged with the peer, e.g. `connect` restores an
+    /// established connection without sending a SYN. This is used to
+    /// checkpoint and restore (or migrate) TCP connections, see
+    /// [`set_tcp_repair_queue`] and [`set_tcp_queue_seq`].
+    ///
+    /// Requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_repair_queue`]: Socket::set_tcp_repair_queue
+    /// [`set_tcp_queue_seq`]: Socket::set_tcp_queue_seq
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_tcp_repair(&self, repair: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_TCP, libc::TCP_REPAIR, &(repair as c_int))
+    }
+
+    /// Get the value of the `TCP_REPAIR_QUEUE` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tcp_repair_queue`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_repair_queue`]: Socket::set_tcp_repair_queue
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn tcp_repair_queue(&self) -> io::Result<TcpRepairQueue> {
+        self.getsockopt::<c_int>(libc::IPPROTO_TCP, libc::TCP_REPAIR_QUEUE)
+            .and_then(TcpRepairQueue::from_raw)
+    }
+
+    /// Set the value of the `TCP_REPAIR_QUEUE` option on this socket.
+    ///
+    /// Selects the queue that subsequent reads, writes and
+    /// [`set_tcp_queue_seq`] calls operate on while the socket is in repair
+    /// mode, see [`set_tcp_repair`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_queue_seq`]: Socket::set_tcp_queue_seq
+    /// [`set_tcp_repair`]: Socket::set_tcp_repair
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_tcp_repair_queue(&self, queue: TcpRepairQueue) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_TCP, libc::TCP_REPAIR_QUEUE, &queue.into_raw())
+    }
+
+    /// Get the value of the `TCP_QUEUE_SEQ` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tcp_queue_seq`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_queue_seq`]: Socket::set_tcp_queue_seq
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn tcp_queue_seq(&self) -> io::Result<u32> {
+        self.getsockopt::<u32>(libc::IPPROTO_TCP, libc::TCP_QUEUE_SEQ)
+    }
+
+    /// Set the value of the `TCP_QUEUE_SEQ` option on this socket.
+    ///
+    /// Sets the sequence number of the queue selected with
+    /// [`set_tcp_repair_queue`]. Only allowed in repair mode on a socket
+    /// that is not yet connected, see [`set_tcp_repair`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_tcp_repair_queue`]: Socket::set_tcp_repair_queue
+    /// [`set_tcp_repair`]: Socket::set_tcp_repair
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_tcp_queue_seq(&self, seq: u32) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_TCP, libc::TCP_QUEUE_SEQ, &seq)
+    }
+
+    /// Get the value of the `TCP_SAVE_SYN` option on this socket.
+    ///
+    /// For more information about this option, see [`set_save_syn`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_save_syn`]: Socket::set_save_syn
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn save_syn(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_TCP, libc::TCP_SAVE_SYN)
+            .map(|save| save != 0)
+    }
+
+    /// Set the value of the `TCP_SAVE_SYN` option on this socket.
+    ///
+    /// If set on a listening socket, the headers of the SYN packet of
+    /// incoming connections are saved, which can be retrieved using
+    /// [`saved_syn`] on the accepted socket.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`saved_syn`]: Socket::saved_syn
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_save_syn(&self, save: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_TCP, libc::TCP_SAVE_SYN, &(save as c_int))
+    }
+
+    /// Get the value of the `TCP_SAVED_SYN` option on this socket.
+    ///
+    /// Returns the IP and TCP headers of the SYN packet that created this
+    /// connection, if the listening socket had [`set_save_syn`] enabled. The
+    /// headers are freed by the kernel once read, so subsequent calls return
+    /// an empty buffer.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_save_syn`]: Socket::set_save_syn
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn saved_syn(&self) -> io::Result<Vec<u8>> {
+        // Maximum IPv6 header (without extension headers) and TCP header.
+        getsockopt_bytes(self.inner, libc::IPPROTO_TCP, libc::TCP_SAVED_SYN, 40 + 60)
+    }
+
+    /// Get the value of the `TCP_NOTSENT_LOWAT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tcp_notsent_lowat`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, iOS, Linux and macOS.
+    ///
+    /// [`set_tcp_notsent_lowat`]: Socket::set_tcp_notsent_lowat
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn tcp_notsent_lowat(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(libc::IPPROTO_TCP, TCP_NOTSENT_LOWAT)
+            .map(|lowat| lowat as u32)
+    }
+
+    /// Set the value of the `TCP_NOTSENT_LOWAT` option on this socket.
+    ///
+    /// Limits the amount of unsent data (in bytes) buffered in the kernel for
+    /// this socket. The socket is only reported as writable once the amount of
+    /// unsent data drops below this threshold, which reduces the latency of
+    /// data written to the socket.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn set_tcp_notsent_lowat(&self, lowat: u32) -> io::Result<()> {
+        let lowat = min(lowat, c_int::MAX as u32) as c_int;
+        self.setsockopt(libc::IPPROTO_TCP, TCP_NOTSENT_LOWAT, &lowat)
+    }
+
+    /// Get the value of the `TCP_CORK` option on this socket.
+    ///
+    /// For more information about this option, see [`set_cork`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD, iOS,
+    /// Linux, macOS and OpenBSD.
+    ///
+    /// [`set_cork`]: Socket::set_cork
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "openbsd"
+    ))]
+    pub fn cork(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_TCP, TCP_CORK)
+            .map(|cork| cork != 0)
+    }
+
+    /// Set the value of the `TCP_CORK` option on this socket.
+    ///
+    /// If set, partial frames are not sent until the option is cleared again
+    /// (or, on Linux, a 200 ms timeout expires). This allows, for example, a
+    /// header written with `send` and a body sent with `sendfile` to be
+    /// coalesced into full sized packets.
+    ///
+    /// On iOS and macOS clearing the option doesn't flush the pending partial
+    /// frame by itself, it's only sent with the next write (or once the
+    /// connection is closed).
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD, iOS,
+    /// Linux, macOS and OpenBSD.
+    ///
+    /// On the BSDs and Apple platforms this uses the `TCP_NOPUSH` option
+    /// instead, see [`set_nopush`].
+    ///
+    /// [`set_nopush`]: Socket::set_nopush
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "openbsd"
+    ))]
+    pub fn set_cork(&self, cork: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_TCP, TCP_CORK, &(cork as c_int))
+    }
+
+    /// Get the value of the `TCP_NOPUSH` option on this socket.
+    ///
+    /// For more information about this option, see [`set_nopush`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFlyBSD, FreeBSD, iOS, macOS
+    /// and OpenBSD.
+    ///
+    /// [`set_nopush`]: Socket::set_nopush
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd"
+    ))]
+    pub fn nopush(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_TCP, libc::TCP_NOPUSH)
+            .map(|nopush| nopush != 0)
+    }
+
+    /// Set the value of the `TCP_NOPUSH` option on this socket.
+    ///
+    /// If set, data is only sent in full sized segments until the option is
+    /// cleared again, at which point any remaining data is sent. This is the
+    /// BSD equivalent of Linux's `TCP_CORK`, see [`set_cork`] for a portable
+    /// version.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFlyBSD, FreeBSD, iOS, macOS
+    /// and OpenBSD.
+    ///
+    /// [`set_cork`]: Socket::set_cork
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd"
+    ))]
+    pub fn set_nopush(&self, nopush: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_TCP, libc::TCP_NOPUSH, &(nopush as c_int))
+    }
+
+    /// Get the value of the `TCP_CONGESTION` option on this socket.
+    ///
+    /// This returns the name of the congestion control algorithm used by this
+    /// socket, e.g. `cubic`. For more information about this option, see
+    /// [`set_tcp_congestion`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD and Linux.
+    ///
+    /// [`set_tcp_congestion`]: Socket::set_tcp_congestion
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    pub fn tcp_congestion(&self) -> io::Result<Vec<u8>> {
+        // `TCP_CA_NAME_MAX` on Linux and FreeBSD.
+        let mut buf = [0u8; 16];
+        let mut len = buf.len() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.inner,
+            libc::IPPROTO_TCP,
+            libc::TCP_CONGESTION,
+            buf.as_mut_ptr().cast(),
+            &mut len,
+        ))?;
+        // The name is NULL terminated if it's shorter than the buffer.
+        let name = &buf[..len as usize];
+        let len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+        Ok(name[..len].to_vec())
+    }
+
+    /// Set the value of the `TCP_CONGESTION` option on this socket.
+    ///
+    /// Selects the congestion control algorithm used by this socket by name,
+    /// e.g. `b"bbr"` or `b"cubic"`. The algorithm must be available in (or
+    /// loadable by) the kernel, unprivileged processes can only select the
+    /// algorithms listed in `net.ipv4.tcp_allowed_congestion_control` on
+    /// Linux.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD and Linux.
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    pub fn set_tcp_congestion(&self, name: &[u8]) -> io::Result<()> {
+        syscall!(setsockopt(
+            self.inner,
+            libc::IPPROTO_TCP,
+            libc::TCP_CONGESTION,
+            name.as_ptr().cast(),
+            name.len() as libc::socklen_t,
+        ))
+        .map(|_| ())
+    }
+
+    /// Get the value of the `TCP_INFO` option on this socket.
+    ///
+    /// Returns information about the TCP connection, such as the round trip
+    /// time and congestion window. See [`TcpInfo`] for the available fields.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        // Older kernels return fewer bytes, leaving the remaining fields zero.
+        let mut info: TcpInfo = unsafe { mem::zeroed() };
+        let mut len = size_of::<TcpInfo>() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.inner,
+            libc::IPPROTO_TCP,
+            libc::TCP_INFO,
+            &mut info as *mut _ as *mut _,
+            &mut len,
+        ))
+        .map(|_| info)
+    }
+
+    /// Get the value of the `TCP_CONNECTION_INFO` option on this socket.
+    ///
+    /// Returns information about the TCP connection, such as the round trip
+    /// time and traffic counters. See [`TcpConnectionInfo`] for the available
+    /// fields. This is the equivalent of [`tcp_info`] on Linux.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on iOS and macOS.
+    ///
+    /// [`tcp_info`]: Socket::tcp_info
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    pub fn tcp_connection_info(&self) -> io::Result<TcpConnectionInfo> {
+        let mut info: TcpConnectionInfo = unsafe { mem::zeroed() };
+        let mut len = size_of::<TcpConnectionInfo>() as libc::socklen_t;
+        syscall!(getsockopt(
+            self.inner,
+            libc::IPPROTO_TCP,
+            libc::TCP_CONNECTION_INFO,
+            &mut info as *mut _ as *mut _,
+            &mut len,
+        ))
+        .map(|_| info)
+    }
+
+    /// Get the value of the `SO_NREAD` option on this socket.
+    ///
+    /// Returns the number of bytes available for reading. For datagram
+    /// sockets this is the size of the first datagram in the receive queue
+    /// (excluding any address and control data).
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on iOS and macOS.
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    pub fn bytes_readable(&self) -> io::Result<usize> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_NREAD)
+            .map(|n| n as usize)
+    }
+
+    /// Get the value of the `SO_NWRITE` option on this socket.
+    ///
+    /// Returns the number of bytes in the send buffer that have not yet been
+    /// acknowledged by the peer.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on iOS and macOS.
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    pub fn bytes_pending_write(&self) -> io::Result<usize> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_NWRITE)
+            .map(|n| n as usize)
+    }
+
+    /// Get the value of the `SO_DOMAIN` option on this socket.
+    ///
+    /// Returns the domain of the socket, e.g. [`Domain::IPV4`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD and Linux.
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    pub fn domain(&self) -> io::Result<Domain> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_DOMAIN)
+            .map(Domain::from)
+    }
+
+    /// Get the value of the `SO_PROTOCOL` option on this socket.
+    ///
+    /// Returns the protocol of the socket, or `None` if the default protocol
+    /// for the socket's domain and type was used (i.e. zero).
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD and Linux.
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    pub fn protocol(&self) -> io::Result<Option<Protocol>> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_PROTOCOL)
+            .map(|protocol| match protocol {
+                0 => None,
+                p => Some(Protocol::from(p)),
+            })
+    }
+
+    /// Join a multicast group using the protocol-independent
+    /// `MCAST_JOIN_GROUP` option on this socket.
+    ///
+    /// Unlike [`Socket::join_multicast_v4`] and [`Socket::join_multicast_v6`]
+    /// this works for both IPv4 and IPv6 groups, using the address family of
+    /// `group`, and allows IPv4 groups to be joined on an interface by index.
+    /// `interface` is the index of the interface to join the group on, or 0
+    /// to let the system choose an appropriate interface. The port of `group`
+    /// is ignored.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn join_multicast(&self, group: &SockAddr, interface: u32) -> io::Result<()> {
+        let (level, req) = group_req(group, interface)?;
+        self.setsockopt(level, libc::MCAST_JOIN_GROUP, &req)
+    }
+
+    /// Leave a multicast group using the protocol-independent
+    /// `MCAST_LEAVE_GROUP` option on this socket.
+    ///
+    /// For more information about this option, see [`join_multicast`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    ///
+    /// [`join_multicast`]: Socket::join_multicast
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn leave_multicast(&self, group: &SockAddr, interface: u32) -> io::Result<()> {
+        let (level, req) = group_req(group, interface)?;
+        self.setsockopt(level, libc::MCAST_LEAVE_GROUP, &req)
+    }
+
+    /// Join a source-specific multicast (SSM) channel using the
+    /// `MCAST_JOIN_SOURCE_GROUP` option on this socket.
+    ///
+    /// This function specifies a new multicast channel, the combination of
+    /// `source` and `group`, for this socket to join. Only datagrams sent to
+    /// `group` by `source` are received. `interface` is the index of the
+    /// interface to join the channel on, or 0 to let the system choose an
+    /// appropriate interface.
+    ///
+    /// This is the IPv6 counterpart of [`Socket::join_ssm_v4`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn join_ssm_v6(
+        &self,
+        source: &Ipv6Addr,
+        group: &Ipv6Addr,
+        interface: u32,
+    ) -> io::Result<()> {
+        let req = group_source_req(source, group, interface);
+        self.setsockopt(libc::IPPROTO_IPV6, libc::MCAST_JOIN_SOURCE_GROUP, &req)
+    }
+
+    /// Leave a source-specific multicast (SSM) channel using the
+    /// `MCAST_LEAVE_SOURCE_GROUP` option on this socket.
+    ///
+    /// For more information about this option, see [`join_ssm_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    ///
+    /// [`join_ssm_v6`]: Socket::join_ssm_v6
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn leave_ssm_v6(
+        &self,
+        source: &Ipv6Addr,
+        group: &Ipv6Addr,
+        interface: u32,
+    ) -> io::Result<()> {
+        let req = group_source_req(source, group, interface);
+        self.setsockopt(libc::IPPROTO_IPV6, libc::MCAST_LEAVE_SOURCE_GROUP, &req)
+    }
+
+    /// Get the value of the `IP_MULTICAST_ALL` option on this socket.
+    ///
+    /// For more information about this option, see [`set_multicast_all_v4`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_multicast_all_v4`]: Socket::set_multicast_all_v4
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn multicast_all_v4(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, libc::IP_MULTICAST_ALL)
+            .map(|all| all != 0)
+    }
+
+    /// Set the value of the `IP_MULTICAST_ALL` option on this socket.
+    ///
+    /// If enabled (the default), the socket receives multicast datagrams for
+    /// all groups joined by any socket on the system, as long as the
+    /// destination port matches. If disabled, the socket only receives
+    /// datagrams for the groups it joined itself.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_multicast_all_v4(&self, all: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_IP, libc::IP_MULTICAST_ALL, &(all as c_int))
+    }
+
+    /// Get the value of the `IP_MTU_DISCOVER` option on this socket.
+    ///
+    /// For more information about this option, see [`set_mtu_discover`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_mtu_discover`]: Socket::set_mtu_discover
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mtu_discover(&self) -> io::Result<PmtudMode> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, libc::IP_MTU_DISCOVER)
+            .and_then(PmtudMode::from_raw)
+    }
+
+    /// Set the value of the `IP_MTU_DISCOVER` option on this socket.
+    ///
+    /// Sets the path MTU discovery mode of the socket, which determines
+    /// whether or not the Don't Fragment (DF) flag is set on outgoing
+    /// packets. See [`PmtudMode`] for the possible modes.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_mtu_discover(&self, mode: PmtudMode) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_IP, libc::IP_MTU_DISCOVER, &mode.into_raw())
+    }
+
+    /// Get the value of the `IPV6_MTU_DISCOVER` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_mtu_discover_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_mtu_discover_v6`]: Socket::set_mtu_discover_v6
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mtu_discover_v6(&self) -> io::Result<PmtudMode> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IPV6, libc::IPV6_MTU_DISCOVER)
+            .and_then(PmtudMode::from_raw)
+    }
+
+    /// Set the value of the `IPV6_MTU_DISCOVER` option on this socket.
+    ///
+    /// The IPv6 counterpart of [`set_mtu_discover`]. Note that IPv6 routers
+    /// never fragment packets, so this only affects fragmentation on this
+    /// host.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_mtu_discover`]: Socket::set_mtu_discover
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_mtu_discover_v6(&self, mode: PmtudMode) -> io::Result<()> {
+        self.setsockopt(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_MTU_DISCOVER,
+            &mode.into_raw(),
+        )
+    }
+
+    /// Get the value of the `IP_MTU` option on this socket.
+    ///
+    /// Returns the currently known path MTU of the socket. This is only
+    /// available on connected sockets, i.e. after calling [`connect`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`connect`]: Socket::connect
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mtu(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, libc::IP_MTU)
+            .map(|mtu| mtu as u32)
+    }
+
+    /// Get the value of the `IPV6_MTU` option on this socket.
+    ///
+    /// The IPv6 counterpart of [`mtu`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`mtu`]: Socket::mtu
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mtu_v6(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IPV6, libc::IPV6_MTU)
+            .map(|mtu| mtu as u32)
+    }
+
+    /// Get the value of the `IP_PKTINFO` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_pktinfo`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, iOS, Linux and macOS.
+    ///
+    /// [`set_recv_pktinfo`]: Socket::set_recv_pktinfo
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn recv_pktinfo(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, libc::IP_PKTINFO)
+            .map(|pktinfo| pktinfo != 0)
+    }
+
+    /// Set the value of the `IP_PKTINFO` option on this socket.
+    ///
+    /// If enabled, a `IP_PKTINFO` control message is passed with every
+    /// received datagram, containing the destination address of the packet
+    /// and the interface it was received on. This can be received using
+    /// [`Socket::recv_msg`] and read using [`Cmsg::pktinfo`].
+    ///
+    /// This allows a socket bound to the unspecified address to reply from
+    /// the address the datagram was sent to, using
+    /// [`CmsgBuffer::push_pktinfo`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn set_recv_pktinfo(&self, pktinfo: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_IP, libc::IP_PKTINFO, &(pktinfo as c_int))
+    }
+
+    /// Get the value of the `IPV6_RECVPKTINFO` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_pktinfo_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, iOS, Linux and macOS.
+    ///
+    /// [`set_recv_pktinfo_v6`]: Socket::set_recv_pktinfo_v6
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn recv_pktinfo_v6(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IPV6, libc::IPV6_RECVPKTINFO)
+            .map(|pktinfo| pktinfo != 0)
+    }
+
+    /// Set the value of the `IPV6_RECVPKTINFO` option on this socket.
+    ///
+    /// The IPv6 counterpart of [`set_recv_pktinfo`], passing a
+    /// `IPV6_PKTINFO` control message with every received datagram.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, iOS, Linux and macOS.
+    ///
+    /// [`set_recv_pktinfo`]: Socket::set_recv_pktinfo
+    #[cfg(any(
+        target_os = "android",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn set_recv_pktinfo_v6(&self, pktinfo: bool) -> io::Result<()> {
+        self.setsockopt(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_RECVPKTINFO,
+            &(pktinfo as c_int),
+        )
+    }
+
+    /// Get the value of the `IP_RECVORIGDSTADDR` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_recv_orig_dst_addr`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD and Linux.
+    ///
+    /// [`set_recv_orig_dst_addr`]: Socket::set_recv_orig_dst_addr
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    pub fn recv_orig_dst_addr(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, libc::IP_RECVORIGDSTADDR)
+            .map(|recv| recv != 0)
+    }
+
+    /// Set the value of the `IP_RECVORIGDSTADDR` option on this socket.
+    ///
+    /// If enabled, the original destination address and port of received
+    /// datagrams is returned as `IP_ORIGDSTADDR` control message, see
+    /// [`Cmsg::orig_dst_addr`]. This is mainly useful for transparent proxies
+    /// (see [`set_ip_transparent`]), where the destination of the datagram is
+    /// not an address of the local system.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD and Linux.
+    ///
+    /// [`set_ip_transparent`]: Socket::set_ip_transparent
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    pub fn set_recv_orig_dst_addr(&self, recv: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_IP, libc::IP_RECVORIGDSTADDR, &(recv as c_int))
+    }
+
+    /// Get the value of the `IPV6_RECVORIGDSTADDR` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_recv_orig_dst_addr_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD and Linux.
+    ///
+    /// [`set_recv_orig_dst_addr_v6`]: Socket::set_recv_orig_dst_addr_v6
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    pub fn recv_orig_dst_addr_v6(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IPV6, libc::IPV6_RECVORIGDSTADDR)
+            .map(|recv| recv != 0)
+    }
+
+    /// Set the value of the `IPV6_RECVORIGDSTADDR` option on this socket.
+    ///
+    /// This is the IPv6 equivalent of [`set_recv_orig_dst_addr`], returning
+    /// the address as `IPV6_ORIGDSTADDR` control message.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD and Linux.
+    ///
+    /// [`set_recv_orig_dst_addr`]: Socket::set_recv_orig_dst_addr
+    #[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+    pub fn set_recv_orig_dst_addr_v6(&self, recv: bool) -> io::Result<()> {
+        self.setsockopt(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_RECVORIGDSTADDR,
+            &(recv as c_int),
+        )
+    }
+
+    /// Get the value of the `SO_ORIGINAL_DST` option on this socket.
+    ///
+    /// Returns the original destination address of a connection redirected
+    /// by netfilter, e.g. using an iptables `REDIRECT` or `DNAT` rule, before
+    /// the address was rewritten. This allows transparent proxies to recover
+    /// the address the client tried to connect to.
+    ///
+    /// For IPv6 connections use [`original_dst_ipv6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`original_dst_ipv6`]: Socket::original_dst_ipv6
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn original_dst(&self) -> io::Result<SockAddr> {
+        getsockopt_addr(self.inner, libc::SOL_IP, libc::SO_ORIGINAL_DST)
+    }
+
+    /// Get the value of the `IP6T_SO_ORIGINAL_DST` option on this socket.
+    ///
+    /// This is the IPv6 equivalent of [`original_dst`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`original_dst`]: Socket::original_dst
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn original_dst_ipv6(&self) -> io::Result<SockAddr> {
+        getsockopt_addr(self.inner, libc::SOL_IPV6, libc::IP6T_SO_ORIGINAL_DST)
+    }
+
+    /// Get the value of the `IP_RECVTOS` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_tos`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    ///
+    /// [`set_recv_tos`]: Socket::set_recv_tos
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn recv_tos(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, libc::IP_RECVTOS)
+            .map(|recv_tos| recv_tos != 0)
+    }
+
+    /// Set the value of the `IP_RECVTOS` option on this socket.
+    ///
+    /// If enabled, the type-of-service field of received packets is passed
+    /// as a control message, which can be received using
+    /// [`Socket::recv_msg`] and read using [`Cmsg::tos`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, FreeBSD, iOS, Linux and
+    /// macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn set_recv_tos(&self, recv_tos: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_IP, libc::IP_RECVTOS, &(recv_tos as c_int))
+    }
+
+    /// Get the value of the `IP_RECVTTL` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_ttl`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux and macOS.
+    ///
+    /// [`set_recv_ttl`]: Socket::set_recv_ttl
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn recv_ttl(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, libc::IP_RECVTTL)
+            .map(|recv_ttl| recv_ttl != 0)
+    }
+
+    /// Set the value of the `IP_RECVTTL` option on this socket.
+    ///
+    /// If enabled, the time-to-live field of received packets is passed as a
+    /// control message, which can be received using [`Socket::recv_msg`] and
+    /// read using [`Cmsg::ttl`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux and macOS.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn set_recv_ttl(&self, recv_ttl: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_IP, libc::IP_RECVTTL, &(recv_ttl as c_int))
+    }
+
+    /// Get the value of the `IPV6_RECVHOPLIMIT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_hoplimit_v6`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux and macOS.
+    ///
+    /// [`set_recv_hoplimit_v6`]: Socket::set_recv_hoplimit_v6
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn recv_hoplimit_v6(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IPV6, libc::IPV6_RECVHOPLIMIT)
+            .map(|recv| recv != 0)
+    }
+
+    /// Set the value of the `IPV6_RECVHOPLIMIT` option on this socket.
+    ///
+    /// If enabled, the hop limit field of received packets is returned as
+    /// `IPV6_HOPLIMIT` control message, see [`Cmsg::hop_limit`]. This is the
+    /// IPv6 equivalent of [`set_recv_ttl`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux and macOS.
+    ///
+    /// [`set_recv_ttl`]: Socket::set_recv_ttl
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos"
+    ))]
+    pub fn set_recv_hoplimit_v6(&self, recv: bool) -> io::Result<()> {
+        self.setsockopt(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_RECVHOPLIMIT,
+            &(recv as c_int),
+        )
+    }
+
+    /// Get the value of the `IPV6_FLOWINFO_SEND` option on this socket.
+    ///
+    /// For more information about this option, see [`set_flowinfo_send`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_flowinfo_send`]: Socket::set_flowinfo_send
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn flowinfo_send(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IPV6, libc::IPV6_FLOWINFO_SEND)
+            .map(|send| send != 0)
+    }
+
+    /// Set the value of the `IPV6_FLOWINFO_SEND` option on this socket.
+    ///
+    /// If enabled, the flow information of the destination address (see
+    /// [`SocketAddrV6::flowinfo`]) is used in the packets sent, e.g. using
+    /// [`send_to`] or [`connect`]. The flow label must first be acquired using
+    /// [`request_flow_label`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`SocketAddrV6::flowinfo`]: std::net::SocketAddrV6::flowinfo
+    /// [`send_to`]: Socket::send_to
+    /// [`connect`]: Socket::connect
+    /// [`request_flow_label`]: Socket::request_flow_label
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_flowinfo_send(&self, send: bool) -> io::Result<()> {
+        self.setsockopt(
+            libc::IPPROTO_IPV6,
+            libc::IPV6_FLOWINFO_SEND,
+            &(send as c_int),
+        )
+    }
+
+    /// Get the value of the `IPV6_FLOWINFO` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_flowinfo`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_recv_flowinfo`]: Socket::set_recv_flowinfo
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn recv_flowinfo(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IPV6, libc::IPV6_FLOWINFO)
+            .map(|recv| recv != 0)
+    }
+
+    /// Set the value of the `IPV6_FLOWINFO` option on this socket.
+    ///
+    /// If enabled, the flow information (traffic class and flow label) of
+    /// received packets is returned as `IPV6_FLOWINFO` control message, see
+    /// [`Cmsg::flowinfo`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_recv_flowinfo(&self, recv: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_IPV6, libc::IPV6_FLOWINFO, &(recv as c_int))
+    }
+
+    /// Acquire a lease on a flow label for sending to `dst`, using the
+    /// `IPV6_FLOWLABEL_MGR` option.
+    ///
+    /// If `label` is zero a random, unused, flow label is allocated by the
+    /// kernel. Otherwise `label` (the lower 20 bits) is created if it is not
+    /// in use yet. Returns the flow label, which can be used as flow
+    /// information in the destination address, see [`set_flowinfo_send`].
+    ///
+    /// Note that the flow label is in host byte order, but flow information
+    /// in [`SocketAddrV6`] is in network byte order, i.e. use
+    /// `label.to_be()` as flow information.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_flowinfo_send`]: Socket::set_flowinfo_send
+    /// [`SocketAddrV6`]: std::net::SocketAddrV6
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn request_flow_label(&self, dst: &Ipv6Addr, label: u32) -> io::Result<u32> {
+        let mut req = in6_flowlabel_req {
+            flr_dst: to_in6_addr(dst),
+            flr_label: label.to_be(),
+            flr_action: IPV6_FL_A_GET,
+            flr_share: IPV6_FL_S_EXCL,
+            flr_flags: IPV6_FL_F_CREATE,
+            flr_expires: 0,
+            flr_linger: 0,
+            __flr_pad: 0,
+        };
+        // If the kernel allocated a label it writes it back into `req`, so we
+        // can't use `setsockopt`.
+        syscall!(setsockopt(
+            self.inner,
+            libc::IPPROTO_IPV6,
+            libc::IPV6_FLOWLABEL_MGR,
+            &mut req as *mut _ as *mut _,
+            size_of::<in6_flowlabel_req>() as libc::socklen_t,
+        ))
+        .map(|_| u32::from_be(req.flr_label))
+    }
+
+    /// Release the lease on a flow label acquired using
+    /// [`request_flow_label`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`request_flow_label`]: Socket::request_flow_label
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn release_flow_label(&self, label: u32) -> io::Result<()> {
+        let req = in6_flowlabel_req {
+            flr_dst: to_in6_addr(&Ipv6Addr::UNSPECIFIED),
+            flr_label: label.to_be(),
+            flr_action: IPV6_FL_A_PUT,
+            flr_share: 0,
+            flr_flags: 0,
+            flr_expires: 0,
+            flr_linger: 0,
+            __flr_pad: 0,
+        };
+        self.setsockopt(libc::IPPROTO_IPV6, libc::IPV6_FLOWLABEL_MGR, &req)
+    }
+
+    /// Get the value of the `IP_RECVIF` option on this socket.
+    ///
+    /// For more information about this option, see [`set_recv_interface`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFlyBSD, FreeBSD, iOS, macOS,
+    /// NetBSD and OpenBSD.
+    ///
+    /// [`set_recv_interface`]: Socket::set_recv_interface
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn recv_interface(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, libc::IP_RECVIF)
+            .map(|recv| recv != 0)
+    }
+
+    /// Set the value of the `IP_RECVIF` option on this socket.
+    ///
+    /// If enabled, the interface on which a datagram was received is returned
+    /// as `IP_RECVIF` control message, see [`Cmsg::recv_interface`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFlyBSD, FreeBSD, iOS, macOS,
+    /// NetBSD and OpenBSD.
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn set_recv_interface(&self, recv: bool) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_IP, libc::IP_RECVIF, &(recv as c_int))
+    }
+
+    /// Get the value of the `IP_PORTRANGE` option on this socket.
+    ///
+    /// For more information about this option, see [`set_port_range`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFlyBSD, FreeBSD, iOS, macOS,
+    /// NetBSD and OpenBSD.
+    ///
+    /// [`set_port_range`]: Socket::set_port_range
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn port_range(&self) -> io::Result<PortRange> {
+        self.getsockopt::<c_int>(libc::IPPROTO_IP, IP_PORTRANGE)
+            .and_then(PortRange::from_raw)
+    }
+
+    /// Set the value of the `IP_PORTRANGE` option on this socket.
+    ///
+    /// This determines the range from which an ephemeral port is selected
+    /// when the socket is bound to port 0, or implicitly bound by `connect`.
+    /// See [`PortRange`] for the possible ranges.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFlyBSD, FreeBSD, iOS, macOS,
+    /// NetBSD and OpenBSD.
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn set_port_range(&self, range: PortRange) -> io::Result<()> {
+        self.setsockopt(libc::IPPROTO_IP, IP_PORTRANGE, &range.into_raw())
+    }
+
+    /// Get the value of the `UDP_SEGMENT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_udp_segment`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_udp_segment`]: Socket::set_udp_segment
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn udp_segment(&self) -> io::Result<u16> {
+        self.getsockopt::<c_int>(libc::SOL_UDP, libc::UDP_SEGMENT)
+            .map(|size| size as u16)
+    }
+
+    /// Set the value of the `UDP_SEGMENT` option on this socket.
+    ///
+    /// This enables UDP generic segmentation offload (GSO): buffers larger
+    /// than `size` passed to a single send call are split into multiple
+    /// datagrams of `size` bytes (the last one may be smaller) by the kernel
+    /// or network device. A `size` of zero disables segmentation.
+    ///
+    /// The segment size can also be set per send call using
+    /// [`CmsgBuffer::push_udp_segment`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_udp_segment(&self, size: u16) -> io::Result<()> {
+        self.setsockopt(libc::SOL_UDP, libc::UDP_SEGMENT, &(size as c_int))
+    }
+
+    /// Get the value of the `UDP_GRO` option on this socket.
+    ///
+    /// For more information about this option, see [`set_udp_gro`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_udp_gro`]: Socket::set_udp_gro
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn udp_gro(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::SOL_UDP, libc::UDP_GRO)
+            .map(|gro| gro != 0)
+    }
+
+    /// Set the value of the `UDP_GRO` option on this socket.
+    ///
+    /// This enables UDP generic receive offload (GRO): multiple datagrams of
+    /// the same size received from the same peer may be coalesced and
+    /// returned by a single receive call. The size of the original datagrams
+    /// is returned as `UDP_GRO` control message, see [`Cmsg::udp_gro`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_udp_gro(&self, gro: bool) -> io::Result<()> {
+        self.setsockopt(libc::SOL_UDP, libc::UDP_GRO, &(gro as c_int))
+    }
+
+    /// Get the value of the `SO_INCOMING_CPU` option on this socket.
+    ///
+    /// Returns the CPU that last processed incoming packets for this socket.
+    /// For more information about this option, see [`set_incoming_cpu`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_incoming_cpu`]: Socket::set_incoming_cpu
+    #[cfg(target_os = "linux")]
+    pub fn incoming_cpu(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_INCOMING_CPU)
+            .map(|cpu| cpu as u32)
+    }
+
+    /// Set the value of the `SO_INCOMING_CPU` option on this socket.
+    ///
+    /// Sets the CPU affinity of the socket, used with `SO_REUSEPORT` to steer
+    /// incoming connections to the listener running on the same CPU that
+    /// handled the packets.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn set_incoming_cpu(&self, cpu: u32) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_INCOMING_CPU, &(cpu as c_int))
+    }
+
+    /// Get the value of the `SO_BUSY_POLL` option on this socket.
+    ///
+    /// For more information about this option, see [`set_busy_poll`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_busy_poll`]: Socket::set_busy_poll
+    #[cfg(target_os = "linux")]
+    pub fn busy_poll(&self) -> io::Result<Duration> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_BUSY_POLL)
+            .map(|usecs| Duration::from_micros(usecs as u64))
+    }
+
+    /// Set the value of the `SO_BUSY_POLL` option on this socket.
+    ///
+    /// Sets the approximate time to busy poll on a blocking receive when there
+    /// is no data, in microseconds. A duration of zero disables busy polling.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// Increasing the value above the current one requires the
+    /// `CAP_NET_ADMIN` capability.
+    #[cfg(target_os = "linux")]
+    pub fn set_busy_poll(&self, busy_poll: Duration) -> io::Result<()> {
+        let usecs = min(busy_poll.as_micros(), c_int::MAX as u128) as c_int;
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_BUSY_POLL, &usecs)
+    }
+
+    /// Set the value of the `SO_RCVBUFFORCE` option on this socket.
+    ///
+    /// This is the same as [`set_recv_buffer_size`], but the maximum
+    /// (`net.core.rmem_max`) is not applied.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// Requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// [`set_recv_buffer_size`]: Socket::set_recv_buffer_size
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_recv_buffer_size_force(&self, size: usize) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_RCVBUFFORCE, &(size as c_int))
+    }
+
+    /// Set the value of the `SO_SNDBUFFORCE` option on this socket.
+    ///
+    /// This is the same as [`set_send_buffer_size`], but the maximum
+    /// (`net.core.wmem_max`) is not applied.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// Requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// [`set_send_buffer_size`]: Socket::set_send_buffer_size
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_send_buffer_size_force(&self, size: usize) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_SNDBUFFORCE, &(size as c_int))
+    }
+
+    /// Get the value of the `SO_MAX_PACING_RATE` option on this socket.
+    ///
+    /// For more information about this option, see [`set_max_pacing_rate`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_max_pacing_rate`]: Socket::set_max_pacing_rate
+    #[cfg(target_os = "linux")]
+    pub fn max_pacing_rate(&self) -> io::Result<u64> {
+        // The kernel uses an `unsigned long`, i.e. 32 bits on 32 bit
+        // architectures.
+        let rate = self.getsockopt::<libc::c_ulong>(libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE)?;
+        // Not a no-op on 32 bit architectures.
+        #[allow(clippy::unnecessary_cast)]
+        Ok(rate as u64)
+    }
+
+    /// Set the value of the `SO_MAX_PACING_RATE` option on this socket.
+    ///
+    /// Caps the rate at which data is sent from this socket, in bytes per
+    /// second. Pacing is implemented by the `fq` queueing discipline, or by
+    /// TCP itself. `u64::MAX` means no limit.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn set_max_pacing_rate(&self, rate: u64) -> io::Result<()> {
+        // Not a no-op on 32 bit architectures.
+        #[allow(clippy::unnecessary_cast)]
+        let rate = min(rate, libc::c_ulong::MAX as u64) as libc::c_ulong;
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE, &rate)
+    }
+
+    /// Get the value of the `SO_TXTIME` option on this socket.
+    ///
+    /// Returns the clock and flags, for more information about this option,
+    /// see [`set_txtime`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [`set_txtime`]: Socket::set_txtime
+    #[cfg(target_os = "linux")]
+    pub fn txtime(&self) -> io::Result<(libc::clockid_t, u32)> {
+        self.getsockopt::<libc::sock_txtime>(libc::SOL_SOCKET, libc::SO_TXTIME)
+            .map(|txtime| (txtime.clockid, txtime.flags))
+    }
+
+    /// Set the value of the `SO_TXTIME` option on this socket.
+    ///
+    /// This enables scheduling the transmission of datagrams at a specific
+    /// time, set per datagram using a `SCM_TXTIME` control message (see
+    /// [`CmsgBuffer::push_txtime`]). The time is relative to `clockid`, e.g.
+    /// `CLOCK_TAI` for the `etf` queueing discipline. `flags` is a
+    /// combination of the `SOF_TXTIME_*` flags, e.g.
+    /// `SOF_TXTIME_REPORT_ERRORS` to report dropped datagrams on the error
+    /// queue.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// Using a clock other than `CLOCK_MONOTONIC` requires the
+    /// `CAP_NET_ADMIN` capability.
+    #[cfg(target_os = "linux")]
+    pub fn set_txtime(&self, clockid: libc::clockid_t, flags: u32) -> io::Result<()> {
+        let txtime = libc::sock_txtime { clockid, flags };
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_TXTIME, &txtime)
+    }
+
+    /// Get the value of the `SO_COOKIE` option on this socket.
+    ///
+    /// The cookie is a unique identifier of the socket assigned by the
+    /// kernel, which can be used to correlate the socket with e.g. eBPF maps
+    /// or the output of `ss`.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    #[cfg(target_os = "linux")]
+    pub fn cookie(&self) -> io::Result<u64> {
+        self.getsockopt::<u64>(libc::SOL_SOCKET, libc::SO_COOKIE)
+    }
+
+    /// Get the value of the `SO_INCOMING_NAPI_ID` option on this socket.
+    ///
+    /// Returns the ID of the NAPI context (roughly, the receive queue of the
+    /// network device) that last received a packet for this socket, or zero
+    /// if unknown. This can be used to group sockets per receive queue, e.g.
+    /// when using [busy polling].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Linux.
+    ///
+    /// [busy polling]: Socket::set_busy_poll
+    #[cfg(target_os = "linux")]
+    pub fn incoming_napi_id(&self) -> io::Result<u32> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_INCOMING_NAPI_ID)
+            .map(|id| id as u32)
+    }
+
+    /// Get the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_reuse_port`].
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on illumos and Solaris.
+    ///
+    /// [`set_reuse_port`]: Socket::set_reuse_port
+    #[cfg(not(any(target_os = "illumos", target_os = "solaris")))]
+    pub fn reuse_port(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_REUSEPORT)
+            .map(|reuse| reuse != 0)
+    }
+
+    /// Set the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// This indicates that further calls to `bind` may allow reuse of local
+    /// addresses. For IPv4 sockets this means that a socket may bind even when
+    /// there's a socket already listening on this port.
+    ///
+    /// # Notes
+    ///
+    /// This function is not available on illumos and Solaris, which don't
+    /// support `SO_REUSEPORT`. On these platforms [`set_reuse_address`] allows
+    /// multiple sockets to bind to the same port, as long as the addresses
+    /// differ, and [`set_exclusive_bind`] can be used to prevent it.
+    ///
+    /// [`set_reuse_address`]: Socket::set_reuse_address
+    /// [`set_exclusive_bind`]: Socket::set_exclusive_bind
+    #[cfg(not(any(target_os = "illumos", target_os = "solaris")))]
+    pub fn set_reuse_port(&self, reuse: bool) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_REUSEPORT, &(reuse as c_int))
+    }
+
+    /// Get the value of the `SO_REUSEPORT_LB` option on this socket.
+    ///
+    /// For more information about this option, see [`set_reuse_port_lb`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on FreeBSD.
+    ///
+    /// [`set_reuse_port_lb`]: Socket::set_reuse_port_lb
+    #[cfg(target_os = "freebsd")]
+    pub fn reuse_port_lb(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_REUSEPORT_LB)
+            .map(|reuse| reuse != 0)
+    }
+
+    /// Set the value of the `SO_REUSEPORT_LB` option on this socket.
+    ///
+    /// This allows multiple sockets to bind to the same address and port, like
+    /// `SO_REUSEPORT`, but incoming connections (or datagrams) are distributed
+    /// across the sockets in the group, similar to `SO_REUSEPORT` on Linux.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on FreeBSD.
+    #[cfg(target_os = "freebsd")]
+    pub fn set_reuse_port_lb(&self, reuse: bool) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_REUSEPORT_LB, &(reuse as c_int))
+    }
+
+    /// Get the value of the `SO_EXCLBIND` option on this socket.
+    ///
+    /// For more information about this option, see [`set_exclusive_bind`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on illumos and Solaris.
+    ///
+    /// [`set_exclusive_bind`]: Socket::set_exclusive_bind
+    #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+    pub fn exclusive_bind(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_EXCLBIND)
+            .map(|exclusive| exclusive != 0)
+    }
+
+    /// Set the value of the `SO_EXCLBIND` option on this socket.
+    ///
+    /// When enabled no other socket can bind to the same port, even when
+    /// `SO_REUSEADDR` is set on either socket. This option must be set before
+    /// calling `bind` on the socket.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on illumos and Solaris.
+    #[cfg(any(target_os = "illumos", target_os = "solaris"))]
+    pub fn set_exclusive_bind(&self, exclusive: bool) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_EXCLBIND, &(exclusive as c_int))
+    }
+
+    /// Get the value of the `SO_USER_COOKIE` option on this socket.
+    ///
+    /// For more information about this option, see [`set_user_cookie`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on FreeBSD.
+    ///
+    /// [`set_user_cookie`]: Socket::set_user_cookie
+    #[cfg(target_os = "freebsd")]
+    pub fn user_cookie(&self) -> io::Result<u32> {
+        self.getsockopt::<u32>(libc::SOL_SOCKET, libc::SO_USER_COOKIE)
+    }
+
+    /// Set the value of the `SO_USER_COOKIE` option on this socket.
+    ///
+    /// Attaches an arbitrary cookie to the socket, which can be matched by
+    /// `ipfw(8)` rules (e.g. to pass the socket's traffic to a specific
+    /// `dummynet(4)` pipe).
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on FreeBSD.
+    #[cfg(target_os = "freebsd")]
+    pub fn set_user_cookie(&self, cookie: u32) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_USER_COOKIE, &cookie)
+    }
+
+    /// Get the value of the `SO_NOSIGPIPE` option on this socket.
+    ///
+    /// For more information about this option, see [`set_nosigpipe`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFly BSD, FreeBSD, iOS, macOS
+    /// and NetBSD.
+    ///
+    /// [`set_nosigpipe`]: Socket::set_nosigpipe
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd"
+    ))]
+    pub fn nosigpipe(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_NOSIGPIPE)
+            .map(|nosigpipe| nosigpipe != 0)
+    }
+
+    /// Set the value of the `SO_NOSIGPIPE` option on this socket.
+    ///
+    /// If set, writing to a socket whose peer has closed the connection
+    /// returns an `EPIPE` error instead of raising the `SIGPIPE` signal, which
+    /// terminates the process by default. This is useful on platforms where
+    /// `MSG_NOSIGNAL` isn't available.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on DragonFly BSD, FreeBSD, iOS, macOS
+    /// and NetBSD.
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "netbsd"
+    ))]
+    pub fn set_nosigpipe(&self, nosigpipe: bool) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_NOSIGPIPE, &(nosigpipe as c_int))
+    }
+
+    /// Returns the credentials of the process on the other side of this Unix
+    /// domain socket.
+    ///
+    /// For connected stream sockets (and socket pairs) these are the
+    /// credentials at the time of calling `connect` or `socketpair`.
+    ///
+    /// # Notes
+    ///
+    /// On Android and Linux this uses the `SO_PEERCRED` option, on iOS and
+    /// macOS `getpeereid(3)` and `LOCAL_PEERPID`, and `getpeereid(3)` on the
+    /// other BSDs.
+    ///
+    /// This function is only available on Android, DragonFlyBSD, FreeBSD,
+    /// iOS, Linux, macOS, NetBSD and OpenBSD.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn peer_cred(&self) -> io::Result<UCred> {
+        peer_cred(self.inner)
+    }
+
+    /// Get the value of the `SO_PEERSEC` option on this socket.
+    ///
+    /// Returns the security context (e.g. the SELinux or SMACK label) of the
+    /// process on the other side of this Unix domain socket. If no Linux
+    /// Security Module that supports this option is loaded this returns an
+    /// `ENOPROTOOPT` error.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn peer_security_context(&self) -> io::Result<Vec<u8>> {
+        let mut context = getsockopt_bytes(self.inner, libc::SOL_SOCKET, libc::SO_PEERSEC, 64)?;
+        // Some security modules include the null terminator, others don't.
+        if context.last() == Some(&0) {
+            context.pop();
+        }
+        Ok(context)
+    }
+
+    /// Get the value of the `SO_PEERGROUPS` option on this socket.
+    ///
+    /// Returns the supplementary group ids of the process on the other side
+    /// of this Unix domain socket, at the time of calling `connect` or
+    /// `socketpair`. See [`peer_cred`] for the user and primary group id.
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux, and requires
+    /// Linux 4.13 or later.
+    ///
+    /// [`peer_cred`]: Socket::peer_cred
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn peer_groups(&self) -> io::Result<Vec<libc::gid_t>> {
+        const GID_SIZE: usize = size_of::<libc::gid_t>();
+        let buf = getsockopt_bytes(self.inner, libc::SOL_SOCKET, SO_PEERGROUPS, 16 * GID_SIZE)?;
+        // NOTE: `buf` isn't necessarily aligned for `gid_t`.
+        let groups = buf
+            .chunks_exact(GID_SIZE)
+            .map(|gid| unsafe { std::ptr::read_unaligned(gid.as_ptr().cast()) })
+            .collect();
+        Ok(groups)
+    }
+
+    /// Get the value of the `SO_PASSCRED` option on this socket.
+    ///
+    /// For more information about this option, see [`set_passcred`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux.
+    ///
+    /// [`set_passcred`]: Socket::set_passcred
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn passcred(&self) -> io::Result<bool> {
+        self.getsockopt::<c_int>(libc::SOL_SOCKET, libc::SO_PASSCRED)
+            .map(|passcred| passcred != 0)
+    }
+
+    /// Set the value of the `SO_PASSCRED` option on this socket.
+    ///
+    /// Enables the receiving of `SCM_CREDENTIALS` control messages on this
+    /// Unix domain socket, containing the credentials of the sending process.
+    /// The credentials can be read using [`Socket::recv_msg`] and
+    /// [`Cmsg::credentials`].
+    ///
+    /// # Notes
+    ///
+    /// This function is only available on Android and Linux. On DragonFlyBSD
+    /// and FreeBSD this option isn't needed, instead the sender adds a
+    /// `SCM_CREDS` control message using [`CmsgBuffer::push_creds`].
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_passcred(&self, passcred: bool) -> io::Result<()> {
+        self.setsockopt(libc::SOL_SOCKET, libc::SO_PASSCRED, &(passcred as c_int))
+    }
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn group_req(group: &SockAddr, interface: u32) -> io::Result<(c_int, libc::group_req)> {
+    let level = match c_int::from(group.family()) {
+        libc::AF_INET => libc::IPPROTO_IP,
+        libc::AF_INET6 => libc::IPPROTO_IPV6,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "multicast group must be an IPv4 or IPv6 address",
+            ))
+        }
+    };
+    let mut req: libc::group_req = unsafe { mem::zeroed() };
+    req.gr_interface = interface;
+    // Safety: `SockAddr` always contains a valid `sockaddr_storage`.
+    req.gr_group = unsafe { *group.as_ptr() };
+    Ok((level, req))
+}
+
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn group_source_req(source: &Ipv6Addr, group: &Ipv6Addr, interface: u32) -> libc::group_source_req {
+    let mut req: libc::group_source_req = unsafe { mem::zeroed() };
+    req.gsr_interface = interface;
+    req.gsr_group = sockaddr_in6(group);
+    req.gsr_source = sockaddr_in6(source);
+    req
+}
+
+/// Returns `addr` as `sockaddr_in6` stored in a `sockaddr_storage`.
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn sockaddr_in6(addr: &Ipv6Addr) -> libc::sockaddr_storage {
+    let addr = SockAddr::from(std::net::SocketAddrV6::new(*addr, 0, 0, 0));
+    // Safety: `SockAddr` always contains a valid `sockaddr_storage`.
+    unsafe { *addr.as_ptr() }
+}
+
+/// `struct in6_flowlabel_req`, not defined in `libc`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+struct in6_flowlabel_req {
+    flr_dst: libc::in6_addr,
+    flr_label: u32,
+    flr_action: u8,
+    flr_share: u8,
+    flr_flags: u16,
+    flr_expires: u16,
+    flr_linger: u16,
+    __flr_pad: u32,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_FL_A_GET: u8 = 0;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_FL_A_PUT: u8 = 1;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_FL_F_CREATE: u16 = 1;
+#[cfg(any(target_os = "android", target_os = "linux"))]
+const IPV6_FL_S_EXCL: u8 = 1;
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn peer_cred(sockfd: RawSocket) -> io::Result<UCred> {
+    getsockopt::<libc::ucred>(sockfd, libc::SOL_SOCKET, libc::SO_PEERCRED).map(|cred| UCred {
+        pid: Some(cred.pid),
+        uid: cred.uid,
+        gid: cred.gid,
+    })
+}
+
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn peer_cred(sockfd: RawSocket) -> io::Result<UCred> {
+    let mut uid = 0;
+    let mut gid = 0;
+    syscall!(getpeereid(sockfd, &mut uid, &mut gid))?;
+    // `SOL_LOCAL` is zero, but not defined in `libc`.
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    let pid = Some(getsockopt::<libc::pid_t>(sockfd, 0, libc::LOCAL_PEERPID)?);
+    #[cfg(not(any(target_os = "ios", target_os = "macos")))]
+    let pid = None;
+    Ok(UCred { pid, uid, gid })
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn splice(fd_in: RawFd, fd_out: RawFd, len: usize, flags: c_int) -> io::Result<usize> {
+    syscall!(splice(
+        fd_in,
+        std::ptr::null_mut(),
+        fd_out,
+        std::ptr::null_mut(),
+        min(len, MAX_BUF_LEN),
+        flags as _,
+    ))
+    .map(|n| n as usize)
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn sendfile(
+    file: RawFd,
+    sockfd: RawSocket,
+    mut offset: libc::off_t,
+    len: usize,
+) -> io::Result<usize> {
+    let len = min(len, MAX_BUF_LEN);
+    syscall!(sendfile(sockfd, file, &mut offset, len)).map(|n| n as usize)
+}
+
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn sendfile(file: RawFd, sockfd: RawSocket, offset: libc::off_t, len: usize) -> io::Result<usize> {
+    // On macOS `len` is a value-result argument: it's the number of bytes to
+    // send and is set to the number of bytes actually send.
+    let mut len = min(len, MAX_BUF_LEN) as libc::off_t;
+    match syscall!(sendfile(
+        file,
+        sockfd,
+        offset,
+        &mut len,
+        std::ptr::null_mut(),
+        0,
+    )) {
+        Ok(_) => Ok(len as usize),
+        // Calls interrupted by a signal or on a non-blocking socket can still
+        // send part of the data.
+        Err(ref err) if len != 0 && is_partial_error(err) => Ok(len as usize),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(target_os = "freebsd")]
+fn sendfile(file: RawFd, sockfd: RawSocket, offset: libc::off_t, len: usize) -> io::Result<usize> {
+    let mut sent: libc::off_t = 0;
+    match syscall!(sendfile(
+        file,
+        sockfd,
+        offset,
+        min(len, MAX_BUF_LEN),
+        std::ptr::null_mut(),
+        &mut sent,
+        0,
+    )) {
+        Ok(_) => Ok(sent as usize),
+        // Calls interrupted by a signal or on a non-blocking socket can still
+        // send part of the data.
+        Err(ref err) if sent != 0 && is_partial_error(err) => Ok(sent as usize),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "ios", target_os = "macos"))]
+fn is_partial_error(err: &io::Error) -> bool {
+    matches!(err.raw_os_error(), Some(libc::EAGAIN) | Some(libc::EINTR))
+}
+
+impl From<UnixStream> for Socket {
+    fn from(socket: UnixStream) -> Socket {
+        unsafe { Socket::from_raw_fd(socket.into_raw_fd()) }
+    }
+}
+
+impl TryFrom<Socket> for UnixStream {
+    type Error = io::Error;
+
+    /// Converts the socket into a `UnixStream`, returns an error if the socket's
+    /// domain or type don't match.
+    fn try_from(socket: Socket) -> io::Result<UnixStream> {
+        check_socket(&socket, &[Domain::UNIX], Type::STREAM)?;
+        Ok(unsafe { UnixStream::from_raw_fd(socket.into_raw_fd()) })
+    }
+}
+
+impl From<UnixListener> for Socket {
+    fn from(socket: UnixListener) -> Socket {
+        unsafe { Socket::from_raw_fd(socket.into_raw_fd()) }
+    }
+}
+
+impl TryFrom<Socket> for UnixListener {
+    type Error = io::Error;
+
+    /// Converts the socket into a `UnixListener`, returns an error if the socket's
+    /// domain or type don't match.
+    fn try_from(socket: Socket) -> io::Result<UnixListener> {
+        check_socket(&socket, &[Domain::UNIX], Type::STREAM)?;
+        Ok(unsafe { UnixListener::from_raw_fd(socket.into_raw_fd()) })
+    }
+}
+
+impl From<UnixDatagram> for Socket {
+    fn from(socket: UnixDatagram) -> Socket {
+        unsafe { Socket::from_raw_fd(socket.into_raw_fd()) }
+    }
+}
+
+impl TryFrom<Socket> for UnixDatagram {
+    type Error = io::Error;
+
+    /// Converts the socket into a `UnixDatagram`, returns an error if the socket's
+    /// domain or type don't match.
+    fn try_from(socket: Socket) -> io::Result<UnixDatagram> {
+        check_socket(&socket, &[Domain::UNIX], Type::DGRAM)?;
+        Ok(unsafe { UnixDatagram::from_raw_fd(socket.into_raw_fd()) })
+    }
+}
+
+impl FromRawFd for Socket {
+    unsafe fn from_raw_fd(fd: RawFd) -> Socket {
+        Socket { inner: fd }
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.inner;
+        mem::forget(self);
+        fd
+    }
+}
+
+impl AsFd for Socket {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        // Safety: the file descriptor is valid for the lifetime of `self`.
+        unsafe { BorrowedFd::borrow_raw(self.inner) }
+    }
+}
+
+impl From<OwnedFd> for Socket {
+    fn from(fd: OwnedFd) -> Socket {
+        Socket {
+            inner: fd.into_raw_fd(),
+        }
+    }
+}
+
+impl From<Socket> for OwnedFd {
+    fn from(socket: Socket) -> OwnedFd {
+        // Safety: `Socket` owns the file descriptor.
+        unsafe { OwnedFd::from_raw_fd(socket.into_raw_fd()) }
     }
 }
 
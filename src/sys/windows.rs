@@ -9,30 +9,36 @@
 // except according to those terms.
 
 use std::cmp;
+use std::convert::TryFrom;
+use std::ffi::CStr;
 use std::fmt;
+use std::fs::File;
 use std::io;
 use std::io::{Read, Write};
 use std::mem;
 use std::net::Shutdown;
 use std::net::{self, Ipv4Addr, Ipv6Addr};
+use std::os::windows::io::{AsSocket, BorrowedSocket, OwnedSocket};
 use std::os::windows::prelude::*;
 use std::ptr;
 use std::sync::Once;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use winapi::ctypes::{c_char, c_long, c_ulong};
 use winapi::shared::in6addr::*;
 use winapi::shared::inaddr::*;
-use winapi::shared::minwindef::DWORD;
+use winapi::shared::minwindef::{BOOL, DWORD};
+use winapi::shared::netioapi;
 use winapi::shared::ntdef::{HANDLE, ULONG};
 use winapi::shared::ws2def::*;
 use winapi::shared::ws2ipdef::*;
-use winapi::um::handleapi::SetHandleInformation;
+use winapi::um::handleapi::{GetHandleInformation, SetHandleInformation};
+use winapi::um::mswsock::{TransmitFile, TRANSMIT_FILE_BUFFERS};
 use winapi::um::processthreadsapi::GetCurrentProcessId;
 use winapi::um::winbase::INFINITE;
 use winapi::um::winsock2 as sock;
 
-use crate::SockAddr;
+use crate::{Domain, Protocol, SockAddr, TcpKeepalive, Type};
 
 const HANDLE_FLAG_INHERIT: DWORD = 0x00000001;
 const MSG_PEEK: c_int = 0x2;
@@ -40,11 +46,16 @@ const SD_BOTH: c_int = 2;
 const SD_RECEIVE: c_int = 0;
 const SD_SEND: c_int = 1;
 const SIO_KEEPALIVE_VALS: DWORD = 0x98000004;
+const SIO_UDP_CONNRESET: DWORD = 0x9800000C;
 const WSA_FLAG_OVERLAPPED: DWORD = 0x01;
+const WSA_FLAG_NO_HANDLE_INHERIT: DWORD = 0x80;
 
 // Used in conversions for `Domain`, `Type` and `Protocol`.
 #[allow(non_camel_case_types)]
 pub(crate) type c_int = winapi::ctypes::c_int;
+// Used in `Interest` and `Readiness`.
+#[allow(non_camel_case_types)]
+pub(crate) type c_short = winapi::ctypes::c_short;
 
 // Used in `Domain`.
 pub(crate) use winapi::shared::ws2def::{AF_INET, AF_INET6};
@@ -55,6 +66,49 @@ pub(crate) const IPPROTO_ICMP: c_int = winapi::shared::ws2def::IPPROTO_ICMP as c
 pub(crate) const IPPROTO_ICMPV6: c_int = winapi::shared::ws2def::IPPROTO_ICMPV6 as c_int;
 pub(crate) const IPPROTO_TCP: c_int = winapi::shared::ws2def::IPPROTO_TCP as c_int;
 pub(crate) const IPPROTO_UDP: c_int = winapi::shared::ws2def::IPPROTO_UDP as c_int;
+// Used in `Socket`.
+pub(crate) use winapi::shared::ws2def::{
+    IPPROTO_IP, SOL_SOCKET, SO_ERROR, SO_KEEPALIVE, SO_RCVBUF, SO_RCVTIMEO, SO_REUSEADDR,
+    SO_SNDBUF, SO_SNDTIMEO, SO_TYPE, TCP_NODELAY,
+};
+pub(crate) const IPPROTO_IPV6: c_int = winapi::shared::ws2def::IPPROTO_IPV6 as c_int;
+pub(crate) use winapi::shared::ws2def::SO_LINGER;
+pub(crate) use winapi::shared::ws2ipdef::{
+    IPV6_ADD_MEMBERSHIP, IPV6_DROP_MEMBERSHIP, IPV6_MULTICAST_HOPS, IPV6_MULTICAST_IF,
+    IPV6_MULTICAST_LOOP, IPV6_UNICAST_HOPS, IPV6_V6ONLY, IP_ADD_MEMBERSHIP, IP_DROP_MEMBERSHIP,
+    IP_MULTICAST_IF, IP_MULTICAST_LOOP, IP_MULTICAST_TTL, IP_TTL,
+};
+pub(crate) use winapi::shared::ws2ipdef::{IPV6_DONTFRAG, IPV6_TCLASS, IP_TOS};
+// Not defined in `winapi`, see `ws2ipdef.h`.
+pub(crate) const TCP_FASTOPEN: c_int = 15;
+// Used in `Socket::linger`.
+pub(crate) use winapi::um::winsock2::linger;
+// Used in `Socket::join_multicast_v4`.
+pub(crate) use winapi::shared::ws2ipdef::IP_MREQ as IpMreq;
+// Used in `Socket::join_ssm_v4`.
+pub(crate) use winapi::shared::ws2ipdef::{
+    IP_ADD_SOURCE_MEMBERSHIP, IP_DROP_SOURCE_MEMBERSHIP, IP_MREQ_SOURCE as IpMreqSource,
+};
+// Used in `Socket::send` and `Socket::send_to`, Windows doesn't raise
+// `SIGPIPE`.
+pub(crate) const MSG_NOSIGNAL: c_int = 0x0;
+// Used in `Socket::set_unicast_if_v4` and `Socket::set_unicast_if_v6`.
+pub(crate) use winapi::shared::ws2ipdef::IPV6_UNICAST_IF;
+// Not defined in `winapi`, see `ws2ipdef.h`.
+pub(crate) const IP_UNICAST_IF: c_int = 31;
+// Used in `Socket::multicast_if_v4`.
+pub(crate) use winapi::shared::inaddr::in_addr;
+// Used in `Socket::join_multicast_v6`.
+pub(crate) use winapi::shared::ws2ipdef::IPV6_MREQ as Ipv6Mreq;
+// Used in `Socket::set_multicast_loop_v4` and `Socket::set_multicast_ttl_v4`.
+pub(crate) type IpV4MultiCastType = DWORD;
+// Used in `Socket::recv_exact`.
+pub(crate) use winapi::um::winsock2::MSG_WAITALL;
+// Used in `RecvFlags`.
+pub(crate) const MSG_TRUNC: c_int = winapi::shared::ws2def::MSG_TRUNC as c_int;
+pub(crate) const MSG_CTRUNC: c_int = winapi::shared::ws2def::MSG_CTRUNC as c_int;
+// Used in `Interest` and `Readiness`.
+pub(crate) use winapi::um::winsock2::{POLLERR, POLLHUP, POLLIN, POLLOUT};
 
 #[repr(C)]
 struct tcp_keepalive {
@@ -78,6 +132,508 @@ fn last_error() -> io::Error {
     io::Error::from_raw_os_error(unsafe { sock::WSAGetLastError() })
 }
 
+pub(crate) fn setsockopt<T>(
+    socket: RawSocket,
+    level: c_int,
+    optname: c_int,
+    opt: &T,
+) -> io::Result<()> {
+    let res = unsafe {
+        sock::setsockopt(
+            socket as sock::SOCKET,
+            level,
+            optname,
+            opt as *const _ as *const c_char,
+            mem::size_of::<T>() as c_int,
+        )
+    };
+    if res == sock::SOCKET_ERROR {
+        Err(last_error())
+    } else {
+        Ok(())
+    }
+}
+
+pub(crate) fn getsockopt<T>(socket: RawSocket, level: c_int, optname: c_int) -> io::Result<T> {
+    let mut optval: mem::MaybeUninit<T> = mem::MaybeUninit::uninit();
+    let mut optlen = mem::size_of::<T>() as c_int;
+    let res = unsafe {
+        sock::getsockopt(
+            socket as sock::SOCKET,
+            level,
+            optname,
+            optval.as_mut_ptr() as *mut c_char,
+            &mut optlen,
+        )
+    };
+    if res == sock::SOCKET_ERROR {
+        Err(last_error())
+    } else {
+        // Safe because `getsockopt` initialised the value for us.
+        debug_assert_eq!(optlen as usize, mem::size_of::<T>());
+        Ok(unsafe { optval.assume_init() })
+    }
+}
+
+/// Returns the domain of the socket, or `None` if it can't be determined.
+pub(crate) fn socket_domain(socket: RawSocket) -> Option<Domain> {
+    // `SO_PROTOCOL_INFOW` also works for sockets that aren't bound yet.
+    getsockopt::<sock::WSAPROTOCOL_INFOW>(socket, sock::SOL_SOCKET, sock::SO_PROTOCOL_INFOW)
+        .ok()
+        .map(|info| Domain::from(info.iAddressFamily))
+}
+
+pub(crate) fn timeout_opt(
+    socket: RawSocket,
+    level: c_int,
+    optname: c_int,
+) -> io::Result<Option<Duration>> {
+    getsockopt::<DWORD>(socket, level, optname).map(|timeout| {
+        if timeout == 0 {
+            None
+        } else {
+            let secs = timeout / 1000;
+            let nanos = (timeout % 1000) * 1_000_000;
+            Some(Duration::new(secs as u64, nanos as u32))
+        }
+    })
+}
+
+pub(crate) fn set_timeout_opt(
+    socket: RawSocket,
+    level: c_int,
+    optname: c_int,
+    duration: Option<Duration>,
+) -> io::Result<()> {
+    let timeout = match duration {
+        Some(duration) if duration.as_secs() == 0 && duration.subsec_nanos() == 0 => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "cannot set a 0 duration timeout",
+            ));
+        }
+        Some(duration) => {
+            let timeout = duration
+                .as_secs()
+                .saturating_mul(1_000)
+                .saturating_add(duration.subsec_nanos() as u64 / 1_000_000);
+            // Don't round sub-millisecond timeouts down to no timeout, nor
+            // round large timeouts to the special value `INFINITE`.
+            cmp::max(cmp::min(timeout, (INFINITE - 1) as u64), 1) as DWORD
+        }
+        None => 0,
+    };
+    setsockopt(socket, level, optname, &timeout)
+}
+
+pub(crate) fn set_tcp_keepalive(socket: RawSocket, keepalive: &TcpKeepalive) -> io::Result<()> {
+    // Windows always sets both the time and interval, using the defaults
+    // documented by Microsoft if not provided.
+    let time = keepalive.time.unwrap_or(Duration::from_secs(2 * 60 * 60));
+    let interval = keepalive.interval.unwrap_or(Duration::from_secs(1));
+    let keepalive = tcp_keepalive {
+        onoff: 1,
+        keepalivetime: into_ms(time),
+        keepaliveinterval: into_ms(interval),
+    };
+    wsa_ioctl_in(socket, SIO_KEEPALIVE_VALS, &keepalive)
+}
+
+/// Calls `WSAIoctl` with `input` as input buffer and without output buffer.
+fn wsa_ioctl_in<T>(socket: RawSocket, control_code: DWORD, input: &T) -> io::Result<()> {
+    let mut out = 0;
+    let res = unsafe {
+        sock::WSAIoctl(
+            socket as sock::SOCKET,
+            control_code,
+            input as *const T as *mut _,
+            mem::size_of::<T>() as DWORD,
+            ptr::null_mut(),
+            0,
+            &mut out,
+            ptr::null_mut(),
+            None,
+        )
+    };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(last_error())
+    }
+}
+
+pub(crate) fn dontfrag_v4(socket: RawSocket) -> io::Result<bool> {
+    getsockopt::<c_int>(socket, IPPROTO_IP, IP_DONTFRAGMENT).map(|dontfrag| dontfrag != 0)
+}
+
+pub(crate) fn set_dontfrag_v4(socket: RawSocket, dontfrag: bool) -> io::Result<()> {
+    setsockopt(socket, IPPROTO_IP, IP_DONTFRAGMENT, &(dontfrag as c_int))
+}
+
+fn into_ms(duration: Duration) -> c_ulong {
+    let ms = duration
+        .as_secs()
+        .saturating_mul(1_000)
+        .saturating_add(duration.subsec_nanos() as u64 / 1_000_000);
+    cmp::min(ms, c_ulong::MAX as u64) as c_ulong
+}
+
+pub(crate) fn to_in_addr(addr: &Ipv4Addr) -> IN_ADDR {
+    let mut s_un: in_addr_S_un = unsafe { mem::zeroed() };
+    // `S_un` is stored as BE on all machines, and the array is in BE order. So
+    // the native endian conversion method is used so that it's never swapped.
+    unsafe { *(s_un.S_addr_mut()) = u32::from_ne_bytes(addr.octets()) };
+    IN_ADDR { S_un: s_un }
+}
+
+pub(crate) fn from_in_addr(addr: in_addr) -> Ipv4Addr {
+    Ipv4Addr::from(unsafe { *addr.S_un.S_addr() }.to_ne_bytes())
+}
+
+pub(crate) fn try_clone(socket: RawSocket) -> io::Result<crate::Socket> {
+    let mut info: sock::WSAPROTOCOL_INFOW = unsafe { mem::zeroed() };
+    let res = unsafe {
+        sock::WSADuplicateSocketW(socket as sock::SOCKET, GetCurrentProcessId(), &mut info)
+    };
+    if res != 0 {
+        return Err(last_error());
+    }
+
+    let socket = unsafe {
+        sock::WSASocketW(
+            info.iAddressFamily,
+            info.iSocketType,
+            info.iProtocol,
+            &mut info,
+            0,
+            WSA_FLAG_OVERLAPPED | WSA_FLAG_NO_HANDLE_INHERIT,
+        )
+    };
+    match socket {
+        sock::INVALID_SOCKET => Err(last_error()),
+        socket => Ok(crate::Socket {
+            inner: socket as RawSocket,
+        }),
+    }
+}
+
+pub(crate) fn poll(
+    socket: RawSocket,
+    events: c_short,
+    timeout: Option<Duration>,
+) -> io::Result<c_short> {
+    let mut fd_array = sock::WSAPOLLFD {
+        fd: socket as sock::SOCKET,
+        events,
+        revents: 0,
+    };
+    let timeout = match timeout {
+        Some(timeout) if timeout.is_zero() => 0,
+        // Round up to at least 1 millisecond, `WSAPoll` would otherwise
+        // return immediately.
+        Some(timeout) => timeout.as_millis().clamp(1, c_int::MAX as u128) as c_int,
+        None => -1,
+    };
+    match unsafe { sock::WSAPoll(&mut fd_array, 1, timeout) } {
+        sock::SOCKET_ERROR => Err(last_error()),
+        0 => Err(io::ErrorKind::TimedOut.into()),
+        _ => Ok(fd_array.revents),
+    }
+}
+
+pub(crate) fn poll_connect(socket: &crate::Socket, timeout: Duration) -> io::Result<()> {
+    if timeout == Duration::from_secs(0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot set a 0 duration timeout",
+        ));
+    }
+
+    let start = Instant::now();
+    let mut fd_array = sock::WSAPOLLFD {
+        fd: socket.inner as sock::SOCKET,
+        events: sock::POLLRDNORM | sock::POLLWRNORM,
+        revents: 0,
+    };
+
+    loop {
+        let elapsed = start.elapsed();
+        if elapsed >= timeout {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+
+        // Round up to at least 1 millisecond, `WSAPoll` would otherwise
+        // return immediately.
+        let timeout = (timeout - elapsed).as_millis();
+        let timeout = timeout.clamp(1, c_int::MAX as u128) as c_int;
+
+        match unsafe { sock::WSAPoll(&mut fd_array, 1, timeout) } {
+            sock::SOCKET_ERROR => return Err(last_error()),
+            0 => return Err(io::ErrorKind::TimedOut.into()),
+            _ => {
+                // Error or hang up indicates an error (or failure to connect).
+                if (fd_array.revents & sock::POLLERR) != 0
+                    || (fd_array.revents & sock::POLLHUP) != 0
+                {
+                    match socket.take_error() {
+                        Ok(Some(err)) | Err(err) => return Err(err),
+                        Ok(None) => return Err(io::Error::other("no error set after POLLHUP")),
+                    }
+                }
+                return Ok(());
+            }
+        }
+    }
+}
+
+pub(crate) fn set_nonblocking(socket: RawSocket, nonblocking: bool) -> io::Result<()> {
+    let mut nonblocking = nonblocking as c_ulong;
+    let res = unsafe {
+        sock::ioctlsocket(
+            socket as sock::SOCKET,
+            sock::FIONBIO as c_int,
+            &mut nonblocking,
+        )
+    };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(last_error())
+    }
+}
+
+pub(crate) fn if_nametoindex(name: &CStr) -> io::Result<u32> {
+    match unsafe { netioapi::if_nametoindex(name.as_ptr()) } {
+        // `if_nametoindex` doesn't set an error.
+        0 => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "network interface not found",
+        )),
+        index => Ok(index),
+    }
+}
+
+pub(crate) fn if_indextoname(index: u32) -> io::Result<String> {
+    // `IF_NAMESIZE`, not defined in `winapi`, see `netioapi.h`.
+    let mut buf = [0 as c_char; 257];
+    let name = unsafe { netioapi::if_indextoname(index, buf.as_mut_ptr()) };
+    if name.is_null() {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "network interface not found",
+        ))
+    } else {
+        // Safety: `if_indextoname` wrote a null terminated string to `buf`.
+        let name = unsafe { CStr::from_ptr(name) };
+        Ok(name.to_string_lossy().into_owned())
+    }
+}
+
+pub(crate) fn bytes_available(socket: RawSocket) -> io::Result<usize> {
+    let mut available: c_ulong = 0;
+    let res = unsafe {
+        sock::ioctlsocket(
+            socket as sock::SOCKET,
+            sock::FIONREAD as c_int,
+            &mut available,
+        )
+    };
+    if res == 0 {
+        Ok(available as usize)
+    } else {
+        Err(last_error())
+    }
+}
+
+pub(crate) fn disconnect(socket: RawSocket) -> io::Result<()> {
+    // Connecting to the unspecified address with port zero, using the
+    // address family of the socket, dissolves the association.
+    let mut addr: SOCKADDR_STORAGE = unsafe { mem::zeroed() };
+    let mut len = mem::size_of_val(&addr) as c_int;
+    let res = unsafe {
+        sock::getsockname(
+            socket as sock::SOCKET,
+            &mut addr as *mut _ as *mut _,
+            &mut len,
+        )
+    };
+    if res != 0 {
+        return Err(last_error());
+    }
+    let family = addr.ss_family;
+    let mut addr: SOCKADDR_STORAGE = unsafe { mem::zeroed() };
+    addr.ss_family = family;
+    let res = unsafe { sock::connect(socket as sock::SOCKET, &addr as *const _ as *const _, len) };
+    if res == 0 {
+        Ok(())
+    } else {
+        Err(last_error())
+    }
+}
+
+/// Windows only API.
+impl crate::Socket {
+    /// Creates a pair of sockets which are connected to each other.
+    ///
+    /// Windows doesn't support `socketpair(2)`, so this is emulated by
+    /// connecting two sockets over the loopback interface. For stream sockets
+    /// this uses a temporary listener, for datagram sockets both sockets are
+    /// bound to the loopback interface and connected to each other.
+    ///
+    /// # Notes
+    ///
+    /// Only the `IPV4` and `IPV6` domains and the `STREAM` and `DGRAM` types
+    /// are supported. Native `AF_UNIX` socket pairs are not supported, as
+    /// this crate doesn't support `AF_UNIX` sockets on Windows (yet).
+    pub fn pair(
+        domain: Domain,
+        type_: Type,
+        protocol: Option<Protocol>,
+    ) -> io::Result<(crate::Socket, crate::Socket)> {
+        let loopback: SockAddr = if domain == Domain::IPV4 {
+            net::SocketAddr::from((Ipv4Addr::LOCALHOST, 0)).into()
+        } else if domain == Domain::IPV6 {
+            net::SocketAddr::from((Ipv6Addr::LOCALHOST, 0)).into()
+        } else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unsupported domain for socket pair",
+            ));
+        };
+
+        if type_ == Type::STREAM {
+            let listener = crate::Socket::new(domain, type_, protocol)?;
+            listener.bind(&loopback)?;
+            listener.listen(1)?;
+
+            let a = crate::Socket::new(domain, type_, protocol)?;
+            a.connect(&listener.local_addr()?)?;
+            let (b, peer_addr) = listener.accept()?;
+            // Make sure we didn't accept a connection from some other process.
+            if peer_addr.as_std() != a.local_addr()?.as_std() {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionRefused,
+                    "accepted unexpected connection for socket pair",
+                ));
+            }
+            Ok((a, b))
+        } else if type_ == Type::DGRAM {
+            let a = crate::Socket::new(domain, type_, protocol)?;
+            a.bind(&loopback)?;
+            let b = crate::Socket::new(domain, type_, protocol)?;
+            b.bind(&loopback)?;
+            a.connect(&b.local_addr()?)?;
+            b.connect(&a.local_addr()?)?;
+            Ok((a, b))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unsupported type for socket pair",
+            ))
+        }
+    }
+
+    /// Sets the `SIO_UDP_CONNRESET` behaviour of this UDP socket.
+    ///
+    /// By default Windows reports an ICMP "port unreachable" message, received
+    /// in response to a previously sent datagram, as a `WSAECONNRESET` error
+    /// on the next receive call. For a UDP server receiving from many peers
+    /// this means a single unreachable peer can cause the next `recv_from` to
+    /// fail. Passing `false` disables this behaviour.
+    ///
+    /// This function corresponds to calling `WSAIoctl` with
+    /// `SIO_UDP_CONNRESET`.
+    pub fn set_udp_connreset(&self, connreset: bool) -> io::Result<()> {
+        let connreset = connreset as BOOL;
+        wsa_ioctl_in(self.inner, SIO_UDP_CONNRESET, &connreset)
+    }
+
+    /// Sends `len` bytes of `file`, starting at the file's current position,
+    /// to the socket without copying the data through userspace. If `len` is
+    /// zero the entire file is sent.
+    ///
+    /// `head` and `tail` are optional buffers sent before and after the file
+    /// data respectively, e.g. for HTTP headers. `flags` are the `TF_*` flags,
+    /// e.g. `TF_DISCONNECT` to disconnect the socket after the data is sent.
+    /// Returns an `InvalidInput` error if `head` or `tail` is longer than
+    /// `u32::MAX` bytes.
+    ///
+    /// This is the Windows equivalent of `Socket::sendfile` on Unix.
+    ///
+    /// This function corresponds to calling `TransmitFile`.
+    pub fn transmit_file(
+        &self,
+        file: &File,
+        len: u32,
+        head: Option<&[u8]>,
+        tail: Option<&[u8]>,
+        flags: u32,
+    ) -> io::Result<()> {
+        let (head, head_len) = head.map_or((ptr::null(), 0), |h| (h.as_ptr(), h.len()));
+        let (tail, tail_len) = tail.map_or((ptr::null(), 0), |t| (t.as_ptr(), t.len()));
+        let too_long = |_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "head or tail buffer is too long for TransmitFile",
+            )
+        };
+        let mut buffers = TRANSMIT_FILE_BUFFERS {
+            Head: head as *mut _,
+            HeadLength: DWORD::try_from(head_len).map_err(too_long)?,
+            Tail: tail as *mut _,
+            TailLength: DWORD::try_from(tail_len).map_err(too_long)?,
+        };
+        let res = unsafe {
+            TransmitFile(
+                self.inner as sock::SOCKET,
+                file.as_raw_handle() as HANDLE,
+                len,
+                0,
+                ptr::null_mut(),
+                &mut buffers,
+                flags,
+            )
+        };
+        if res == 0 {
+            Err(last_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns `true` if this socket is *not* inherited by child processes,
+    /// `false` otherwise.
+    ///
+    /// This function corresponds to calling `GetHandleInformation` and
+    /// checking `HANDLE_FLAG_INHERIT`.
+    pub fn no_inherit(&self) -> io::Result<bool> {
+        let mut flags: DWORD = 0;
+        if unsafe { GetHandleInformation(self.inner as HANDLE, &mut flags) } == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(flags & HANDLE_FLAG_INHERIT == 0)
+        }
+    }
+
+    /// Sets or unsets the inheritance of this socket by child processes, the
+    /// Windows equivalent of the close-on-exec flag on Unix.
+    ///
+    /// If `no_inherit` is `true` the socket isn't inherited by processes
+    /// spawned by this process, preventing it from leaking into them.
+    ///
+    /// This function corresponds to calling `SetHandleInformation` with
+    /// `HANDLE_FLAG_INHERIT`.
+    pub fn set_no_inherit(&self, no_inherit: bool) -> io::Result<()> {
+        let flags = if no_inherit { 0 } else { HANDLE_FLAG_INHERIT };
+        let res = unsafe { SetHandleInformation(self.inner as HANDLE, HANDLE_FLAG_INHERIT, flags) };
+        if res == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
 pub struct Socket {
     socket: sock::SOCKET,
 }
@@ -824,6 +1380,27 @@ impl FromRawSocket for crate::Socket {
     }
 }
 
+impl AsSocket for crate::Socket {
+    fn as_socket(&self) -> BorrowedSocket<'_> {
+        // Safety: the socket is valid for the lifetime of `self`.
+        unsafe { BorrowedSocket::borrow_raw(self.as_raw_socket()) }
+    }
+}
+
+impl From<OwnedSocket> for crate::Socket {
+    fn from(socket: OwnedSocket) -> crate::Socket {
+        // Safety: `OwnedSocket` owns the socket.
+        unsafe { crate::Socket::from_raw_socket(socket.into_raw_socket()) }
+    }
+}
+
+impl From<crate::Socket> for OwnedSocket {
+    fn from(socket: crate::Socket) -> OwnedSocket {
+        // Safety: `Socket` owns the socket.
+        unsafe { OwnedSocket::from_raw_socket(socket.into_raw_socket()) }
+    }
+}
+
 impl Drop for Socket {
     fn drop(&mut self) {
         unsafe {
@@ -947,7 +1524,7 @@ fn from_s_addr(in_addr: in_addr_S_un) -> Ipv4Addr {
     Ipv4Addr::new(a, b, c, d)
 }
 
-fn to_in6_addr(addr: &Ipv6Addr) -> in6_addr {
+pub(crate) fn to_in6_addr(addr: &Ipv6Addr) -> in6_addr {
     let mut ret_addr: in6_addr_u = unsafe { mem::zeroed() };
     unsafe { *(ret_addr.Byte_mut()) = addr.octets() };
     let mut ret: in6_addr = unsafe { mem::zeroed() };
@@ -0,0 +1,35 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Integration with [tokio], enabled using the `tokio` feature.
+//!
+//! `Socket` can be converted into the types in `tokio::net` using the
+//! [`TryFrom`] trait, which checks the socket's domain and type and sets it
+//! to nonblocking mode. Like the `from_std` functions of these types, the
+//! conversion must be done from within a tokio runtime.
+//!
+//! [tokio]: https://docs.rs/tokio
+//! [`TryFrom`]: std::convert::TryFrom
+
+use std::net;
+#[cfg(unix)]
+use std::os::unix::net as unix_net;
+
+try_from_socket! {
+    net::TcpStream => tokio::net::TcpStream = tokio::net::TcpStream::from_std,
+    net::TcpListener => tokio::net::TcpListener = tokio::net::TcpListener::from_std,
+    net::UdpSocket => tokio::net::UdpSocket = tokio::net::UdpSocket::from_std,
+    #[cfg(unix)]
+    unix_net::UnixStream => tokio::net::UnixStream = tokio::net::UnixStream::from_std,
+    #[cfg(unix)]
+    unix_net::UnixListener => tokio::net::UnixListener = tokio::net::UnixListener::from_std,
+    #[cfg(unix)]
+    unix_net::UnixDatagram => tokio::net::UnixDatagram = tokio::net::UnixDatagram::from_std,
+}
@@ -1,6 +1,9 @@
-use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::convert::TryFrom;
+use std::io::{self, IoSliceMut, Read, Write};
+use std::net::{Ipv4Addr, Ipv6Addr, TcpListener, TcpStream, UdpSocket};
+use std::time::{Duration, Instant};
 
-use socket2::{Domain, Socket, Type};
+use socket2::{interface, Domain, Interest, SockRef, Socket, TcpKeepalive, Type};
 
 mod util;
 use util::any_local_ipv4_addr;
@@ -27,34 +30,81 @@ fn from_std_udp_socket() {
     drop(socket);
 }
 
+#[test]
+fn sockref_std_tcp_stream() {
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let mut stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+
+    {
+        let socket = SockRef::from(&stream);
+        assert_eq!(
+            socket.local_addr().unwrap().as_std(),
+            Some(stream.local_addr().unwrap())
+        );
+        socket.set_tcp_nodelay(true).unwrap();
+        assert!(stream.nodelay().unwrap());
+        socket.set_tcp_nodelay(false).unwrap();
+        assert!(!stream.nodelay().unwrap());
+    }
+
+    // Dropping the reference must not close the stream.
+    stream.write_all(b"Hello").unwrap();
+    let mut buf = [0; 5];
+    peer.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"Hello");
+}
+
+#[test]
+fn sockref_std_udp_socket() {
+    let udp_socket = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    let socket = SockRef::from(&udp_socket);
+    socket.set_ttl(10).unwrap();
+    assert_eq!(udp_socket.ttl().unwrap(), 10);
+}
+
 #[test]
 fn into_std_tcp_stream() {
     let socket: Socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
-    let tcp_socket: TcpStream = socket.into();
+    let tcp_socket = TcpStream::try_from(socket).unwrap();
     drop(tcp_socket);
 }
 
 #[test]
 fn into_std_tcp_listener() {
     let socket: Socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
-    let tcp_socket: TcpListener = socket.into();
+    let tcp_socket = TcpListener::try_from(socket).unwrap();
     drop(tcp_socket);
 }
 
 #[test]
 fn into_std_udp_socket() {
     let socket: Socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
-    let udp_socket: UdpSocket = socket.into();
+    let udp_socket = UdpSocket::try_from(socket).unwrap();
     drop(udp_socket);
 }
 
+#[test]
+fn into_std_invalid_type() {
+    let socket: Socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let err = UdpSocket::try_from(socket).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let socket: Socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    let err = TcpStream::try_from(socket).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
 #[test]
 fn socket_connect_tcp() {
     let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
     let addr = listener.local_addr().unwrap();
 
     let socket: TcpStream = Socket::new(Domain::IPV4, Type::STREAM, None)
-        .and_then(|socket| socket.connect(&addr.into()).map(|()| socket.into()))
+        .and_then(|socket| {
+            socket.connect(&addr.into())?;
+            TcpStream::try_from(socket)
+        })
         .unwrap();
     assert_eq!(socket.peer_addr().unwrap(), addr);
 
@@ -64,13 +114,141 @@ fn socket_connect_tcp() {
     assert_eq!(stream.peer_addr().unwrap(), socket_local_addr);
 }
 
+#[test]
+fn socket_connect_timeout() {
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket
+        .connect_timeout(&addr.into(), Duration::from_secs(1))
+        .unwrap();
+    assert_eq!(socket.peer_addr().unwrap().as_std().unwrap(), addr);
+    // Socket should be in blocking mode again.
+    #[cfg(unix)]
+    assert!(!socket.nonblocking().unwrap());
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let err = socket
+        .connect_timeout(&addr.into(), Duration::from_secs(0))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn socket_connect_timeout_refused() {
+    // Bind and drop a listener to get an address nobody listens on.
+    let addr = TcpListener::bind(any_local_ipv4_addr())
+        .unwrap()
+        .local_addr()
+        .unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let err = socket
+        .connect_timeout(&addr.into(), Duration::from_secs(1))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+}
+
+#[test]
+fn socket_disconnect_udp() {
+    let peer = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    let peer_addr = peer.local_addr().unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.connect(&peer_addr.into()).unwrap();
+    assert_eq!(socket.peer_addr().unwrap().as_std(), Some(peer_addr));
+
+    socket.disconnect().unwrap();
+    assert!(socket.peer_addr().is_err());
+    // Can send to any address again.
+    let other = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    let other_addr = other.local_addr().unwrap();
+    assert_eq!(socket.send_to(b"Hello", &other_addr.into()).unwrap(), 5);
+    let mut buf = [0; 16];
+    let n = other.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+}
+
+#[test]
+fn socket_poll() {
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_nonblocking(true).unwrap();
+    match socket.connect(&listener.local_addr().unwrap().into()) {
+        Ok(()) => {}
+        Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {}
+        #[cfg(unix)]
+        Err(ref err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+
+    let interest = Interest::READABLE | Interest::WRITABLE;
+    assert!(interest.is_readable() && interest.is_writable());
+    let readiness = socket
+        .poll(Interest::WRITABLE, Some(Duration::from_secs(1)))
+        .unwrap();
+    assert!(readiness.is_writable());
+    assert!(!readiness.is_error());
+
+    // Nothing to read yet.
+    let err = socket
+        .poll(Interest::READABLE, Some(Duration::from_millis(10)))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+    let (mut stream, _) = listener.accept().unwrap();
+    stream.write_all(b"Hello").unwrap();
+    let readiness = socket.poll(Interest::READABLE, None).unwrap();
+    assert!(readiness.is_readable());
+}
+
+#[test]
+fn socket_builder_listen_connect() {
+    let listener = Socket::builder(Domain::IPV4, Type::STREAM)
+        .with_reuse_address(true)
+        .with_recv_buffer_size(64 * 1024)
+        .with_nonblocking(true)
+        .listen(&any_local_ipv4_addr().into(), 128)
+        .unwrap();
+    assert!(listener.reuse_address().unwrap());
+    let addr = listener.local_addr().unwrap();
+
+    let socket = Socket::builder(Domain::IPV4, Type::STREAM)
+        .with_nodelay(true)
+        .with_keepalive(TcpKeepalive::new())
+        .with_read_timeout(Some(Duration::from_secs(1)))
+        .connect(&addr)
+        .unwrap();
+    assert!(socket.tcp_nodelay().unwrap());
+    assert!(socket.keepalive().unwrap());
+    assert_eq!(socket.read_timeout().unwrap(), Some(Duration::from_secs(1)));
+    assert_eq!(socket.peer_addr().unwrap().as_std(), addr.as_std());
+
+    // The listener is nonblocking, but the connection is already queued.
+    let (_, peer_addr) = listener.accept().unwrap();
+    assert_eq!(peer_addr.as_std(), socket.local_addr().unwrap().as_std());
+    let err = listener.accept().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+}
+
+#[test]
+fn socket_only_v6() {
+    let socket = Socket::builder(Domain::IPV6, Type::DGRAM)
+        .with_only_v6(true)
+        .build()
+        .unwrap();
+    assert!(socket.only_v6().unwrap());
+    socket.set_only_v6(false).unwrap();
+    assert!(!socket.only_v6().unwrap());
+}
+
 #[test]
 fn socket_bind_tcp() {
-    let socket: TcpListener = Socket::new(Domain::IPV4, Type::STREAM, None)
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None)
         .and_then(|socket| {
-            socket
-                .bind(&any_local_ipv4_addr().into())
-                .map(|()| socket.into())
+            socket.bind(&any_local_ipv4_addr().into())?;
+            TcpListener::try_from(socket)
         })
         .unwrap();
 
@@ -79,11 +257,11 @@ fn socket_bind_tcp() {
 
 #[test]
 fn socket_listen_tcp() {
-    let socket: TcpListener = Socket::new(Domain::IPV4, Type::STREAM, None)
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None)
         .and_then(|socket| {
             socket.bind(&any_local_ipv4_addr().into())?;
             socket.listen(1024)?;
-            Ok(socket.into())
+            TcpListener::try_from(socket)
         })
         .unwrap();
     let addr = socket.local_addr().unwrap();
@@ -120,3 +298,742 @@ fn socket_accept_tcp() {
         stream_addr
     );
 }
+
+#[test]
+#[cfg(unix)]
+fn socket_new_cloexec() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(socket.cloexec().unwrap());
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    assert!(socket.nosigpipe().unwrap());
+
+    let socket = Socket::new_raw(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.cloexec().unwrap());
+    #[cfg(any(target_os = "ios", target_os = "macos"))]
+    assert!(!socket.nosigpipe().unwrap());
+}
+
+#[test]
+#[cfg(windows)]
+fn socket_new_no_inherit() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(socket.no_inherit().unwrap());
+}
+
+#[test]
+#[cfg(unix)]
+fn socket_accept_cloexec() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_local_ipv4_addr().into()).unwrap();
+    listener.listen(2).unwrap();
+    let addr = listener.local_addr().unwrap().as_std().unwrap();
+
+    let _stream1 = TcpStream::connect(addr).unwrap();
+    let (socket, _) = listener.accept().unwrap();
+    assert!(socket.cloexec().unwrap());
+
+    let _stream2 = TcpStream::connect(addr).unwrap();
+    let (socket, _) = listener.accept_raw().unwrap();
+    assert!(!socket.cloexec().unwrap());
+}
+
+#[test]
+fn socket_accept_with() {
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&any_local_ipv4_addr().into()).unwrap();
+    listener.listen(2).unwrap();
+    let addr = listener.local_addr().unwrap().as_std().unwrap();
+
+    let _stream1 = TcpStream::connect(addr).unwrap();
+    let (socket, _) = listener.accept_with(true).unwrap();
+    let mut buf = [0; 8];
+    let err = socket.recv(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    #[cfg(unix)]
+    assert!(socket.cloexec().unwrap());
+
+    // Non-blocking listener, but the accepted socket must be blocking.
+    listener.set_nonblocking(true).unwrap();
+    let _stream2 = TcpStream::connect(addr).unwrap();
+    let (socket, _) = loop {
+        match listener.accept_with(false) {
+            Ok(res) => break res,
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(1))
+            }
+            Err(err) => panic!("unexpected error accepting: {}", err),
+        }
+    };
+    // Blocking sockets wait for the timeout, rather than returning
+    // immediately.
+    let timeout = Duration::from_millis(10);
+    socket.set_read_timeout(Some(timeout)).unwrap();
+    let start = Instant::now();
+    let err = socket.recv(&mut buf).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    ));
+    assert!(start.elapsed() >= timeout);
+}
+
+#[test]
+fn socket_bytes_available() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    assert_eq!(socket.bytes_available().unwrap(), 0);
+
+    let sender = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    let addr = socket.local_addr().unwrap().as_std().unwrap();
+    sender.send_to(b"Hello world", addr).unwrap();
+
+    let mut available = 0;
+    for _ in 0..100 {
+        available = socket.bytes_available().unwrap();
+        if available != 0 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    // Some platforms, e.g. macOS, include the size of the address.
+    assert!(available >= 11, "{}", available);
+}
+
+#[test]
+fn socket_try_clone() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = socket.local_addr().unwrap().as_std().unwrap();
+
+    let cloned = socket.try_clone().unwrap();
+    assert_eq!(cloned.local_addr().unwrap().as_std().unwrap(), addr);
+
+    let sender = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    sender.send_to(b"Hello world", addr).unwrap();
+
+    // Received on the clone, but sent to the original.
+    let mut buf = [0; 32];
+    let n = cloned.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello world");
+
+    #[cfg(unix)]
+    {
+        let flags = cloned.fcntl(libc::F_GETFD, ()).unwrap();
+        assert!((flags & libc::FD_CLOEXEC) != 0);
+    }
+}
+
+#[test]
+fn socket_recv() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = socket.local_addr().unwrap().as_std().unwrap();
+
+    let sender = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    sender.send_to(b"Hello world", addr).unwrap();
+
+    let mut buf = [0; 32];
+    let n = socket.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello world");
+}
+
+#[test]
+fn socket_recv_vectored_truncated() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = socket.local_addr().unwrap().as_std().unwrap();
+
+    let sender = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    sender.send_to(b"Hello world", addr).unwrap();
+    sender.send_to(b"Hello", addr).unwrap();
+
+    let mut buf1 = [0; 3];
+    let mut buf2 = [0; 4];
+    let mut bufs = [IoSliceMut::new(&mut buf1), IoSliceMut::new(&mut buf2)];
+    let (n, flags) = socket.recv_vectored(&mut bufs).unwrap();
+    assert_eq!(n, 7);
+    assert!(flags.is_truncated());
+    assert!(!flags.is_control_truncated());
+    assert_eq!(&buf1, b"Hel");
+    assert_eq!(&buf2, b"lo w");
+
+    let mut buf = [0; 32];
+    let (n, flags) = socket
+        .recv_vectored(&mut [IoSliceMut::new(&mut buf)])
+        .unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    assert!(!flags.is_truncated());
+}
+
+#[test]
+fn socket_recv_exact() {
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.connect(&addr.into()).unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+
+    stream.write_all(b"Hello").unwrap();
+    stream.write_all(b" world").unwrap();
+    let mut buf = [0; 11];
+    socket.recv_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"Hello world");
+
+    stream.write_all(b"Hi").unwrap();
+    drop(stream);
+    let mut buf = [0; 11];
+    let err = socket.recv_exact(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn socket_send() {
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.connect(&addr.into()).unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+
+    assert_eq!(socket.send(b"Hello world").unwrap(), 11);
+    let mut buf = [0; 11];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"Hello world");
+}
+
+#[test]
+fn socket_send_to() {
+    let receiver = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    let addr = receiver.local_addr().unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.send_to(b"Hello world", &addr.into()).unwrap(), 11);
+
+    let mut buf = [0; 32];
+    let n = receiver.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello world");
+}
+
+#[test]
+fn socket_recv_from() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = socket.local_addr().unwrap().as_std().unwrap();
+
+    let sender = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    let sender_addr = sender.local_addr().unwrap();
+    sender.send_to(b"Hello world", addr).unwrap();
+
+    let mut buf = [0; 32];
+    let (n, from) = socket.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello world");
+    assert_eq!(from.as_std().unwrap(), sender_addr);
+}
+
+#[test]
+fn socket_recv_from_vectored() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = socket.local_addr().unwrap().as_std().unwrap();
+
+    let sender = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    let sender_addr = sender.local_addr().unwrap();
+    sender.send_to(b"Hello world", addr).unwrap();
+
+    let mut header = [0; 6];
+    let mut body = [0; 32];
+    let mut bufs = [IoSliceMut::new(&mut header), IoSliceMut::new(&mut body)];
+    let (n, flags, from) = socket.recv_from_vectored(&mut bufs).unwrap();
+    assert_eq!(n, 11);
+    assert!(!flags.is_truncated());
+    assert_eq!(from.as_std().unwrap(), sender_addr);
+    assert_eq!(&header, b"Hello ");
+    assert_eq!(&body[..5], b"world");
+}
+
+#[test]
+fn socket_peek_sender() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = socket.local_addr().unwrap().as_std().unwrap();
+
+    let sender = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+    let sender_addr = sender.local_addr().unwrap();
+    sender.send_to(b"Hello world", addr).unwrap();
+
+    let from = socket.peek_sender().unwrap();
+    assert_eq!(from.as_std().unwrap(), sender_addr);
+
+    // The datagram should still be queued.
+    let mut buf = [0; 32];
+    let (n, from) = socket.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello world");
+    assert_eq!(from.as_std().unwrap(), sender_addr);
+}
+
+#[test]
+fn socket_set_nonblocking() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    socket.listen(1).unwrap();
+
+    socket.set_nonblocking(true).unwrap();
+    let err = socket.accept().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+}
+
+#[test]
+fn socket_ttl() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.set_ttl(42).unwrap();
+    assert_eq!(socket.ttl().unwrap(), 42);
+}
+
+#[test]
+#[cfg(not(windows))] // Windows doesn't support setting `IP_TOS`.
+fn socket_tos() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.set_tos(0x10).unwrap();
+    assert_eq!(socket.tos().unwrap(), 0x10);
+}
+
+#[test]
+fn socket_unicast_hops_v6() {
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    socket.set_unicast_hops_v6(42).unwrap();
+    assert_eq!(socket.unicast_hops_v6().unwrap(), 42);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    windows
+))]
+fn socket_tclass_v6() {
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    socket.set_tclass_v6(0x10).unwrap();
+    assert_eq!(socket.tclass_v6().unwrap(), 0x10);
+}
+
+#[test]
+fn socket_tcp_nodelay() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.tcp_nodelay().unwrap());
+    socket.set_tcp_nodelay(true).unwrap();
+    assert!(socket.tcp_nodelay().unwrap());
+}
+
+#[test]
+fn socket_reuse_address() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.reuse_address().unwrap());
+    socket.set_reuse_address(true).unwrap();
+    assert!(socket.reuse_address().unwrap());
+}
+
+#[test]
+fn socket_linger() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.linger().unwrap(), None);
+    socket.set_linger(Some(Duration::from_secs(5))).unwrap();
+    assert_eq!(socket.linger().unwrap(), Some(Duration::from_secs(5)));
+    socket.set_linger(Some(Duration::from_secs(0))).unwrap();
+    assert_eq!(socket.linger().unwrap(), Some(Duration::from_secs(0)));
+    socket.set_linger(None).unwrap();
+    assert_eq!(socket.linger().unwrap(), None);
+}
+
+#[test]
+fn socket_recv_buffer_size() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.set_recv_buffer_size(16 * 1024).unwrap();
+    // Linux doubles the size set.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert_eq!(socket.recv_buffer_size().unwrap(), 2 * 16 * 1024);
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    assert_eq!(socket.recv_buffer_size().unwrap(), 16 * 1024);
+}
+
+#[test]
+fn socket_send_buffer_size() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.set_send_buffer_size(16 * 1024).unwrap();
+    // Linux doubles the size set.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert_eq!(socket.send_buffer_size().unwrap(), 2 * 16 * 1024);
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    assert_eq!(socket.send_buffer_size().unwrap(), 16 * 1024);
+}
+
+#[test]
+fn socket_read_timeout() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.read_timeout().unwrap(), None);
+    let timeout = Duration::from_secs(5);
+    socket.set_read_timeout(Some(timeout)).unwrap();
+    assert_eq!(socket.read_timeout().unwrap(), Some(timeout));
+
+    // The timeout is rounded by the OS, so we can't compare it.
+    socket
+        .set_read_timeout(Some(Duration::from_millis(10)))
+        .unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let mut buf = [0; 8];
+    let err = socket.recv(&mut buf).unwrap_err();
+    assert!(
+        err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::TimedOut,
+        "unexpected error: {}",
+        err
+    );
+
+    let err = socket
+        .set_read_timeout(Some(Duration::from_secs(0)))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    socket.set_read_timeout(None).unwrap();
+    assert_eq!(socket.read_timeout().unwrap(), None);
+}
+
+#[test]
+fn socket_write_timeout() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.write_timeout().unwrap(), None);
+    let timeout = Duration::from_secs(5);
+    socket.set_write_timeout(Some(timeout)).unwrap();
+    assert_eq!(socket.write_timeout().unwrap(), Some(timeout));
+    socket.set_write_timeout(None).unwrap();
+    assert_eq!(socket.write_timeout().unwrap(), None);
+}
+
+#[test]
+fn socket_multicast_v4() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.set_multicast_loop_v4(false).unwrap();
+    assert!(!socket.multicast_loop_v4().unwrap());
+    socket.set_multicast_loop_v4(true).unwrap();
+    assert!(socket.multicast_loop_v4().unwrap());
+    socket.set_multicast_ttl_v4(42).unwrap();
+    assert_eq!(socket.multicast_ttl_v4().unwrap(), 42);
+
+    socket.set_multicast_if_v4(&Ipv4Addr::LOCALHOST).unwrap();
+    assert_eq!(socket.multicast_if_v4().unwrap(), Ipv4Addr::LOCALHOST);
+
+    let multiaddr = Ipv4Addr::new(224, 0, 0, 251);
+    let interface = Ipv4Addr::LOCALHOST;
+    socket.join_multicast_v4(&multiaddr, &interface).unwrap();
+    socket.leave_multicast_v4(&multiaddr, &interface).unwrap();
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    windows
+))]
+fn socket_ssm_v4() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    let source = Ipv4Addr::LOCALHOST;
+    let group = Ipv4Addr::new(232, 1, 1, 1);
+    let interface = Ipv4Addr::LOCALHOST;
+    socket.join_ssm_v4(&source, &group, &interface).unwrap();
+    socket.leave_ssm_v4(&source, &group, &interface).unwrap();
+}
+
+#[test]
+fn socket_multicast_v6() {
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    socket.set_multicast_loop_v6(false).unwrap();
+    assert!(!socket.multicast_loop_v6().unwrap());
+    socket.set_multicast_loop_v6(true).unwrap();
+    assert!(socket.multicast_loop_v6().unwrap());
+    socket.set_multicast_hops_v6(42).unwrap();
+    assert_eq!(socket.multicast_hops_v6().unwrap(), 42);
+
+    socket.set_multicast_if_v6(0).unwrap();
+    assert_eq!(socket.multicast_if_v6().unwrap(), 0);
+
+    let multiaddr = Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xfb);
+    socket.join_multicast_v6(&multiaddr, 0).unwrap();
+    socket.leave_multicast_v6(&multiaddr, 0).unwrap();
+}
+
+#[test]
+fn socket_tcp_keepalive() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.keepalive().unwrap());
+
+    let params = TcpKeepalive::new().with_time(Duration::from_secs(200));
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd",
+        windows
+    ))]
+    let params = params.with_interval(Duration::from_secs(30));
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "netbsd"
+    ))]
+    let params = params.with_retries(10);
+    socket.set_tcp_keepalive(&params).unwrap();
+    assert!(socket.keepalive().unwrap());
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    {
+        let time: libc::c_int = socket
+            .getsockopt(libc::IPPROTO_TCP, libc::TCP_KEEPIDLE)
+            .unwrap();
+        assert_eq!(time, 200);
+        let interval: libc::c_int = socket
+            .getsockopt(libc::IPPROTO_TCP, libc::TCP_KEEPINTVL)
+            .unwrap();
+        assert_eq!(interval, 30);
+        let retries: libc::c_int = socket
+            .getsockopt(libc::IPPROTO_TCP, libc::TCP_KEEPCNT)
+            .unwrap();
+        assert_eq!(retries, 10);
+    }
+
+    socket.set_keepalive(false).unwrap();
+    assert!(!socket.keepalive().unwrap());
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn socket_tcp_fastopen() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    socket.set_tcp_fastopen(5).unwrap();
+    socket.listen(128).unwrap();
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert_eq!(socket.tcp_fastopen().unwrap(), 5);
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    assert_ne!(socket.tcp_fastopen().unwrap(), 0);
+}
+
+#[test]
+fn socket_type() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.r#type().unwrap(), Type::STREAM);
+
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.r#type().unwrap(), Type::DGRAM);
+}
+
+#[test]
+#[cfg(unix)]
+fn socket_send_closed_peer_sigpipe() {
+    use std::process::Command;
+
+    // The Rust runtime ignores `SIGPIPE` before `main`, which would hide a
+    // missing `MSG_NOSIGNAL` in `socket_send_closed_peer`. So run it again in a
+    // child process with the default `SIGPIPE` disposition, which kills the
+    // process if the signal is raised.
+    let status = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "socket_send_closed_peer", "--test-threads=1"])
+        .env("SOCKET2_DEFAULT_SIGPIPE", "1")
+        .status()
+        .unwrap();
+    assert!(status.success(), "child process failed: {}", status);
+}
+
+#[test]
+fn socket_send_closed_peer() {
+    // See `socket_send_closed_peer_sigpipe`.
+    #[cfg(unix)]
+    if std::env::var_os("SOCKET2_DEFAULT_SIGPIPE").is_some() {
+        unsafe { libc::signal(libc::SIGPIPE, libc::SIG_DFL) };
+    }
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.connect(&addr.into()).unwrap();
+    drop(listener.accept().unwrap());
+    drop(listener);
+
+    // The first send may succeed, after which the peer responds with a reset.
+    // Either way we should get an error rather than being killed by `SIGPIPE`.
+    let err = loop {
+        match socket.send(b"Hello world") {
+            Ok(_) => std::thread::sleep(Duration::from_millis(10)),
+            Err(err) => break err,
+        }
+    };
+    assert!(
+        matches!(
+            err.kind(),
+            io::ErrorKind::BrokenPipe
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+        ),
+        "unexpected error: {}",
+        err
+    );
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    windows
+))]
+fn socket_dontfrag() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.set_dontfrag_v4(true).unwrap();
+    assert!(socket.dontfrag_v4().unwrap());
+    socket.set_dontfrag_v4(false).unwrap();
+    assert!(!socket.dontfrag_v4().unwrap());
+
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    assert!(!socket.dontfrag_v6().unwrap());
+    socket.set_dontfrag_v6(true).unwrap();
+    assert!(socket.dontfrag_v6().unwrap());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux", windows))]
+fn socket_unicast_if() {
+    // Index of the loopback interface.
+    let interface = 1;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.unicast_if_v4().unwrap(), 0);
+    socket.set_unicast_if_v4(interface).unwrap();
+    assert_eq!(socket.unicast_if_v4().unwrap(), interface);
+
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.unicast_if_v6().unwrap(), 0);
+    socket.set_unicast_if_v6(interface).unwrap();
+    assert_eq!(socket.unicast_if_v6().unwrap(), interface);
+}
+
+#[test]
+fn interface_name_index() {
+    // The loopback interface usually has index 1.
+    let name = interface::index_to_name(1).unwrap();
+    assert_eq!(interface::name_to_index(&name).unwrap(), 1);
+
+    assert!(interface::name_to_index("not-an-interface").is_err());
+    assert!(interface::name_to_index("lo\0").is_err());
+    assert!(interface::index_to_name(u32::MAX).is_err());
+}
+
+#[test]
+#[cfg(all(unix, feature = "mio"))]
+fn mio_source() {
+    use mio::{Events, Poll, Token};
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let mut socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_nonblocking(true).unwrap();
+
+    let mut poll = Poll::new().unwrap();
+    poll.registry()
+        .register(&mut socket, Token(0), mio::Interest::WRITABLE)
+        .unwrap();
+    match socket.connect(&listener.local_addr().unwrap().into()) {
+        Ok(()) => {}
+        Err(ref err) if err.raw_os_error() == Some(libc::EINPROGRESS) => {}
+        Err(err) => panic!("unexpected error: {}", err),
+    }
+
+    let mut events = Events::with_capacity(8);
+    poll.poll(&mut events, Some(Duration::from_secs(1)))
+        .unwrap();
+    let event = events.iter().next().unwrap();
+    assert_eq!(event.token(), Token(0));
+    assert!(event.is_writable());
+    poll.registry().deregister(&mut socket).unwrap();
+}
+
+#[test]
+#[cfg(feature = "mio")]
+fn mio_try_from() {
+    use std::convert::TryInto;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let socket: mio::net::UdpSocket = socket.try_into().unwrap();
+    // Nonblocking, so this mustn't block.
+    let mut buf = [0; 16];
+    let err = socket.recv(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    let err = mio::net::TcpStream::try_from(socket).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(feature = "tokio")]
+fn tokio_try_from() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+        socket.bind(&any_local_ipv4_addr().into()).unwrap();
+        socket.listen(128).unwrap();
+        let listener = tokio::net::TcpListener::try_from(socket).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let stream = TcpStream::connect(addr).unwrap();
+        let (_, peer_addr) = listener.accept().await.unwrap();
+        assert_eq!(peer_addr, stream.local_addr().unwrap());
+
+        let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+        let err = tokio::net::UdpSocket::try_from(socket).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    });
+}
+
+#[test]
+#[cfg(feature = "async-std")]
+fn async_std_try_from() {
+    async_std::task::block_on(async {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+        socket.bind(&any_local_ipv4_addr().into()).unwrap();
+        let socket = async_std::net::UdpSocket::try_from(socket).unwrap();
+        let addr = socket.local_addr().unwrap();
+        let sender = UdpSocket::bind(any_local_ipv4_addr()).unwrap();
+        sender.send_to(b"Hello", addr).unwrap();
+        let mut buf = [0; 16];
+        let (n, _) = socket.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"Hello");
+
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+        let err = async_std::net::TcpStream::try_from(socket).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    });
+}
@@ -2,6 +2,7 @@
 
 #![cfg(unix)]
 
+use std::convert::TryFrom;
 use std::os::unix::net::{UnixDatagram, UnixListener, UnixStream};
 
 use socket2::{Domain, Socket, Type};
@@ -35,26 +36,1715 @@ fn from_std_unix_socket() {
     drop(socket);
 }
 
+#[test]
+fn owned_fd() {
+    use std::os::unix::io::{AsFd, AsRawFd, OwnedFd};
+
+    let socket = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    let raw_fd = socket.as_raw_fd();
+    assert_eq!(socket.as_fd().as_raw_fd(), raw_fd);
+
+    let fd: OwnedFd = socket.into();
+    assert_eq!(fd.as_raw_fd(), raw_fd);
+    let socket = Socket::from(fd);
+    assert_eq!(socket.as_raw_fd(), raw_fd);
+    assert_eq!(socket.r#type().unwrap(), Type::STREAM);
+}
+
 #[test]
 fn into_std_unix_stream() {
     let socket: Socket = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
-    let unix_socket: UnixStream = socket.into();
+    let unix_socket = UnixStream::try_from(socket).unwrap();
     drop(unix_socket);
 }
 
 #[test]
 fn into_std_tcp_listener() {
     let socket: Socket = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
-    let unix_socket: UnixListener = socket.into();
+    let unix_socket = UnixListener::try_from(socket).unwrap();
     drop(unix_socket);
 }
 
 #[test]
 fn into_std_udp_socket() {
     let socket: Socket = Socket::new(Domain::UNIX, Type::DGRAM, None).unwrap();
-    let unix_socket: UnixDatagram = socket.into();
+    let unix_socket = UnixDatagram::try_from(socket).unwrap();
     drop(unix_socket);
 }
 
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn sendfile() {
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    use util::any_local_ipv4_addr;
+
+    let path = temp_file("sendfile");
+    File::create(&path)
+        .and_then(|mut file| file.write_all(b"Hello world"))
+        .unwrap();
+    let file = File::open(&path).unwrap();
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket
+        .connect(&listener.local_addr().unwrap().into())
+        .unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+
+    assert_eq!(socket.sendfile(&file, 6, 5).unwrap(), 5);
+    assert_eq!(socket.sendfile(&file, 0, 0).unwrap(), 0);
+    let err = socket.sendfile(&file, u64::MAX, 5).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    drop(socket);
+
+    let mut buf = Vec::new();
+    stream.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, b"world");
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn splice() {
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    use util::any_local_ipv4_addr;
+
+    let mut fds = [-1; 2];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    let mut pipe_read = unsafe { File::from_raw_fd(fds[0]) };
+    let mut pipe_write = unsafe { File::from_raw_fd(fds[1]) };
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket
+        .connect(&listener.local_addr().unwrap().into())
+        .unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+
+    stream.write_all(b"Hello world").unwrap();
+    let n = socket
+        .splice_to(pipe_write.as_raw_fd(), 11, libc::SPLICE_F_MOVE as _)
+        .unwrap();
+    assert_eq!(n, 11);
+    let mut buf = [0; 11];
+    pipe_read.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"Hello world");
+
+    pipe_write.write_all(b"Hello back").unwrap();
+    let n = socket
+        .splice_from(pipe_read.as_raw_fd(), 32, libc::SPLICE_F_MOVE as _)
+        .unwrap();
+    assert_eq!(n, 10);
+    let mut buf = [0; 10];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"Hello back");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn zerocopy() {
+    use std::io::{self, Read};
+    use std::net::TcpListener;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use util::any_local_ipv4_addr;
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.zerocopy().unwrap());
+    socket.set_zerocopy(true).unwrap();
+    assert!(socket.zerocopy().unwrap());
+    socket
+        .connect(&listener.local_addr().unwrap().into())
+        .unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+
+    let err = socket.recv_zerocopy_completion().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    assert_eq!(socket.send_zerocopy(b"Hello world").unwrap(), 11);
+    let mut buf = [0; 11];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"Hello world");
+
+    let completion = loop {
+        match socket.recv_zerocopy_completion() {
+            Ok(completion) => break completion.unwrap(),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                sleep(Duration::from_millis(10))
+            }
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    };
+    assert_eq!(completion.start(), 0);
+    assert_eq!(completion.end(), 0);
+    // Loopback always copies the data.
+    assert!(completion.copied());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn timestamping() {
+    use std::io::{self, IoSliceMut};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use socket2::{CmsgBuffer, Timestamping};
+    use util::any_local_ipv4_addr;
+
+    let flags = Timestamping::RX_SOFTWARE
+        | Timestamping::TX_SOFTWARE
+        | Timestamping::SOFTWARE
+        | Timestamping::OPT_ID
+        | Timestamping::OPT_TSONLY;
+    assert!(flags.contains(Timestamping::SOFTWARE | Timestamping::OPT_ID));
+    assert!(!flags.contains(Timestamping::RAW_HARDWARE));
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    assert_eq!(socket.timestamping().unwrap(), Timestamping::empty());
+    socket.set_timestamping(flags).unwrap();
+    assert_eq!(socket.timestamping().unwrap(), flags);
+
+    let err = socket.recv_tx_timestamp().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    // The kernel enables receive timestamps asynchronously if no other
+    // socket has them enabled, so the first datagrams may not have one.
+    let addr = socket.local_addr().unwrap();
+    let mut timestamps = None;
+    for _ in 0..100 {
+        // Send a datagram to ourselves, so we get both a TX and RX timestamp.
+        socket.send_to(b"Hello", &addr).unwrap();
+
+        let mut buf = [0; 16];
+        let mut control = CmsgBuffer::with_capacity(128);
+        let (n, _, _) = socket
+            .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+            .unwrap();
+        assert_eq!(&buf[..n], b"Hello");
+        timestamps = control.iter().find_map(|cmsg| cmsg.timestamping());
+        if timestamps.is_some() {
+            break;
+        }
+        sleep(Duration::from_millis(10));
+    }
+    let timestamps = timestamps.expect("no SCM_TIMESTAMPING control message received");
+    assert!(timestamps.software().is_some());
+    assert!(timestamps.hardware().is_none());
+
+    let timestamp = loop {
+        match socket.recv_tx_timestamp() {
+            Ok(timestamp) => break timestamp.unwrap(),
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                sleep(Duration::from_millis(10))
+            }
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    };
+    assert_eq!(timestamp.id(), 0);
+    assert!(timestamp.is_sent());
+    assert!(timestamp.timestamps().software().is_some());
+}
+
+#[test]
+fn nonblocking() {
+    let socket = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    assert!(!socket.nonblocking().unwrap());
+    socket.set_nonblocking(true).unwrap();
+    assert!(socket.nonblocking().unwrap());
+    socket.set_nonblocking(false).unwrap();
+    assert!(!socket.nonblocking().unwrap());
+}
+
+#[test]
+fn pair_cloexec() {
+    let (a, b) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+    assert!(a.cloexec().unwrap());
+    assert!(b.cloexec().unwrap());
+
+    let (a, b) = Socket::pair_raw(Domain::UNIX, Type::STREAM, None).unwrap();
+    assert!(!a.cloexec().unwrap());
+    assert!(!b.cloexec().unwrap());
+}
+
+#[test]
+fn cloexec() {
+    let socket = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    socket.set_cloexec(true).unwrap();
+    assert!(socket.cloexec().unwrap());
+    socket.set_cloexec(false).unwrap();
+    assert!(!socket.cloexec().unwrap());
+    let flags = socket.fcntl(libc::F_GETFD, ()).unwrap();
+    assert_eq!(flags & libc::FD_CLOEXEC, 0);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn mark() {
+    use std::io;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert_eq!(socket.mark().unwrap(), 0);
+    match socket.set_mark(123) {
+        Ok(()) => assert_eq!(socket.mark().unwrap(), 123),
+        // Setting the mark requires `CAP_NET_ADMIN`.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => {}
+        Err(err) => panic!("unexpected error setting mark: {}", err),
+    }
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn bpf_filter() {
+    use std::io;
+    use std::time::Duration;
+
+    use util::any_local_ipv4_addr;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_millis(100)))
+        .unwrap();
+    let addr = socket.local_addr().unwrap();
+    let sender = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+
+    // `ret #0`, i.e. drop all packets.
+    let drop_all = libc::sock_filter {
+        // `BPF_RET | BPF_K`, not defined in `libc` for all platforms.
+        code: 0x06,
+        jt: 0,
+        jf: 0,
+        k: 0,
+    };
+    socket.attach_filter(&[drop_all]).unwrap();
+    sender.send_to(b"Hello", &addr).unwrap();
+    let mut buf = [0; 16];
+    let err = socket.recv(&mut buf).unwrap_err();
+    assert!(
+        err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::TimedOut,
+        "unexpected error: {}",
+        err
+    );
+
+    socket.detach_filter().unwrap();
+    sender.send_to(b"World", &addr).unwrap();
+    let n = socket.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"World");
+    // No filter attached anymore.
+    assert!(socket.detach_filter().is_err());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn device() {
+    use std::io;
+
+    // Some common network interface on Linux.
+    const INTERFACES: &[&str] = &["lo", "lo0", "eth0", "wlan0"];
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.device().unwrap(), None);
+
+    for interface in INTERFACES.iter() {
+        if let Err(err) = socket.bind_device(Some(interface.as_bytes())) {
+            // Network interface is not available, try another one.
+            if matches!(err.raw_os_error(), Some(libc::ENODEV)) {
+                continue;
+            }
+            // Binding to a device requires `CAP_NET_RAW` on older kernels.
+            if err.kind() == io::ErrorKind::PermissionDenied {
+                return;
+            }
+            panic!("unexpected error binding device: {}", err);
+        }
+        assert_eq!(
+            socket.device().unwrap().as_deref(),
+            Some(interface.as_bytes())
+        );
+
+        socket.bind_device(None).unwrap();
+        assert_eq!(socket.device().unwrap(), None);
+        // Just need to do it with one interface.
+        return;
+    }
+
+    panic!("failed to bind to any device.");
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn ip_transparent() {
+    use std::io;
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.ip_transparent().unwrap());
+    match socket.set_ip_transparent(true) {
+        Ok(()) => assert!(socket.ip_transparent().unwrap()),
+        // Setting the option requires `CAP_NET_ADMIN`.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => {}
+        Err(err) => panic!("unexpected error setting IP_TRANSPARENT: {}", err),
+    }
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn tcp_fastopen_client() {
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    use util::any_local_ipv4_addr;
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let addr = listener.local_addr().unwrap().into();
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.tcp_fastopen_connect().unwrap());
+    socket.set_tcp_fastopen_connect(true).unwrap();
+    assert!(socket.tcp_fastopen_connect().unwrap());
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.send_fastopen(b"Hello world", &addr).unwrap(), 11);
+    let (mut stream, _) = listener.accept().unwrap();
+    let mut buf = [0; 11];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"Hello world");
+}
+
+#[test]
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn connectx() {
+    use std::io::{IoSlice, Read};
+    use std::net::TcpListener;
+
+    use util::any_local_ipv4_addr;
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let addr = listener.local_addr().unwrap().into();
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let bufs = [IoSlice::new(b"Hello"), IoSlice::new(b" world")];
+    let n = socket
+        .connectx(&addr, None, &bufs, libc::CONNECT_DATA_IDEMPOTENT)
+        .unwrap();
+    assert_eq!(n, 11);
+    assert_eq!(socket.peer_addr().unwrap().as_std(), addr.as_std());
+    let (mut stream, _) = listener.accept().unwrap();
+    let mut buf = [0; 11];
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"Hello world");
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn tcp_quickack() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_tcp_quickack(false).unwrap();
+    assert!(!socket.tcp_quickack().unwrap());
+    socket.set_tcp_quickack(true).unwrap();
+    assert!(socket.tcp_quickack().unwrap());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn syn_count() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_syn_count(3).unwrap();
+    assert_eq!(socket.syn_count().unwrap(), 3);
+    // The kernel limits the value to 127.
+    assert!(socket.set_syn_count(200).is_err());
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn tcp_notsent_lowat() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_tcp_notsent_lowat(16 * 1024).unwrap();
+    assert_eq!(socket.tcp_notsent_lowat().unwrap(), 16 * 1024);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+fn tcp_congestion() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let original = socket.tcp_congestion().unwrap();
+    assert!(!original.is_empty());
+
+    // Reno is always available on Linux, and NewReno is FreeBSD's default.
+    #[cfg(not(target_os = "freebsd"))]
+    let name: &[u8] = b"reno";
+    #[cfg(target_os = "freebsd")]
+    let name: &[u8] = b"newreno";
+    socket.set_tcp_congestion(name).unwrap();
+    assert_eq!(socket.tcp_congestion().unwrap(), name);
+
+    socket.set_tcp_congestion(&original).unwrap();
+    assert_eq!(socket.tcp_congestion().unwrap(), original);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn tcp_info() {
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    use util::any_local_ipv4_addr;
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket
+        .connect(&listener.local_addr().unwrap().into())
+        .unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+
+    assert_eq!(socket.send(b"Hello world").unwrap(), 11);
+    let mut buf = [0; 11];
+    stream.read_exact(&mut buf).unwrap();
+
+    let info = socket.tcp_info().unwrap();
+    assert_eq!(info.state(), 1); // `TCP_ESTABLISHED`.
+    assert!(info.snd_mss() > 0);
+    assert!(info.snd_cwnd() > 0);
+}
+
+#[test]
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn tcp_connection_info() {
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    use util::any_local_ipv4_addr;
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket
+        .connect(&listener.local_addr().unwrap().into())
+        .unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+
+    assert_eq!(socket.send(b"Hello world").unwrap(), 11);
+    let mut buf = [0; 11];
+    stream.read_exact(&mut buf).unwrap();
+
+    let info = socket.tcp_connection_info().unwrap();
+    assert_eq!(info.state(), 4); // `TCPS_ESTABLISHED`.
+    assert!(info.maxseg() > 0);
+    assert_eq!(info.txbytes(), 11);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn incoming_cpu() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_incoming_cpu(0).unwrap();
+    assert_eq!(socket.incoming_cpu().unwrap(), 0);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn busy_poll() {
+    use std::io;
+    use std::time::Duration;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    match socket.set_busy_poll(Duration::from_micros(50)) {
+        Ok(()) => assert_eq!(socket.busy_poll().unwrap(), Duration::from_micros(50)),
+        // Increasing the value requires `CAP_NET_ADMIN`.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => {}
+        Err(err) => panic!("unexpected error setting SO_BUSY_POLL: {}", err),
+    }
+    socket.set_busy_poll(Duration::from_micros(0)).unwrap();
+    assert_eq!(socket.busy_poll().unwrap(), Duration::from_micros(0));
+}
+
+#[test]
+#[cfg(target_os = "freebsd")]
+fn reuse_port_lb() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.reuse_port_lb().unwrap());
+    socket.set_reuse_port_lb(true).unwrap();
+    assert!(socket.reuse_port_lb().unwrap());
+}
+
+#[test]
+#[cfg(target_os = "freebsd")]
+fn user_cookie() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.user_cookie().unwrap(), 0);
+    socket.set_user_cookie(0xdead_beef).unwrap();
+    assert_eq!(socket.user_cookie().unwrap(), 0xdead_beef);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd"
+))]
+fn nosigpipe() {
+    // `Socket::new` sets `SO_NOSIGPIPE` on iOS and macOS.
+    let socket = Socket::new_raw(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.nosigpipe().unwrap());
+    socket.set_nosigpipe(true).unwrap();
+    assert!(socket.nosigpipe().unwrap());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+fn domain_and_protocol() {
+    use socket2::Protocol;
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.domain().unwrap(), Domain::IPV4);
+    assert_eq!(socket.protocol().unwrap(), Some(Protocol::TCP));
+
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::UDP)).unwrap();
+    assert_eq!(socket.domain().unwrap(), Domain::IPV6);
+    assert_eq!(socket.protocol().unwrap(), Some(Protocol::UDP));
+
+    let socket = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    assert_eq!(socket.domain().unwrap(), Domain::UNIX);
+    assert_eq!(socket.protocol().unwrap(), None);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn peer_cred() {
+    let (a, b) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+    for socket in &[a, b] {
+        let cred = socket.peer_cred().unwrap();
+        assert_eq!(cred.uid(), unsafe { libc::geteuid() });
+        assert_eq!(cred.gid(), unsafe { libc::getegid() });
+        #[cfg(any(
+            target_os = "android",
+            target_os = "ios",
+            target_os = "linux",
+            target_os = "macos"
+        ))]
+        assert_eq!(cred.pid(), Some(std::process::id() as libc::pid_t));
+    }
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn passcred() {
+    use std::io::{IoSlice, IoSliceMut};
+
+    use socket2::{CmsgBuffer, UCred};
+
+    let (a, b) = Socket::pair(Domain::UNIX, Type::DGRAM, None).unwrap();
+    assert!(!b.passcred().unwrap());
+    b.set_passcred(true).unwrap();
+    assert!(b.passcred().unwrap());
+
+    let pid = std::process::id() as libc::pid_t;
+    let uid = unsafe { libc::getuid() };
+    let gid = unsafe { libc::getgid() };
+
+    // Without explicitly sending credentials the kernel adds them for us.
+    let sent = a.send_msg(&[IoSlice::new(b"Hello")], None, &CmsgBuffer::new(), 0);
+    assert_eq!(sent.unwrap(), 5);
+    let mut buf = [0; 16];
+    let mut control = CmsgBuffer::with_capacity(64);
+    let (n, flags, _) = b
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+        .unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    assert!(!flags.is_control_truncated());
+    let cred = control.iter().find_map(|cmsg| cmsg.credentials()).unwrap();
+    assert_eq!(cred, UCred::new(pid, uid, gid));
+
+    let mut control = CmsgBuffer::new();
+    control.push_credentials(&UCred::new(pid, uid, gid));
+    let sent = a.send_msg(&[IoSlice::new(b"World")], None, &control, 0);
+    assert_eq!(sent.unwrap(), 5);
+    let mut control = CmsgBuffer::with_capacity(64);
+    let (n, _, _) = b
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+        .unwrap();
+    assert_eq!(&buf[..n], b"World");
+    let cmsgs = control.iter().collect::<Vec<_>>();
+    assert_eq!(cmsgs.len(), 1);
+    assert_eq!(cmsgs[0].level(), libc::SOL_SOCKET);
+    assert_eq!(cmsgs[0].r#type(), libc::SCM_CREDENTIALS);
+    assert_eq!(cmsgs[0].credentials(), Some(UCred::new(pid, uid, gid)));
+}
+
+#[test]
+#[cfg(any(target_os = "dragonfly", target_os = "freebsd"))]
+fn scm_creds() {
+    use std::io::{IoSlice, IoSliceMut};
+
+    use socket2::CmsgBuffer;
+
+    let (a, b) = Socket::pair(Domain::UNIX, Type::DGRAM, None).unwrap();
+
+    let mut control = CmsgBuffer::new();
+    control.push_creds();
+    let sent = a.send_msg(&[IoSlice::new(b"Hello")], None, &control, 0);
+    assert_eq!(sent.unwrap(), 5);
+    let mut buf = [0; 16];
+    let mut control = CmsgBuffer::with_capacity(256);
+    let (n, flags, _) = b
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+        .unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    assert!(!flags.is_control_truncated());
+    let cred = control.iter().find_map(|cmsg| cmsg.credentials()).unwrap();
+    assert_eq!(cred.pid(), Some(std::process::id() as libc::pid_t));
+    assert_eq!(cred.uid(), unsafe { libc::geteuid() });
+    assert_eq!(cred.gid(), unsafe { libc::getegid() });
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn recv_tos_and_ttl() {
+    use std::io::IoSliceMut;
+
+    use socket2::CmsgBuffer;
+    use util::any_local_ipv4_addr;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = socket.local_addr().unwrap();
+    assert!(!socket.recv_tos().unwrap());
+    assert!(!socket.recv_ttl().unwrap());
+    socket.set_recv_tos(true).unwrap();
+    socket.set_recv_ttl(true).unwrap();
+    assert!(socket.recv_tos().unwrap());
+    assert!(socket.recv_ttl().unwrap());
+
+    let sender = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    sender.set_tos(0x10).unwrap();
+    sender.set_ttl(42).unwrap();
+    sender.send_to(b"Hello", &addr).unwrap();
+
+    let mut buf = [0; 16];
+    let mut control = CmsgBuffer::with_capacity(2 * CmsgBuffer::space(4));
+    let (n, _, _) = socket
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+        .unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    let tos = control.iter().find_map(|cmsg| cmsg.tos());
+    assert_eq!(tos, Some(0x10));
+    let ttl = control.iter().find_map(|cmsg| cmsg.ttl());
+    assert_eq!(ttl, Some(42));
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn pktinfo() {
+    use std::io::{IoSlice, IoSliceMut};
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+    use socket2::{CmsgBuffer, PktInfo};
+    use util::any_local_ipv4_addr;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    let addr: SocketAddr = "0.0.0.0:0".parse().unwrap();
+    socket.bind(&addr.into()).unwrap();
+    let port = socket.local_addr().unwrap().as_std().unwrap().port();
+    assert!(!socket.recv_pktinfo().unwrap());
+    socket.set_recv_pktinfo(true).unwrap();
+    assert!(socket.recv_pktinfo().unwrap());
+
+    let sender = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    sender.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr: SocketAddr = (Ipv4Addr::LOCALHOST, port).into();
+    sender.send_to(b"Hello", &addr.into()).unwrap();
+
+    let mut buf = [0; 16];
+    let mut control = CmsgBuffer::with_capacity(64);
+    let (n, _, from) = socket
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+        .unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    let pktinfo = control.iter().find_map(|cmsg| cmsg.pktinfo()).unwrap();
+    assert_eq!(pktinfo.addr(), IpAddr::V4(Ipv4Addr::LOCALHOST));
+    assert_ne!(pktinfo.interface(), 0);
+
+    // Reply from the address the datagram was sent to.
+    let mut control = CmsgBuffer::new();
+    control.push_pktinfo(&PktInfo::new(pktinfo.addr(), 0));
+    let sent = socket.send_msg(&[IoSlice::new(b"World")], Some(&from), &control, 0);
+    assert_eq!(sent.unwrap(), 5);
+    let (n, from) = sender.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"World");
+    assert_eq!(from.as_std().unwrap(), addr);
+
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    assert!(!socket.recv_pktinfo_v6().unwrap());
+    socket.set_recv_pktinfo_v6(true).unwrap();
+    assert!(socket.recv_pktinfo_v6().unwrap());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn mtu_discover() {
+    use socket2::PmtudMode;
+    use util::any_local_ipv4_addr;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    for mode in &[PmtudMode::Dont, PmtudMode::Do, PmtudMode::Probe] {
+        socket.set_mtu_discover(*mode).unwrap();
+        assert_eq!(socket.mtu_discover().unwrap(), *mode);
+    }
+
+    let receiver = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    receiver.bind(&any_local_ipv4_addr().into()).unwrap();
+    socket.connect(&receiver.local_addr().unwrap()).unwrap();
+    assert!(socket.mtu().unwrap() > 0);
+
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    socket.set_mtu_discover_v6(PmtudMode::Do).unwrap();
+    assert_eq!(socket.mtu_discover_v6().unwrap(), PmtudMode::Do);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn ssm_v6() {
+    use std::net::Ipv6Addr;
+
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    let source = Ipv6Addr::LOCALHOST;
+    let group = Ipv6Addr::new(0xff3e, 0, 0, 0, 0, 0, 0, 0x1234);
+    match socket.join_ssm_v6(&source, &group, 0) {
+        Ok(()) => socket.leave_ssm_v6(&source, &group, 0).unwrap(),
+        // No IPv6 multicast route available.
+        Err(ref err) if err.raw_os_error() == Some(libc::ENODEV) => {}
+        Err(err) => panic!("unexpected error joining SSM channel: {}", err),
+    }
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn join_multicast() {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    let interface = unsafe { libc::if_nametoindex(b"lo\0".as_ptr().cast()) };
+    #[cfg(not(any(target_os = "android", target_os = "linux")))]
+    let interface = unsafe { libc::if_nametoindex(b"lo0\0".as_ptr().cast()) };
+    assert_ne!(interface, 0);
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    let group: SocketAddr = (Ipv4Addr::new(224, 0, 0, 251), 0).into();
+    socket.join_multicast(&group.into(), interface).unwrap();
+    socket.leave_multicast(&group.into(), interface).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn cookie() {
+    let socket1 = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let socket2 = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    let cookie = socket1.cookie().unwrap();
+    assert_ne!(cookie, 0);
+    assert_eq!(socket1.cookie().unwrap(), cookie);
+    assert_ne!(socket2.cookie().unwrap(), cookie);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn incoming_napi_id() {
+    use std::net::TcpListener;
+
+    use util::any_local_ipv4_addr;
+
+    let listener = TcpListener::bind(any_local_ipv4_addr()).unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket
+        .connect(&listener.local_addr().unwrap().into())
+        .unwrap();
+    // The loopback device doesn't use NAPI.
+    assert_eq!(socket.incoming_napi_id().unwrap(), 0);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn udp_segment_and_gro() {
+    use std::io::{IoSlice, IoSliceMut};
+
+    use socket2::CmsgBuffer;
+    use util::any_local_ipv4_addr;
+
+    let receiver = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    receiver.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = receiver.local_addr().unwrap();
+
+    let sender = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert_eq!(sender.udp_segment().unwrap(), 0);
+    sender.set_udp_segment(5).unwrap();
+    assert_eq!(sender.udp_segment().unwrap(), 5);
+
+    // Without GRO the receiver gets the individual segments.
+    assert_eq!(sender.send_to(b"HelloWorld", &addr).unwrap(), 10);
+    let mut buf = [0; 16];
+    let n = receiver.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    let n = receiver.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"World");
+
+    // With GRO the segments are received as a single buffer.
+    assert!(!receiver.udp_gro().unwrap());
+    receiver.set_udp_gro(true).unwrap();
+    assert!(receiver.udp_gro().unwrap());
+    sender.set_udp_segment(0).unwrap();
+    let mut control = CmsgBuffer::new();
+    control.push_udp_segment(3);
+    let sent = sender.send_msg(&[IoSlice::new(b"foobar")], Some(&addr), &control, 0);
+    assert_eq!(sent.unwrap(), 6);
+
+    let mut control = CmsgBuffer::with_capacity(64);
+    let (n, _, _) = receiver
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+        .unwrap();
+    assert_eq!(&buf[..n], b"foobar");
+    let size = control.iter().find_map(|cmsg| cmsg.udp_gro());
+    assert_eq!(size, Some(3));
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn buffer_size_force() {
+    use std::io;
+
+    // Larger than the default `net.core.{r,w}mem_max`.
+    const SIZE: usize = 16 * 1024 * 1024;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    match socket.set_recv_buffer_size_force(SIZE) {
+        // The kernel doubles the value.
+        Ok(()) => assert_eq!(socket.recv_buffer_size().unwrap(), 2 * SIZE),
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => {}
+        Err(err) => panic!("unexpected error setting SO_RCVBUFFORCE: {}", err),
+    }
+    match socket.set_send_buffer_size_force(SIZE) {
+        Ok(()) => assert_eq!(socket.send_buffer_size().unwrap(), 2 * SIZE),
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => {}
+        Err(err) => panic!("unexpected error setting SO_SNDBUFFORCE: {}", err),
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn max_pacing_rate() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_max_pacing_rate(1_000_000).unwrap();
+    assert_eq!(socket.max_pacing_rate().unwrap(), 1_000_000);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn txtime() {
+    use std::io::IoSlice;
+    use std::time::Duration;
+
+    use socket2::CmsgBuffer;
+    use util::any_local_ipv4_addr;
+
+    let receiver = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    receiver.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = receiver.local_addr().unwrap();
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.set_txtime(libc::CLOCK_MONOTONIC, 0).unwrap();
+    assert_eq!(socket.txtime().unwrap(), (libc::CLOCK_MONOTONIC, 0));
+
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    assert_eq!(
+        unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) },
+        0
+    );
+    let now = Duration::new(now.tv_sec as u64, now.tv_nsec as u32);
+
+    // Without a queueing discipline that supports it (e.g. `etf`) the time is
+    // ignored.
+    let mut control = CmsgBuffer::new();
+    control.push_txtime(now + Duration::from_millis(1));
+    let sent = socket.send_msg(&[IoSlice::new(b"Hello")], Some(&addr), &control, 0);
+    assert_eq!(sent.unwrap(), 5);
+
+    let mut buf = [0; 16];
+    let n = receiver.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "freebsd", target_os = "linux"))]
+fn orig_dst_addr() {
+    use std::io::IoSliceMut;
+    use std::net::SocketAddr;
+
+    use socket2::CmsgBuffer;
+
+    for (domain, addr) in [(Domain::IPV4, "127.0.0.1:0"), (Domain::IPV6, "[::1]:0")] {
+        let socket = Socket::new(domain, Type::DGRAM, None).unwrap();
+        socket
+            .bind(&addr.parse::<SocketAddr>().unwrap().into())
+            .unwrap();
+        if domain == Domain::IPV4 {
+            assert!(!socket.recv_orig_dst_addr().unwrap());
+            socket.set_recv_orig_dst_addr(true).unwrap();
+            assert!(socket.recv_orig_dst_addr().unwrap());
+        } else {
+            assert!(!socket.recv_orig_dst_addr_v6().unwrap());
+            socket.set_recv_orig_dst_addr_v6(true).unwrap();
+            assert!(socket.recv_orig_dst_addr_v6().unwrap());
+        }
+        let addr = socket.local_addr().unwrap();
+        socket.send_to(b"Hello", &addr).unwrap();
+
+        let mut buf = [0; 16];
+        let mut control = CmsgBuffer::with_capacity(64);
+        let (n, _, _) = socket
+            .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+            .unwrap();
+        assert_eq!(&buf[..n], b"Hello");
+        let orig_dst = control.iter().find_map(|cmsg| cmsg.orig_dst_addr());
+        assert_eq!(orig_dst, addr.as_std());
+    }
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos"
+))]
+fn recv_hoplimit_v6() {
+    use std::io::IoSliceMut;
+    use std::net::SocketAddr;
+
+    use socket2::CmsgBuffer;
+
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    let addr: SocketAddr = "[::1]:0".parse().unwrap();
+    socket.bind(&addr.into()).unwrap();
+    let addr = socket.local_addr().unwrap();
+    assert!(!socket.recv_hoplimit_v6().unwrap());
+    socket.set_recv_hoplimit_v6(true).unwrap();
+    assert!(socket.recv_hoplimit_v6().unwrap());
+
+    let sender = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    sender.set_unicast_hops_v6(42).unwrap();
+    sender.send_to(b"Hello", &addr).unwrap();
+
+    let mut buf = [0; 16];
+    let mut control = CmsgBuffer::with_capacity(CmsgBuffer::space(4));
+    let (n, _, _) = socket
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+        .unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    let hops = control.iter().find_map(|cmsg| cmsg.hop_limit());
+    assert_eq!(hops, Some(42));
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn multicast_all_v4() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert!(socket.multicast_all_v4().unwrap());
+    socket.set_multicast_all_v4(false).unwrap();
+    assert!(!socket.multicast_all_v4().unwrap());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn flow_label() {
+    use std::io::IoSliceMut;
+    use std::net::{Ipv6Addr, SocketAddrV6};
+
+    use socket2::CmsgBuffer;
+
+    let receiver = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    let addr = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 0, 0, 0);
+    receiver.bind(&addr.into()).unwrap();
+    let port = receiver.local_addr().unwrap().as_inet6().unwrap().port();
+    assert!(!receiver.recv_flowinfo().unwrap());
+    receiver.set_recv_flowinfo(true).unwrap();
+    assert!(receiver.recv_flowinfo().unwrap());
+
+    let sender = Socket::new(Domain::IPV6, Type::DGRAM, None).unwrap();
+    assert!(!sender.flowinfo_send().unwrap());
+    sender.set_flowinfo_send(true).unwrap();
+    assert!(sender.flowinfo_send().unwrap());
+    let label = sender.request_flow_label(&Ipv6Addr::LOCALHOST, 0).unwrap();
+    assert_ne!(label, 0);
+    assert_eq!(label & !0xfffff, 0);
+
+    let addr = SocketAddrV6::new(Ipv6Addr::LOCALHOST, port, label.to_be(), 0);
+    sender.send_to(b"Hello", &addr.into()).unwrap();
+
+    let mut buf = [0; 16];
+    let mut control = CmsgBuffer::with_capacity(64);
+    let (n, _, _) = receiver
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+        .unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    let flowinfo = control.iter().find_map(|cmsg| cmsg.flowinfo()).unwrap();
+    assert_eq!(u32::from_be(flowinfo) & 0xfffff, label);
+
+    sender.release_flow_label(label).unwrap();
+}
+
+#[test]
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn recv_interface() {
+    use std::io::IoSliceMut;
+
+    use socket2::CmsgBuffer;
+
+    use util::any_local_ipv4_addr;
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert!(!socket.recv_interface().unwrap());
+    socket.set_recv_interface(true).unwrap();
+    assert!(socket.recv_interface().unwrap());
+
+    socket.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = socket.local_addr().unwrap();
+    socket.send_to(b"Hello", &addr).unwrap();
+
+    let mut buf = [0; 16];
+    let mut control = CmsgBuffer::with_capacity(128);
+    let (n, flags, _) = socket
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, 0)
+        .unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    assert!(!flags.is_control_truncated());
+    let index = control
+        .iter()
+        .find_map(|cmsg| cmsg.recv_interface())
+        .unwrap();
+    assert_ne!(index, 0);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn port_range() {
+    use std::net::SocketAddr;
+
+    use socket2::PortRange;
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.port_range().unwrap(), PortRange::Default);
+    socket.set_port_range(PortRange::High).unwrap();
+    assert_eq!(socket.port_range().unwrap(), PortRange::High);
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    socket.bind(&addr.into()).unwrap();
+    let addr = socket.local_addr().unwrap().as_std().unwrap();
+    assert_ne!(addr.port(), 0);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "linux",
+    target_os = "macos",
+    target_os = "openbsd"
+))]
+fn cork() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.cork().unwrap());
+    socket.set_cork(true).unwrap();
+    assert!(socket.cork().unwrap());
+}
+
+#[test]
+#[cfg(any(
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "openbsd"
+))]
+fn nopush() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.nopush().unwrap());
+    socket.set_nopush(true).unwrap();
+    assert!(socket.nopush().unwrap());
+    socket.set_cork(false).unwrap();
+    assert!(!socket.nopush().unwrap());
+}
+
+#[test]
+#[cfg(any(target_os = "ios", target_os = "macos"))]
+fn bytes_readable() {
+    use std::net::SocketAddr;
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&addr.into()).unwrap();
+    assert_eq!(socket.bytes_readable().unwrap(), 0);
+    assert_eq!(socket.bytes_pending_write().unwrap(), 0);
+
+    let sender = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    let addr = socket.local_addr().unwrap();
+    sender.send_to(b"hello", &addr).unwrap();
+    let mut readable = 0;
+    for _ in 0..100 {
+        readable = socket.bytes_readable().unwrap();
+        if readable != 0 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    assert_eq!(readable, 5);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn original_dst() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    // Not connected, let alone redirected. Depending on whether or not
+    // connection tracking is loaded this returns `ENOENT` or `ENOPROTOOPT`.
+    assert!(socket.original_dst().is_err());
+
+    let socket = Socket::new(Domain::IPV6, Type::STREAM, None).unwrap();
+    assert!(socket.original_dst_ipv6().is_err());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn tcp_repair() {
+    use std::io;
+
+    use socket2::TcpRepairQueue;
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    match socket.set_tcp_repair(true) {
+        Ok(()) => {}
+        // Repair mode requires `CAP_NET_ADMIN`.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => return,
+        Err(err) => panic!("unexpected error setting repair mode: {}", err),
+    }
+    assert!(socket.tcp_repair().unwrap());
+
+    socket.set_tcp_repair_queue(TcpRepairQueue::Send).unwrap();
+    assert_eq!(socket.tcp_repair_queue().unwrap(), TcpRepairQueue::Send);
+    socket.set_tcp_queue_seq(1234).unwrap();
+    assert_eq!(socket.tcp_queue_seq().unwrap(), 1234);
+
+    socket.set_tcp_repair(false).unwrap();
+    assert!(!socket.tcp_repair().unwrap());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn peer_security_context() {
+    let (a, _b) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+    match a.peer_security_context() {
+        Ok(context) => assert!(!context.contains(&0)),
+        // No security module loaded.
+        Err(ref err) if err.raw_os_error() == Some(libc::ENOPROTOOPT) => {}
+        Err(err) => panic!("unexpected error getting peer security context: {}", err),
+    }
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn saved_syn() {
+    use std::net::SocketAddr;
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!listener.save_syn().unwrap());
+    listener.set_save_syn(true).unwrap();
+    assert!(listener.save_syn().unwrap());
+    listener.bind(&addr.into()).unwrap();
+    listener.listen(1).unwrap();
+
+    let client = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    client.connect(&listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+
+    let syn = server.saved_syn().unwrap();
+    // At least an IPv4 and TCP header.
+    assert!(syn.len() >= 40, "{:?}", syn);
+    assert_eq!(syn[0] >> 4, 4);
+    // Saved SYN is freed after the first read.
+    assert!(server.saved_syn().unwrap().is_empty());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn device_index() {
+    use std::io;
+    use std::num::NonZeroU32;
+
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert_eq!(socket.device_index().unwrap(), None);
+
+    let index = unsafe { libc::if_nametoindex(b"lo\0".as_ptr().cast()) };
+    let index = NonZeroU32::new(index).expect("no loopback interface");
+    match socket.bind_device_by_index(Some(index)) {
+        Ok(()) => {}
+        // Binding to a device requires `CAP_NET_RAW` on older kernels.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => return,
+        Err(err) => panic!("unexpected error binding device: {}", err),
+    }
+    assert_eq!(socket.device_index().unwrap(), Some(index));
+    assert_eq!(socket.device().unwrap().as_deref(), Some(&b"lo"[..]));
+
+    socket.bind_device_by_index(None).unwrap();
+    assert_eq!(socket.device_index().unwrap(), None);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn peer_groups() {
+    let mut expected = vec![0; 256];
+    let n = unsafe { libc::getgroups(expected.len() as libc::c_int, expected.as_mut_ptr()) };
+    assert!(n >= 0);
+    expected.truncate(n as usize);
+    expected.sort_unstable();
+
+    let (a, _b) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+    let mut groups = a.peer_groups().unwrap();
+    groups.sort_unstable();
+    assert_eq!(groups, expected);
+}
+
+#[test]
+#[cfg(not(target_os = "espidf"))]
+fn ioctl() {
+    use std::net::SocketAddr;
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.bind(&addr.into()).unwrap();
+    socket
+        .send_to(b"hello", &socket.local_addr().unwrap())
+        .unwrap();
+
+    let mut available: libc::c_int = 0;
+    unsafe { socket.ioctl(libc::FIONREAD as _, &mut available as *mut libc::c_int) }.unwrap();
+    assert_eq!(available, 5);
+}
+
+#[test]
+#[cfg(any(
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd"
+))]
+fn queue_size() {
+    use std::io::Write;
+    use std::net::{SocketAddr, TcpStream};
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener.bind(&addr.into()).unwrap();
+    listener.listen(1).unwrap();
+    let addr = listener.local_addr().unwrap().as_std().unwrap();
+
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let (socket, _) = listener.accept().unwrap();
+    assert_eq!(socket.send_queue_size().unwrap(), 0);
+    assert_eq!(socket.recv_queue_size().unwrap(), 0);
+
+    stream.write_all(b"hello").unwrap();
+    let mut size = 0;
+    for _ in 0..100 {
+        size = socket.recv_queue_size().unwrap();
+        if size != 0 {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    assert_eq!(size, 5);
+}
+
+#[test]
+#[cfg(not(target_os = "espidf"))]
+fn scm_rights() {
+    use std::io::{IoSlice, IoSliceMut};
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+
+    use socket2::{CmsgBuffer, ControlMessage, ControlMessageOwned};
+
+    let (a, b) = Socket::pair(Domain::UNIX, Type::DGRAM, None).unwrap();
+    let (c, d) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+
+    let mut control = CmsgBuffer::new();
+    control.push_message(ControlMessage::ScmRights(&[c.as_raw_fd()]));
+    let sent = a.send_msg(&[IoSlice::new(b"Hello")], None, &control, 0);
+    assert_eq!(sent.unwrap(), 5);
+    drop(c);
+
+    // Without `MSG_CMSG_CLOEXEC` the received file descriptors are not
+    // close-on-exec.
+    #[cfg(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "illumos",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    let recv_flags = libc::MSG_CMSG_CLOEXEC;
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "illumos",
+        target_os = "linux",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )))]
+    let recv_flags = 0;
+    let mut buf = [0; 16];
+    let mut control = CmsgBuffer::with_capacity(CmsgBuffer::space(4));
+    let (n, flags, _) = b
+        .recv_msg(&mut [IoSliceMut::new(&mut buf)], &mut control, recv_flags)
+        .unwrap();
+    assert_eq!(&buf[..n], b"Hello");
+    assert!(!flags.is_control_truncated());
+    let msgs = control
+        .iter()
+        .map(|cmsg| cmsg.to_message())
+        .collect::<Vec<_>>();
+    assert_eq!(msgs.len(), 1);
+    let fd = match msgs[0] {
+        ControlMessageOwned::ScmRights(ref fds) if fds.len() == 1 => fds[0],
+        ref msg => panic!("unexpected control message: {:?}", msg),
+    };
+    // The received file descriptor must refer to the same socket.
+    let c = unsafe { Socket::from_raw_fd(fd) };
+    assert_eq!(c.cloexec().unwrap(), recv_flags != 0);
+    c.send(b"World").unwrap();
+    let n = d.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"World");
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn recv_error() {
+    use std::io;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use util::any_local_ipv4_addr;
+
+    // Get an address no one is listening on.
+    let unused = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    unused.bind(&any_local_ipv4_addr().into()).unwrap();
+    let addr = unused.local_addr().unwrap();
+    drop(unused);
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    assert!(!socket.recv_err().unwrap());
+    socket.set_recv_err(true).unwrap();
+    assert!(socket.recv_err().unwrap());
+
+    let err = socket.recv_error().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    socket.connect(&addr).unwrap();
+    socket.send(b"Hello").unwrap();
+
+    let mut received = None;
+    for _ in 0..100 {
+        match socket.recv_error() {
+            Ok(err) => {
+                received = err;
+                break;
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                sleep(Duration::from_millis(10))
+            }
+            Err(err) => panic!("unexpected error: {}", err),
+        }
+    }
+    let err = received.expect("no error received from the error queue");
+    assert_eq!(err.errno(), libc::ECONNREFUSED as u32);
+    assert_eq!(err.error().kind(), io::ErrorKind::ConnectionRefused);
+    assert_eq!(err.origin(), libc::SO_EE_ORIGIN_ICMP);
+    assert!(err.is_icmp());
+    // Destination unreachable, port unreachable.
+    assert_eq!(err.icmp_type(), 3);
+    assert_eq!(err.icmp_code(), 3);
+    let offender = err.offender().unwrap();
+    assert_eq!(offender.ip(), addr.as_std().unwrap().ip());
+}
+
+#[test]
+#[cfg(not(any(target_os = "illumos", target_os = "solaris")))]
+fn reuse_port() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.reuse_port().unwrap());
+    socket.set_reuse_port(true).unwrap();
+    assert!(socket.reuse_port().unwrap());
+}
+
+#[test]
+#[cfg(any(target_os = "illumos", target_os = "solaris"))]
+fn exclusive_bind() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    assert!(!socket.exclusive_bind().unwrap());
+    socket.set_exclusive_bind(true).unwrap();
+    assert!(socket.exclusive_bind().unwrap());
+}
+
+#[test]
+fn sockaddr_unix() {
+    use socket2::SockAddr;
+
+    let path = temp_file("sockaddr_unix");
+    let addr = SockAddr::unix(&path).unwrap();
+
+    let listener = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    listener.bind(&addr).unwrap();
+    listener.listen(1).unwrap();
+
+    let client = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    client.connect(&addr).unwrap();
+    let (server, _) = listener.accept().unwrap();
+
+    assert_eq!(client.send(b"hello").unwrap(), 5);
+    let mut buf = [0; 5];
+    assert_eq!(server.recv(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+}
+
+#[test]
+fn sockaddr_unix_too_long() {
+    use socket2::SockAddr;
+
+    let path = "a".repeat(1024);
+    let err = SockAddr::unix(&path).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn sockaddr_unix_abstract() {
+    use socket2::SockAddr;
+
+    let name = format!("socket2_sockaddr_unix_abstract_{}", std::process::id());
+    let addr = SockAddr::unix_abstract(name.as_bytes()).unwrap();
+
+    let listener = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    listener.bind(&addr).unwrap();
+    listener.listen(1).unwrap();
+    let local_addr = listener.local_addr().unwrap();
+    assert_eq!(local_addr.len(), addr.len());
+
+    let client = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    client.connect(&addr).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    assert_eq!(client.peer_addr().unwrap().len(), addr.len());
+    assert_eq!(server.local_addr().unwrap().len(), addr.len());
+
+    assert_eq!(client.send(b"hello").unwrap(), 5);
+    let mut buf = [0; 5];
+    assert_eq!(server.recv(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn sockaddr_unix_leading_null() {
+    use socket2::SockAddr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let addr = SockAddr::unix("\0socket2").unwrap();
+    let expected = SockAddr::unix_abstract(b"socket2").unwrap();
+    assert_eq!(addr.len(), expected.len());
+    assert_eq!(addr.as_abstract_name(), Some(&b"socket2"[..]));
+
+    let name = [0; 108];
+    let path = std::ffi::OsStr::from_bytes(&name);
+    assert_eq!(
+        SockAddr::unix(path)
+            .unwrap()
+            .as_abstract_name()
+            .unwrap()
+            .len(),
+        107
+    );
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn sockaddr_unix_abstract_too_long() {
+    use socket2::SockAddr;
+
+    let name = [b'a'; 1024];
+    let err = SockAddr::unix_abstract(&name).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn sockaddr_unix_pathname() {
+    use socket2::SockAddr;
+
+    let path = temp_file("sockaddr_unix_pathname");
+    let addr = SockAddr::unix(&path).unwrap();
+    assert_eq!(addr.family(), libc::AF_UNIX as libc::sa_family_t);
+    assert!(addr.is_unix());
+    assert!(!addr.is_ipv4());
+    assert!(!addr.is_ipv6());
+    assert_eq!(addr.as_pathname(), Some(path.as_path()));
+    assert!(!addr.is_unnamed());
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert_eq!(addr.as_abstract_name(), None);
+
+    let socket = Socket::new(Domain::UNIX, Type::DGRAM, None).unwrap();
+    socket.bind(&addr).unwrap();
+    let local_addr = socket.local_addr().unwrap();
+    assert_eq!(local_addr.as_pathname(), Some(path.as_path()));
+    assert!(!local_addr.is_unnamed());
+}
+
+#[test]
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn sockaddr_unix_abstract_name() {
+    use socket2::SockAddr;
+
+    let name = format!("socket2_sockaddr_unix_abstract_name_{}", std::process::id());
+    let addr = SockAddr::unix_abstract(name.as_bytes()).unwrap();
+    assert_eq!(addr.as_abstract_name(), Some(name.as_bytes()));
+    assert_eq!(addr.as_pathname(), None);
+    assert!(!addr.is_unnamed());
+
+    let socket = Socket::new(Domain::UNIX, Type::DGRAM, None).unwrap();
+    socket.bind(&addr).unwrap();
+    let local_addr = socket.local_addr().unwrap();
+    assert_eq!(local_addr.as_abstract_name(), Some(name.as_bytes()));
+}
+
+#[test]
+fn sockaddr_unix_unnamed() {
+    let (a, _) = Socket::pair(Domain::UNIX, Type::STREAM, None).unwrap();
+    let addr = a.local_addr().unwrap();
+    assert!(addr.is_unnamed());
+    assert_eq!(addr.as_pathname(), None);
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    assert_eq!(addr.as_abstract_name(), None);
+
+    let addr = socket2::SockAddr::from("127.0.0.1:80".parse::<std::net::SocketAddr>().unwrap());
+    assert!(!addr.is_unix());
+    assert!(!addr.is_unnamed());
+    assert_eq!(addr.as_pathname(), None);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn sockaddr_packet() {
+    use std::io;
+
+    use socket2::SockAddr;
+
+    let mac = [0x02, 0x00, 0x00, 0x00, 0x00, 0x01];
+    let addr = SockAddr::packet(libc::ETH_P_ALL as u16, 1, &mac).unwrap();
+    assert!(addr.is_packet());
+    assert_eq!(addr.family(), libc::AF_PACKET as libc::sa_family_t);
+    assert_eq!(addr.packet_protocol(), Some(libc::ETH_P_ALL as u16));
+    assert_eq!(addr.packet_ifindex(), Some(1));
+    assert_eq!(addr.packet_hatype(), Some(0));
+    assert_eq!(addr.packet_addr(), Some(&mac[..]));
+    assert!(SockAddr::packet(0, 0, &[0; 9]).is_err());
+
+    let addr = SockAddr::from("127.0.0.1:80".parse::<std::net::SocketAddr>().unwrap());
+    assert!(!addr.is_packet());
+    assert_eq!(addr.packet_protocol(), None);
+    assert_eq!(addr.packet_addr(), None);
+
+    let protocol = (libc::ETH_P_ALL as u16).to_be();
+    let socket = match Socket::new(Domain::PACKET, Type::RAW, Some((protocol as i32).into())) {
+        Ok(socket) => socket,
+        // Packet sockets require `CAP_NET_RAW`.
+        Err(ref err) if err.kind() == io::ErrorKind::PermissionDenied => return,
+        Err(err) => panic!("unexpected error creating packet socket: {}", err),
+    };
+    let ifindex = socket2::interface::name_to_index("lo").unwrap();
+    let addr = SockAddr::packet(libc::ETH_P_ALL as u16, ifindex, &[]).unwrap();
+    socket.bind(&addr).unwrap();
+    let local_addr = socket.local_addr().unwrap();
+    assert_eq!(local_addr.packet_ifindex(), Some(ifindex));
+    assert_eq!(local_addr.packet_protocol(), Some(libc::ETH_P_ALL as u16));
+    assert_eq!(local_addr.packet_hatype(), Some(libc::ARPHRD_LOOPBACK));
+}
+
 // TODO: test accept4.
 // TODO: test pair.
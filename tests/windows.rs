@@ -0,0 +1,67 @@
+//! Tests for Windows only API.
+
+#![cfg(windows)]
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::net::TcpListener;
+
+use socket2::{Domain, Socket, Type};
+
+#[test]
+fn udp_connreset() {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, None).unwrap();
+    socket.set_udp_connreset(false).unwrap();
+    socket.set_udp_connreset(true).unwrap();
+}
+
+#[test]
+fn no_inherit() {
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket.set_no_inherit(false).unwrap();
+    assert!(!socket.no_inherit().unwrap());
+    socket.set_no_inherit(true).unwrap();
+    assert!(socket.no_inherit().unwrap());
+}
+
+#[test]
+fn pair() {
+    for domain in &[Domain::IPV4, Domain::IPV6] {
+        for type_ in &[Type::STREAM, Type::DGRAM] {
+            let (a, b) = Socket::pair(*domain, *type_, None).unwrap();
+            a.send(b"hello").unwrap();
+            let mut buf = [0; 16];
+            let n = b.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..n], b"hello");
+            b.send(b"world").unwrap();
+            let n = a.recv(&mut buf).unwrap();
+            assert_eq!(&buf[..n], b"world");
+        }
+    }
+}
+
+#[test]
+fn transmit_file() {
+    let path = std::env::temp_dir().join("socket2_transmit_file");
+    File::create(&path)
+        .and_then(|mut f| f.write_all(b"file"))
+        .unwrap();
+    let file = File::open(&path).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let socket = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    socket
+        .connect(&listener.local_addr().unwrap().into())
+        .unwrap();
+    let (mut stream, _) = listener.accept().unwrap();
+
+    socket
+        .transmit_file(&file, 0, Some(b"head "), Some(b" tail"), 0)
+        .unwrap();
+    drop(socket);
+
+    let mut buf = String::new();
+    stream.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "head file tail");
+    let _ = std::fs::remove_file(&path);
+}